@@ -9,7 +9,8 @@ use url::Url;
 use std::time::Duration;
 use tokio::time::timeout;
 use blake2::{Blake2b, Digest as Blake2Digest};
-use codec::{Decode, Encode};
+use codec::{Decode, Encode, Output};
+use scale::{DecodeError, PathSegment};
 
 // SCALE-encoded header structures for proper Polkadot header encoding
 #[derive(Debug, Encode, Decode)]
@@ -99,6 +100,7 @@ struct BabeNextConfig {
 }
 
 #[derive(Debug, Encode, Decode)]
+#[allow(clippy::enum_variant_names)]
 enum AllowedSlots {
     #[codec(index = 0)]
     PrimarySlots,
@@ -114,6 +116,30 @@ enum AuraLog {
     PreDigest(Vec<u8>),
 }
 
+/// SCALE output that feeds a Blake2b-256 hasher directly, so a header can be
+/// hashed while it is encoded, without an intermediate byte vector
+struct Blake2bOutput {
+    hasher: Blake2b<blake2::digest::consts::U32>,
+    len: usize,
+}
+
+impl Output for Blake2bOutput {
+    fn write(&mut self, bytes: &[u8]) {
+        self.hasher.update(bytes);
+        self.len += bytes.len();
+    }
+}
+
+/// Blake2b-256 hash of the SCALE-encoded header, and the encoded length
+fn hash_header(header: &Header) -> ([u8; 32], usize) {
+    let mut output = Blake2bOutput {
+        hasher: Blake2b::new(),
+        len: 0,
+    };
+    header.encode_to(&mut output);
+    (output.hasher.finalize().into(), output.len)
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("🔗 Polkadot Header Subscription Example");
@@ -283,8 +309,7 @@ fn display_header(header: &Value) {
 
 fn decode_parent_hash(parent_hash: &Value) {
     if let Some(hash_str) = parent_hash.as_str() {
-        if hash_str.starts_with("0x") {
-            let hex_part = &hash_str[2..];
+        if let Some(hex_part) = hash_str.strip_prefix("0x") {
             println!("  📋 Decoded Parent Hash:");
             println!("     - Hex: {}", hex_part);
             println!("     - Length: {} bytes ({} bits)", hex_part.len() / 2, hex_part.len() * 4);
@@ -296,8 +321,7 @@ fn decode_parent_hash(parent_hash: &Value) {
 
 fn decode_block_number(number: &Value) {
     if let Some(number_str) = number.as_str() {
-        if number_str.starts_with("0x") {
-            let hex_part = &number_str[2..];
+        if let Some(hex_part) = number_str.strip_prefix("0x") {
             if let Ok(block_num) = u64::from_str_radix(hex_part, 16) {
                 println!("  📋 Decoded Block Number:");
                 println!("     - Hex: {}", hex_part);
@@ -311,8 +335,7 @@ fn decode_block_number(number: &Value) {
 
 fn decode_state_root(state_root: &Value) {
     if let Some(root_str) = state_root.as_str() {
-        if root_str.starts_with("0x") {
-            let hex_part = &root_str[2..];
+        if let Some(hex_part) = root_str.strip_prefix("0x") {
             println!("  📋 Decoded State Root:");
             println!("     - Hex: {}", hex_part);
             println!("     - Length: {} bytes ({} bits)", hex_part.len() / 2, hex_part.len() * 4);
@@ -324,8 +347,7 @@ fn decode_state_root(state_root: &Value) {
 
 fn decode_extrinsics_root(extrinsics_root: &Value) {
     if let Some(root_str) = extrinsics_root.as_str() {
-        if root_str.starts_with("0x") {
-            let hex_part = &root_str[2..];
+        if let Some(hex_part) = root_str.strip_prefix("0x") {
             println!("  📋 Decoded Extrinsics Root:");
             println!("     - Hex: {}", hex_part);
             println!("     - Length: {} bytes ({} bits)", hex_part.len() / 2, hex_part.len() * 4);
@@ -343,8 +365,7 @@ fn decode_digest(digest: &Value) {
             
             for (i, log) in logs_array.iter().enumerate() {
                 if let Some(log_str) = log.as_str() {
-                    if log_str.starts_with("0x") {
-                        let hex_part = &log_str[2..];
+                    if let Some(hex_part) = log_str.strip_prefix("0x") {
                         println!("     - Log {}: {}", i + 1, log_str);
                         decode_digest_log(hex_part, i + 1);
                     }
@@ -405,19 +426,15 @@ fn compute_header_hash(header: &Value) {
         }
     };
     
-    // Encode header using SCALE
-    let encoded_header = scale_header.encode();
-    
-    // Compute Blake2b-256 hash of SCALE-encoded header
-    let mut hasher = Blake2b::<blake2::digest::consts::U32>::new();
-    hasher.update(&encoded_header);
-    let result = hasher.finalize();
+    // Compute Blake2b-256 hash of the SCALE-encoded header, encoding
+    // straight into the hasher
+    let (hash, encoded_len) = hash_header(&scale_header);
     
     // Convert to hex string
-    let hash_hex = format!("0x{}", hex::encode(result));
+    let hash_hex = format!("0x{}", hex::encode(hash));
     
     println!("  📋 Header Hash Computation:");
-    println!("     - SCALE encoded length: {} bytes", encoded_header.len());
+    println!("     - SCALE encoded length: {} bytes", encoded_len);
     println!("     - Hash algorithm: Blake2b-256");
     println!("     - Computed hash: {}", hash_hex);
     println!("     - Note: This uses proper SCALE encoding as used by Polkadot");
@@ -431,36 +448,48 @@ fn compute_header_hash(header: &Value) {
     println!("     - Digest logs: {} items", scale_header.digest.logs.len());
 }
 
-fn parse_header_to_scale(header: &Value) -> Result<Header, Box<dyn std::error::Error>> {
+/// Decodes a hex value as JSON-RPC sends it, with a `0x` prefix. Slicing
+/// the prefix off with `[2..]` instead would panic on strings shorter than
+/// two bytes or starting with a multi-byte character.
+fn hex_bytes(value: &str) -> Result<Vec<u8>, DecodeError> {
+    let digits = value.strip_prefix("0x").ok_or("Missing 0x prefix")?;
+    Ok(scale::from_hex(digits).ok_or("Invalid hex")?)
+}
+
+/// Decodes the bytes of a 32-byte hash field of the header, locating a
+/// wrong length at e.g. `Header.state_root`.
+fn decode_hash(bytes: &[u8], field: &'static str) -> Result<[u8; 32], DecodeError> {
+    <[u8; 32] as scale::Decode>::decode_all(bytes).map_err(|e| {
+        let offset = e.offset().unwrap_or(0);
+        e.within(offset, "[u8; 32]", "Header", vec![PathSegment::Field(field)])
+    })
+}
+
+fn parse_header_to_scale(header: &Value) -> Result<Header, DecodeError> {
     // Parse parent hash
     let parent_hash_str = header.get("parentHash")
         .and_then(|v| v.as_str())
         .ok_or("Missing parentHash")?;
-    let parent_hash = hex::decode(&parent_hash_str[2..])?;
-    let parent_hash: [u8; 32] = parent_hash.try_into()
-        .map_err(|_| "Invalid parent hash length")?;
+    let parent_hash = decode_hash(&hex_bytes(parent_hash_str)?, "parent_hash")?;
     
     // Parse block number
     let number_str = header.get("number")
         .and_then(|v| v.as_str())
         .ok_or("Missing number")?;
-    let number = u32::from_str_radix(&number_str[2..], 16)?;
+    let number_hex = number_str.strip_prefix("0x").ok_or("Missing 0x prefix")?;
+    let number = u32::from_str_radix(number_hex, 16).map_err(|_| "Invalid block number")?;
     
     // Parse state root
     let state_root_str = header.get("stateRoot")
         .and_then(|v| v.as_str())
         .ok_or("Missing stateRoot")?;
-    let state_root = hex::decode(&state_root_str[2..])?;
-    let state_root: [u8; 32] = state_root.try_into()
-        .map_err(|_| "Invalid state root length")?;
+    let state_root = decode_hash(&hex_bytes(state_root_str)?, "state_root")?;
     
     // Parse extrinsics root
     let extrinsics_root_str = header.get("extrinsicsRoot")
         .and_then(|v| v.as_str())
         .ok_or("Missing extrinsicsRoot")?;
-    let extrinsics_root = hex::decode(&extrinsics_root_str[2..])?;
-    let extrinsics_root: [u8; 32] = extrinsics_root.try_into()
-        .map_err(|_| "Invalid extrinsics root length")?;
+    let extrinsics_root = decode_hash(&hex_bytes(extrinsics_root_str)?, "extrinsics_root")?;
     
     // Parse digest
     let digest = parse_digest(header.get("digest").ok_or("Missing digest")?)?;
@@ -474,7 +503,7 @@ fn parse_header_to_scale(header: &Value) -> Result<Header, Box<dyn std::error::E
     })
 }
 
-fn parse_digest(digest: &Value) -> Result<HeaderDigest, Box<dyn std::error::Error>> {
+fn parse_digest(digest: &Value) -> Result<HeaderDigest, DecodeError> {
     let logs_array = digest.get("logs")
         .and_then(|v| v.as_array())
         .ok_or("Missing logs in digest")?;
@@ -483,7 +512,7 @@ fn parse_digest(digest: &Value) -> Result<HeaderDigest, Box<dyn std::error::Erro
     
     for log in logs_array {
         let log_str = log.as_str().ok_or("Log is not a string")?;
-        let log_bytes = hex::decode(&log_str[2..])?;
+        let log_bytes = hex_bytes(log_str)?;
         
         // Parse digest item based on the first byte (consensus engine ID)
         if log_bytes.is_empty() {
@@ -563,4 +592,64 @@ mod tests {
         // Test that header hash computation doesn't panic
         compute_header_hash(&test_header);
     }
+
+    #[test]
+    fn test_streamed_hash_matches_buffered_encoding() {
+        let test_header = json!({
+            "parentHash": "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef",
+            "number": "0x1234567",
+            "stateRoot": "0xabcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890",
+            "extrinsicsRoot": "0x9876543210fedcba9876543210fedcba9876543210fedcba9876543210fedcba",
+            "digest": {
+                "logs": ["0x0642414245b5010100000000", "0x05424142450101"]
+            }
+        });
+        let header = parse_header_to_scale(&test_header).unwrap();
+
+        let encoded = header.encode();
+        let buffered: [u8; 32] = Blake2b::<blake2::digest::consts::U32>::digest(&encoded).into();
+        assert_eq!(hash_header(&header), (buffered, encoded.len()));
+    }
+
+    #[test]
+    fn test_malformed_headers_are_errors() {
+        let valid = json!({
+            "parentHash": "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef",
+            "number": "0x1234567",
+            "stateRoot": "0xabcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890",
+            "extrinsicsRoot": "0x9876543210fedcba9876543210fedcba9876543210fedcba9876543210fedcba",
+            "digest": { "logs": ["0x05424142450101"] }
+        });
+        assert!(parse_header_to_scale(&valid).is_ok());
+
+        // Short, unprefixed, mistyped and out-of-range values are errors.
+        for (field, value) in [
+            ("parentHash", json!("")),
+            ("parentHash", json!("0")),
+            ("number", json!("é")),
+            ("number", json!("0x100000000")),
+            ("stateRoot", json!("1234")),
+            ("extrinsicsRoot", json!(42)),
+            ("digest", json!({ "logs": ["x"] })),
+            ("digest", json!({ "logs": [7] })),
+        ] {
+            let mut header = valid.clone();
+            header[field] = value;
+            assert!(parse_header_to_scale(&header).is_err(), "{} = {}", field, header[field]);
+        }
+    }
+
+    #[test]
+    fn test_header_fuzz_corpus() {
+        // Replays the seeds of the `header` fuzz target (see `fuzz/`).
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../fuzz/corpus/header");
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let bytes = std::fs::read(entry.unwrap().path()).unwrap();
+            for len in 0..=bytes.len() {
+                if let Ok(header) = serde_json::from_slice::<Value>(&bytes[..len]) {
+                    let _ = parse_header_to_scale(&header);
+                }
+            }
+        }
+    }
 }
@@ -16,4 +16,44 @@ fn main() {
     // Convert to native-endian bytes (matches your CPU)
     let ne = num.to_ne_bytes();
     println!("Native-endian bytes: {:x?}", ne);
+
+    // Signed integers are stored in two's complement, so -2 is all 0xff
+    // bytes except the lowest one
+    let signed: i32 = -2;
+    println!("i32 -2 little-endian: {:x?}", signed.to_le_bytes()); // [fe, ff, ff, ff]
+    println!("i8 -1 little-endian: {:x?}", (-1i8).to_le_bytes()); // [ff]
+    println!("i16::MIN little-endian: {:x?}", i16::MIN.to_le_bytes()); // [0, 80]
+
+    // 128-bit integers work the same way, just with 16 bytes
+    let wide: u128 = 0x0102030405060708090a0b0c0d0e0f10;
+    println!("u128 little-endian: {:x?}", wide.to_le_bytes());
+    println!("i128::MIN little-endian: {:x?}", i128::MIN.to_le_bytes());
+
+    // Decoding goes the other way with from_le_bytes. It needs an array of
+    // exactly the right size, so check the length of incoming bytes first
+    // instead of indexing blindly
+    let bytes = [0xfe, 0xff, 0xff, 0xff];
+    match decode_i32_le(&bytes) {
+        Ok(value) => println!("Decoded i32: {}", value), // -2
+        Err(e) => println!("Error: {}", e),
+    }
+    match decode_i32_le(&bytes[..3]) {
+        Ok(value) => println!("Decoded i32: {}", value),
+        Err(e) => println!("Error: {}", e), // only 3 bytes
+    }
+
+    // Narrowing a decoded value into a smaller type is checked too
+    let decoded = i64::from_le_bytes((-300i64).to_le_bytes());
+    match i8::try_from(decoded) {
+        Ok(value) => println!("Fits in i8: {}", value),
+        Err(_) => println!("{} does not fit in i8", decoded),
+    }
+}
+
+/// Decodes a little-endian i32 from exactly 4 bytes.
+fn decode_i32_le(bytes: &[u8]) -> Result<i32, String> {
+    let array: [u8; 4] = bytes
+        .try_into()
+        .map_err(|_| format!("Expected 4 bytes, got {}", bytes.len()))?;
+    Ok(i32::from_le_bytes(array))
 }
@@ -14,33 +14,35 @@
 // - 0b11: Variable bytes, for values 1073741824 to 2^536 - 1
 //   Remaining 6 bits indicate length (+4): [LLLLLL11, VVVVVVVV, ...]
 
+use scale::DecodeError;
+
 /// Encodes a u128 integer into SCALE Compact bytes.
 /// Supports the full range up to u128 (subset of the full 2^536 range).
 fn encode_compact(value: u128) -> Vec<u8> {
     if value <= 63 {
         // 0b00 case: 1 byte
         // Value fits in 6 bits, tag is 00
-        vec![((value as u8) << 2) | 0b00]
+        vec![(value as u8) << 2]
     } else if value <= 16383 {
         // 0b01 case: 2 bytes
         // Value needs 14 bits, tag is 01
-        let mut bytes = Vec::with_capacity(2);
-        // First byte: 6 LSBs of value + 0b01 tag
-        bytes.push(((value & 0x3F) as u8) << 2 | 0b01);
-        // Second byte: next 8 bits of value
-        bytes.push(((value >> 6) & 0xFF) as u8);
-        bytes
+        vec![
+            // First byte: 6 LSBs of value + 0b01 tag
+            ((value & 0x3F) as u8) << 2 | 0b01,
+            // Second byte: next 8 bits of value
+            ((value >> 6) & 0xFF) as u8,
+        ]
     } else if value <= 1073741823 {
         // 0b10 case: 4 bytes
         // Value needs 30 bits, tag is 10
-        let mut bytes = Vec::with_capacity(4);
-        // First byte: 6 LSBs of value + 0b10 tag
-        bytes.push(((value & 0x3F) as u8) << 2 | 0b10);
-        // Subsequent bytes: next 8 bits each
-        bytes.push(((value >> 6) & 0xFF) as u8);
-        bytes.push(((value >> 14) & 0xFF) as u8);
-        bytes.push(((value >> 22) & 0xFF) as u8);
-        bytes
+        vec![
+            // First byte: 6 LSBs of value + 0b10 tag
+            ((value & 0x3F) as u8) << 2 | 0b10,
+            // Subsequent bytes: next 8 bits each
+            ((value >> 6) & 0xFF) as u8,
+            ((value >> 14) & 0xFF) as u8,
+            ((value >> 22) & 0xFF) as u8,
+        ]
     } else {
         // 0b11 case: Variable bytes
         // For u128, max bytes needed for value is 16
@@ -69,10 +71,46 @@ fn encode_compact(value: u128) -> Vec<u8> {
     }
 }
 
+/// Number of bytes `encode_compact(value)` produces, worked out from the
+/// mode boundaries alone so buffers can be sized before encoding.
+fn compact_len(value: u128) -> usize {
+    if value <= 63 {
+        1
+    } else if value <= 16383 {
+        2
+    } else if value <= 1073741823 {
+        4
+    } else {
+        // 0b11 case: the length byte, then the value bytes without
+        // trailing zeros (never fewer than 4)
+        let value_bytes = 16 - (value.leading_zeros() / 8) as usize;
+        1 + value_bytes.max(4)
+    }
+}
+
+/// Controls whether non-canonical (non-minimal) encodings are accepted.
+/// For example, the value 1 can be written as [0x04] (canonical) but also
+/// as [0x05, 0x00] in the 2-byte mode or [0x06, 0x00, 0x00, 0x00] in the
+/// 4-byte mode.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DecodeMode {
+    /// Reject non-minimal encodings, as the node does for untrusted data.
+    Strict,
+    /// Accept any well-formed encoding.
+    Lenient,
+}
+
 /// Decodes SCALE Compact bytes into a u128 integer.
-fn decode_compact(bytes: &[u8]) -> Result<u128, &'static str> {
+/// Errors are located like the `scale` crate's: at the byte that could not
+/// be read or that holds the offending value.
+fn decode_compact(bytes: &[u8], mode: DecodeMode) -> Result<u128, DecodeError> {
+    decode_compact_as(bytes, mode, "Compact<u128>")
+}
+
+/// `decode_compact`, with errors located in the type named `expected`.
+fn decode_compact_as(bytes: &[u8], mode: DecodeMode, expected: &str) -> Result<u128, DecodeError> {
     if bytes.is_empty() {
-        return Err("Input bytes cannot be empty");
+        return Err(DecodeError::NotEnoughData { expected: 1, remaining: 0 }.at(0, expected));
     }
 
     let first_byte = bytes[0];
@@ -87,20 +125,28 @@ fn decode_compact(bytes: &[u8]) -> Result<u128, &'static str> {
         0b01 => {
             // 2 bytes encoding
             if bytes.len() < 2 {
-                return Err("Not enough bytes for 0b01 encoding");
+                return Err(not_enough_bytes(bytes, 2, expected));
             }
             let value = initial_value_part | ((bytes[1] as u128) << 6);
+            if mode == DecodeMode::Strict && value <= 63 {
+                // Non-canonical: the value fits in the 0b00 mode
+                return Err(DecodeError::NonCanonicalCompact.at(1, expected));
+            }
             Ok(value)
         }
         0b10 => {
             // 4 bytes encoding
             if bytes.len() < 4 {
-                return Err("Not enough bytes for 0b10 encoding");
+                return Err(not_enough_bytes(bytes, 4, expected));
             }
             let value = initial_value_part
                 | ((bytes[1] as u128) << 6)
                 | ((bytes[2] as u128) << 14)
                 | ((bytes[3] as u128) << 22);
+            if mode == DecodeMode::Strict && value <= 16383 {
+                // Non-canonical: the value fits in a smaller mode
+                return Err(DecodeError::NonCanonicalCompact.at(1, expected));
+            }
             Ok(value)
         }
         0b11 => {
@@ -108,21 +154,112 @@ fn decode_compact(bytes: &[u8]) -> Result<u128, &'static str> {
             let length_indicator = initial_value_part as usize;
             let num_value_bytes = length_indicator + 4;
             if bytes.len() < 1 + num_value_bytes {
-                return Err("Not enough bytes for 0b11 encoding");
+                return Err(not_enough_bytes(bytes, 1 + num_value_bytes, expected));
+            }
+
+            if mode == DecodeMode::Strict {
+                check_canonical_big_mode(&bytes[1..1 + num_value_bytes])
+                    .map_err(|e| e.at(1, expected))?;
             }
 
-            let mut value_bytes = [0u8; 16]; // Max u128 needs 16 bytes
-            if num_value_bytes > 16 {
-                return Err("Decoded value exceeds u128 capacity");
+            // Max u128 needs 16 bytes; extra bytes are only allowed if they are zero
+            let significant = bytes[1..1 + num_value_bytes]
+                .iter()
+                .rposition(|&b| b != 0)
+                .map_or(0, |i| i + 1);
+            if significant > 16 {
+                return Err(DecodeError::CompactOverflow { type_name: "u128" }.at(1, expected));
             }
-            value_bytes[..num_value_bytes].copy_from_slice(&bytes[1..1 + num_value_bytes]);
+            let mut value_bytes = [0u8; 16];
+            value_bytes[..significant].copy_from_slice(&bytes[1..1 + significant]);
 
             Ok(u128::from_le_bytes(value_bytes))
         }
-        _ => Err("Invalid SCALE Compact tag"),
+        _ => unreachable!("the tag is two bits"),
     }
 }
 
+/// The error for an encoding of `len` bytes cut short: the bytes after the
+/// first one are read at once, so that read is the one that fails.
+fn not_enough_bytes(bytes: &[u8], len: usize, expected: &str) -> DecodeError {
+    DecodeError::NotEnoughData { expected: len - 1, remaining: bytes.len() - 1 }.at(1, expected)
+}
+
+/// Checks that the value bytes of a 0b11 encoding are minimal:
+/// the most significant byte must be non-zero, and a 4-byte value must be
+/// too large for the 0b10 mode.
+fn check_canonical_big_mode(value_bytes: &[u8]) -> Result<(), DecodeError> {
+    if value_bytes[value_bytes.len() - 1] == 0 {
+        // Non-canonical: the 0b11 mode with unused value bytes
+        return Err(DecodeError::NonCanonicalCompact);
+    }
+    if value_bytes.len() == 4 && value_bytes[3] < 0x40 {
+        // Non-canonical: the value fits in a smaller mode
+        return Err(DecodeError::NonCanonicalCompact);
+    }
+    Ok(())
+}
+
+/// Encodes an unsigned integer of any size into SCALE Compact bytes.
+/// The value is given as little-endian bytes, so this covers the full
+/// 0b11 range up to 2^536 - 1 (67 value bytes), e.g. U256 balances.
+fn encode_compact_big(value_le: &[u8]) -> Result<Vec<u8>, &'static str> {
+    // Drop the most significant zero bytes
+    let significant = value_le.iter().rposition(|&b| b != 0).map_or(0, |i| i + 1);
+    let value_bytes = &value_le[..significant];
+
+    if value_bytes.len() <= 16 {
+        let mut buf = [0u8; 16];
+        buf[..value_bytes.len()].copy_from_slice(value_bytes);
+        let value = u128::from_le_bytes(buf);
+        if value <= 1073741823 {
+            // Small values use the 0b00, 0b01 and 0b10 modes
+            return Ok(encode_compact(value));
+        }
+    }
+
+    if value_bytes.len() > 67 {
+        return Err("Value too large for SCALE Compact encoding (max 2^536 - 1)");
+    }
+
+    // 0b11 mode always carries at least 4 value bytes
+    let num_value_bytes = value_bytes.len().max(4);
+    let mut encoded_bytes = Vec::with_capacity(1 + num_value_bytes);
+    encoded_bytes.push((((num_value_bytes - 4) as u8) << 2) | 0b11);
+    encoded_bytes.extend_from_slice(value_bytes);
+    encoded_bytes.resize(1 + num_value_bytes, 0);
+    Ok(encoded_bytes)
+}
+
+/// Decodes SCALE Compact bytes of any size into little-endian value bytes
+/// (without the most significant zero bytes).
+fn decode_compact_big(bytes: &[u8], mode: DecodeMode) -> Result<Vec<u8>, DecodeError> {
+    const EXPECTED: &str = "CompactBig";
+    if bytes.is_empty() {
+        return Err(DecodeError::NotEnoughData { expected: 1, remaining: 0 }.at(0, EXPECTED));
+    }
+
+    if bytes[0] & 0b11 != 0b11 {
+        let value = decode_compact_as(bytes, mode, EXPECTED)?;
+        let value_bytes = value.to_le_bytes();
+        let significant = value_bytes.iter().rposition(|&b| b != 0).map_or(0, |i| i + 1);
+        return Ok(value_bytes[..significant].to_vec());
+    }
+
+    let num_value_bytes = (bytes[0] >> 2) as usize + 4;
+    if bytes.len() < 1 + num_value_bytes {
+        return Err(not_enough_bytes(bytes, 1 + num_value_bytes, EXPECTED));
+    }
+    if mode == DecodeMode::Strict {
+        check_canonical_big_mode(&bytes[1..1 + num_value_bytes]).map_err(|e| e.at(1, EXPECTED))?;
+    }
+    let mut value_bytes = bytes[1..1 + num_value_bytes].to_vec();
+    while value_bytes.last() == Some(&0) {
+        value_bytes.pop();
+    }
+    Ok(value_bytes)
+}
+
 /// Helper function to print binary representation
 fn print_binary(bytes: &[u8]) {
     for (i, &byte) in bytes.iter().enumerate() {
@@ -163,10 +300,12 @@ fn main() {
         
         let encoded = encode_compact(value);
         println!("Encoded bytes: {:?} ({} bytes)", encoded, encoded.len());
+        // The size is known up front, without encoding
+        assert_eq!(compact_len(value), encoded.len());
         print!("Binary representation: ");
         print_binary(&encoded);
         
-        let decoded = decode_compact(&encoded);
+        let decoded = decode_compact(&encoded, DecodeMode::Strict);
         match decoded {
             Ok(d_value) => {
                 if d_value == value {
@@ -195,13 +334,76 @@ fn main() {
     for (bytes, description) in error_cases {
         println!("--- {} ---", description);
         println!("Input: {:?}", bytes);
-        match decode_compact(&bytes) {
+        match decode_compact(&bytes, DecodeMode::Strict) {
             Ok(value) => println!("Unexpected success: {}", value),
             Err(e) => println!("✅ Expected error: {}", e),
         }
         println!();
     }
 
+    println!("=== Canonical Encoding Examples ===");
+
+    // Well-formed but non-minimal encodings: Strict rejects, Lenient accepts
+    let non_canonical_cases = vec![
+        (vec![0x05, 0x00], "1 in the 0b01 mode (canonical: [0x04])"),
+        (vec![0x06, 0x00, 0x00, 0x00], "1 in the 0b10 mode (canonical: [0x04])"),
+        (vec![0x02, 0x01, 0x00, 0x00], "64 in the 0b10 mode (canonical: [0x01, 0x01])"),
+        (vec![0x03, 0xff, 0xff, 0xff, 0x3f], "2^30 - 1 in the 0b11 mode"),
+        (vec![0x07, 0x00, 0x00, 0x00, 0x40, 0x00], "2^30 with a trailing zero byte"),
+    ];
+
+    for (bytes, description) in non_canonical_cases {
+        println!("--- {} ---", description);
+        println!("Input: {:?}", bytes);
+        match decode_compact(&bytes, DecodeMode::Strict) {
+            Ok(value) => println!("❌ Strict: unexpected success: {}", value),
+            Err(e) => println!("✅ Strict: {}", e),
+        }
+        match decode_compact(&bytes, DecodeMode::Lenient) {
+            Ok(value) => println!("✅ Lenient: {}", value),
+            Err(e) => println!("❌ Lenient: unexpected error: {}", e),
+        }
+        println!();
+    }
+
+    println!("=== Big Integer Examples (beyond u128) ===");
+
+    // Values given as little-endian bytes
+    let mut two_pow_128 = vec![0u8; 17];
+    two_pow_128[16] = 0x01;
+    let big_values = vec![
+        (two_pow_128, "2^128: smallest value that needs 17 value bytes"),
+        (vec![0xff; 32], "2^256 - 1: maximum U256 value (32 value bytes)"),
+        (vec![0xff; 67], "2^536 - 1: maximum compact value (67 value bytes)"),
+    ];
+
+    for (value_le, description) in big_values {
+        println!("--- {} ---", description);
+        match encode_compact_big(&value_le) {
+            Ok(encoded) => {
+                println!("Encoded length: {} bytes", encoded.len());
+                print!("First byte: ");
+                print_binary(&encoded[..1]);
+                match decode_compact_big(&encoded, DecodeMode::Strict) {
+                    Ok(decoded) if decoded == value_le => println!("✅ Decoded value bytes match"),
+                    Ok(_) => println!("❌ Decoded value bytes MISMATCH"),
+                    Err(e) => println!("❌ Decoding Error: {}", e),
+                }
+            }
+            Err(e) => println!("❌ Encoding Error: {}", e),
+        }
+        println!();
+    }
+
+    println!("--- 2^536: one more than the maximum ---");
+    let mut too_big = vec![0u8; 68];
+    too_big[67] = 0x01;
+    match encode_compact_big(&too_big) {
+        Ok(_) => println!("Unexpected success"),
+        Err(e) => println!("✅ Expected error: {}", e),
+    }
+    println!();
+
     println!("=== Encoding Analysis ===");
     println!("This demonstrates how SCALE Compact optimizes storage:");
     println!("- Small values (0-63): 1 byte (vs 8 bytes for u64)");
@@ -209,3 +411,96 @@ fn main() {
     println!("- Large values (16384-1073741823): 4 bytes (vs 8 bytes for u64)");
     println!("- Very large values: Variable bytes (efficient for any size)");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Both sides of every compact mode boundary, plus the 0b11 extremes.
+    const BOUNDARIES: [(u128, usize); 10] = [
+        (0, 1),
+        (63, 1),
+        (64, 2),
+        (16_383, 2),
+        (16_384, 4),
+        (1_073_741_823, 4),
+        (1_073_741_824, 5),
+        ((1 << 32) - 1, 5),
+        (1 << 32, 6),
+        (u128::MAX, 17),
+    ];
+
+    #[test]
+    fn test_compact_mode_boundaries() {
+        for (value, expected_len) in BOUNDARIES {
+            let encoded = encode_compact(value);
+            assert_eq!(encoded.len(), expected_len, "length for {}", value);
+            assert_eq!(decode_compact(&encoded, DecodeMode::Strict), Ok(value), "round trip for {}", value);
+            // The big-integer codec agrees wherever the ranges overlap
+            assert_eq!(encode_compact_big(&value.to_le_bytes()), Ok(encoded));
+        }
+    }
+
+    #[test]
+    fn test_strict_rejects_non_canonical() {
+        let cases: Vec<(Vec<u8>, u128)> = vec![
+            // 2-byte mode for a 1-byte value
+            (vec![0x05, 0x00], 1),
+            // 4-byte mode for a 1-byte and a 2-byte value
+            (vec![0x06, 0x00, 0x00, 0x00], 1),
+            (vec![0x02, 0x01, 0x00, 0x00], 64),
+            // 0b11 mode for a 4-byte value
+            (vec![0x03, 0xff, 0xff, 0xff, 0x3f], 1_073_741_823),
+            // 0b11 mode with a trailing zero byte
+            (vec![0x07, 0x00, 0x00, 0x00, 0x40, 0x00], 1_073_741_824),
+        ];
+        for (bytes, value) in cases {
+            let error = decode_compact(&bytes, DecodeMode::Strict).unwrap_err();
+            assert_eq!(error.kind(), &DecodeError::NonCanonicalCompact, "strict {:?}", bytes);
+            assert_eq!(decode_compact(&bytes, DecodeMode::Lenient), Ok(value), "lenient {:?}", bytes);
+
+            let significant = value.to_le_bytes().iter().rposition(|&b| b != 0).map_or(0, |i| i + 1);
+            assert!(decode_compact_big(&bytes, DecodeMode::Strict).is_err(), "strict big {:?}", bytes);
+            assert_eq!(
+                decode_compact_big(&bytes, DecodeMode::Lenient),
+                Ok(value.to_le_bytes()[..significant].to_vec())
+            );
+        }
+    }
+
+    #[test]
+    fn test_compact_big_round_trip() {
+        // The largest value: 67 bytes of 0xff, with all 6 length bits set
+        let max = vec![0xff; 67];
+        let encoded = encode_compact_big(&max).unwrap();
+        assert_eq!(encoded.len(), 68);
+        assert_eq!(encoded[0], 0xff);
+        assert_eq!(decode_compact_big(&encoded, DecodeMode::Strict), Ok(max));
+
+        // Most significant zero bytes of the input are dropped
+        let mut padded = vec![0x01, 0x00, 0x00, 0x00, 0x01];
+        padded.resize(80, 0);
+        let encoded = encode_compact_big(&padded).unwrap();
+        assert_eq!(encoded, vec![0x07, 0x01, 0x00, 0x00, 0x00, 0x01]);
+        assert_eq!(decode_compact_big(&encoded, DecodeMode::Strict), Ok(padded[..5].to_vec()));
+
+        assert_eq!(encode_compact_big(&[]), Ok(vec![0x00]));
+    }
+
+    #[test]
+    fn test_compact_big_rejects_values_over_2_pow_536() {
+        let mut too_big = vec![0u8; 68];
+        too_big[67] = 0x01;
+        assert!(encode_compact_big(&too_big).is_err());
+    }
+
+    #[test]
+    fn test_compact_rejects_u128_overflow() {
+        // 17 value bytes fit a CompactBig but not a u128
+        let mut bytes = vec![(13 << 2) | 0b11];
+        bytes.extend_from_slice(&[0xff; 17]);
+        let error = decode_compact(&bytes, DecodeMode::Lenient).unwrap_err();
+        assert_eq!(error.kind(), &DecodeError::CompactOverflow { type_name: "u128" });
+        assert_eq!(decode_compact_big(&bytes, DecodeMode::Strict), Ok(vec![0xff; 17]));
+    }
+}
@@ -3,7 +3,7 @@
 // for Enum types, where the first byte indicates the variant (tag)
 // and subsequent bytes hold the associated data, if any.
 
-use std::fmt;
+use scale::{DecodeError, PathSegment};
 
 // Define an Enum similar to the one in the image
 #[derive(Debug, PartialEq)]
@@ -42,9 +42,12 @@ impl MyScaleEnum {
 
     /// Decodes SCALE bytes into an enum variant.
     /// Returns the decoded enum and the number of bytes consumed.
-    fn decode(bytes: &[u8]) -> Result<(Self, usize), String> {
+    /// Errors are located like the `scale` crate's: at the byte that could
+    /// not be read or that holds the offending value, with the variant
+    /// being decoded in the path.
+    fn decode(bytes: &[u8]) -> Result<(Self, usize), DecodeError> {
         if bytes.is_empty() {
-            return Err("Input bytes are empty for SCALE Enum decoding".to_string());
+            return Err(DecodeError::NotEnoughData { expected: 1, remaining: 0 }.at(0, "MyScaleEnum"));
         }
 
         let tag = bytes[0];
@@ -53,24 +56,26 @@ impl MyScaleEnum {
         match tag {
             0x00 => { // Foo(u16)
                 if bytes.len() < consumed_bytes + 2 {
-                    return Err(format!("Not enough bytes for Foo(u16). Expected at least {}, got {}", consumed_bytes + 2, bytes.len()));
+                    let error = DecodeError::NotEnoughData { expected: 2, remaining: bytes.len() - consumed_bytes };
+                    return Err(in_variant(error, consumed_bytes, "u16", "Foo"));
                 }
                 let u16_bytes: [u8; 2] = bytes[consumed_bytes..consumed_bytes + 2]
                     .try_into()
-                    .map_err(|_| "Failed to convert bytes to u16 array".to_string())?;
+                    .expect("length checked above");
                 let value = u16::from_le_bytes(u16_bytes);
                 consumed_bytes += 2;
                 Ok((MyScaleEnum::Foo(value), consumed_bytes))
             }
             0x01 => { // Bar(bool)
                 if bytes.len() < consumed_bytes + 1 {
-                    return Err(format!("Not enough bytes for Bar(bool). Expected at least {}, got {}", consumed_bytes + 1, bytes.len()));
+                    let error = DecodeError::NotEnoughData { expected: 1, remaining: 0 };
+                    return Err(in_variant(error, consumed_bytes, "bool", "Bar"));
                 }
                 let bool_byte = bytes[consumed_bytes];
                 let value = match bool_byte {
                     0x00 => false,
                     0x01 => true,
-                    _ => return Err(format!("Invalid boolean byte for Bar: 0x{:02x}", bool_byte)),
+                    _ => return Err(in_variant(DecodeError::InvalidBool(bool_byte), consumed_bytes, "bool", "Bar")),
                 };
                 consumed_bytes += 1;
                 Ok((MyScaleEnum::Bar(value), consumed_bytes))
@@ -78,11 +83,17 @@ impl MyScaleEnum {
             0x02 => { // Baz (_void)
                 Ok((MyScaleEnum::Baz, consumed_bytes))
             }
-            _ => Err(format!("Unknown SCALE Enum tag: 0x{:02x}", tag)),
+            _ => Err(DecodeError::UnknownVariant { type_name: "MyScaleEnum", tag }.at(0, "MyScaleEnum")),
         }
     }
 }
 
+/// Locates `error` at `offset`, in the field of type `expected` of the
+/// variant named `variant`, e.g. `MyScaleEnum.Bar`.
+fn in_variant(error: DecodeError, offset: usize, expected: &str, variant: &'static str) -> DecodeError {
+    error.within(offset as u64, expected, "MyScaleEnum", vec![PathSegment::Variant(variant)])
+}
+
 // Helper for printing byte arrays in hex
 fn bytes_to_hex(bytes: &[u8]) -> String {
     bytes.iter()
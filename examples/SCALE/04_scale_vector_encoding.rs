@@ -3,42 +3,55 @@
 // for Vector types. The length of the vector is always compact-encoded,
 // followed by the SCALE-encoded elements of the vector.
 
-use std::fmt;
+use scale::{DecodeError, PathSegment};
 
 // --- Helper functions for Compact Encoding (reused from Example 02) ---
 
-/// Encodes a u64 value into SCALE Compact format.
+/// Appends a u64 value in SCALE Compact format to `dest`.
 /// This function is used for encoding both the vector length and
 /// individual elements when the vector contains compact-encoded values.
-fn encode_compact(value: u64) -> Vec<u8> {
+/// Appending to a caller's buffer lets a whole vector be encoded into a
+/// single allocation instead of one small `Vec` per element.
+fn encode_compact_to(value: u64, dest: &mut Vec<u8>) {
     if value < 64 {
         // 0b00 variant: value in 6 bits, 00 suffix
-        vec![(value as u8) << 2]
+        dest.push((value as u8) << 2);
     } else if value < 16384 {
         // 0b01 variant: value in 14 bits, 01 suffix
         let val = (value << 2) | 0b01;
         let bytes = val.to_le_bytes();
-        bytes[0..2].to_vec() // Take the first two bytes (little-endian)
+        dest.extend_from_slice(&bytes[0..2]); // The first two bytes (little-endian)
     } else if value < 1073741824 {
         // 0b10 variant: value in 30 bits, 10 suffix
         let val = (value << 2) | 0b10;
         let bytes = val.to_le_bytes();
-        bytes[0..4].to_vec() // Take the first four bytes (little-endian)
+        dest.extend_from_slice(&bytes[0..4]); // The first four bytes (little-endian)
     } else {
-        // 0b11 variant: value as u32, then length prefix
-        // This case is for very large numbers, where the value itself is encoded
-        // as a u32, and the length of the u32 is encoded in the prefix.
-        // For simplicity and to match the image examples, we'll assume values
-        // fit within the 4-byte compact encoding for this example.
-        // A full implementation would handle arbitrary length encoding.
-        panic!("Values larger than 2^30-1 are not fully implemented in this example's compact encoding.");
+        // 0b11 variant: the 6 MSBs of the first byte hold the number of
+        // value bytes minus 4, followed by the value in little-endian.
+        // A u64 needs between 4 and 8 value bytes.
+        let bytes = value.to_le_bytes();
+        let num_value_bytes = (8 - value.leading_zeros() as usize / 8).max(4);
+        dest.push((((num_value_bytes - 4) as u8) << 2) | 0b11);
+        dest.extend_from_slice(&bytes[..num_value_bytes]);
     }
 }
 
+/// Controls whether non-canonical (non-minimal) compact encodings are accepted.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DecodeMode {
+    /// Reject values that could have been encoded in fewer bytes.
+    Strict,
+    /// Accept any well-formed encoding.
+    Lenient,
+}
+
 /// Decodes SCALE Compact bytes into a u64 value and the number of bytes consumed.
-fn decode_compact(bytes: &[u8]) -> Result<(u64, usize), String> {
+/// Errors are located like the `scale` crate's, at an offset into `bytes`.
+fn decode_compact(bytes: &[u8], decode_mode: DecodeMode) -> Result<(u64, usize), DecodeError> {
+    const EXPECTED: &str = "Compact<u64>";
     if bytes.is_empty() {
-        return Err("Input bytes are empty for compact decoding.".to_string());
+        return Err(DecodeError::NotEnoughData { expected: 1, remaining: 0 }.at(0, EXPECTED));
     }
 
     let first_byte = bytes[0];
@@ -53,30 +66,56 @@ fn decode_compact(bytes: &[u8]) -> Result<(u64, usize), String> {
         0b01 => {
             // 2-byte encoding: value in 14 bits
             if bytes.len() < 2 {
-                return Err("Not enough bytes for 2-byte compact decoding.".to_string());
+                return Err(DecodeError::NotEnoughData { expected: 1, remaining: 0 }.at(1, EXPECTED));
             }
             let mut val_bytes = [0u8; 2];
             val_bytes.copy_from_slice(&bytes[0..2]);
             let val = u16::from_le_bytes(val_bytes);
             let value = (val >> 2) as u64;
+            if decode_mode == DecodeMode::Strict && value < 64 {
+                // Non-canonical: the value fits in 1 byte
+                return Err(DecodeError::NonCanonicalCompact.at(1, EXPECTED));
+            }
             Ok((value, 2))
         }
         0b10 => {
             // 4-byte encoding: value in 30 bits
             if bytes.len() < 4 {
-                return Err("Not enough bytes for 4-byte compact decoding.".to_string());
+                let remaining = bytes.len() - 1;
+                return Err(DecodeError::NotEnoughData { expected: 3, remaining }.at(1, EXPECTED));
             }
             let mut val_bytes = [0u8; 4];
             val_bytes.copy_from_slice(&bytes[0..4]);
             let val = u32::from_le_bytes(val_bytes);
             let value = (val >> 2) as u64;
+            if decode_mode == DecodeMode::Strict && value < 16384 {
+                // Non-canonical: the value fits in 2 bytes or fewer
+                return Err(DecodeError::NonCanonicalCompact.at(1, EXPECTED));
+            }
             Ok((value, 4))
         }
         0b11 => {
-            // Multi-byte encoding (not fully implemented for arbitrary length in this example)
-            // The first byte indicates the number of additional bytes (N) for the length.
-            // The value is then encoded in N+4 bytes.
-            Err("Multi-byte compact encoding (0b11) not fully implemented in this example.".to_string())
+            // Multi-byte encoding: the 6 MSBs hold the number of value bytes
+            // minus 4. The value follows in little-endian.
+            let num_value_bytes = (first_byte >> 2) as usize + 4;
+            if num_value_bytes > 8 {
+                return Err(DecodeError::CompactOverflow { type_name: "u64" }.at(0, EXPECTED));
+            }
+            if bytes.len() < 1 + num_value_bytes {
+                let remaining = bytes.len() - 1;
+                return Err(DecodeError::NotEnoughData { expected: num_value_bytes, remaining }.at(1, EXPECTED));
+            }
+            let mut val_bytes = [0u8; 8];
+            val_bytes[..num_value_bytes].copy_from_slice(&bytes[1..1 + num_value_bytes]);
+            let value = u64::from_le_bytes(val_bytes);
+            // The most significant value byte must be used, and 4-byte values
+            // must be too large for the 4-byte 0b10 mode.
+            if decode_mode == DecodeMode::Strict
+                && (bytes[num_value_bytes] == 0 || value < 1_073_741_824)
+            {
+                return Err(DecodeError::NonCanonicalCompact.at(1, EXPECTED));
+            }
+            Ok((value, 1 + num_value_bytes))
         }
         _ => unreachable!(), // Should not happen with 2-bit mode
     }
@@ -87,24 +126,33 @@ fn decode_compact(bytes: &[u8]) -> Result<(u64, usize), String> {
 /// Encodes a vector of u8 values into SCALE format.
 /// The length is compact-encoded, followed by raw u8 elements.
 fn encode_vector_u8(vec: &[u8]) -> Vec<u8> {
-    let mut encoded = encode_compact(vec.len() as u64);
+    // At most 9 bytes of length prefix, then the elements as they are
+    let mut encoded = Vec::with_capacity(9 + vec.len());
+    encode_compact_to(vec.len() as u64, &mut encoded);
     encoded.extend_from_slice(vec);
     encoded
 }
 
 /// Decodes SCALE bytes into a vector of u8 values.
-fn decode_vector_u8(bytes: &[u8]) -> Result<Vec<u8>, String> {
+fn decode_vector_u8(bytes: &[u8]) -> Result<Vec<u8>, DecodeError> {
+    const EXPECTED: &str = "Vec<u8>";
     if bytes.is_empty() {
         return Ok(vec![]); // Empty vector case
     }
 
-    let (len, len_bytes_consumed) = decode_compact(bytes)?;
+    // A bad length prefix is reported against the vector itself
+    let (len, len_bytes_consumed) = decode_compact(bytes, DecodeMode::Strict).map_err(|e| {
+        let offset = e.offset().unwrap_or(0);
+        e.at(offset, EXPECTED)
+    })?;
 
-    if bytes.len() < len_bytes_consumed + len as usize {
-        return Err(format!(
-            "Not enough bytes to decode vector of u8. Expected {} bytes, got {}.",
-            len_bytes_consumed + len as usize, bytes.len()
-        ));
+    // The length prefix is untrusted: compare it with the bytes that are
+    // actually left before using it for anything
+    let remaining = bytes.len() - len_bytes_consumed;
+    if len > remaining as u64 {
+        let expected = usize::try_from(len).unwrap_or(usize::MAX);
+        let error = DecodeError::NotEnoughData { expected, remaining };
+        return Err(error.at(len_bytes_consumed as u64, EXPECTED));
     }
 
     let start_index = len_bytes_consumed;
@@ -115,29 +163,49 @@ fn decode_vector_u8(bytes: &[u8]) -> Result<Vec<u8>, String> {
 /// Encodes a vector of u64 values into SCALE format, where each u64 element
 /// is itself compact-encoded.
 fn encode_vector_compact(vec: &[u64]) -> Vec<u8> {
-    let mut encoded = encode_compact(vec.len() as u64);
+    // Every element is appended to the same buffer: one allocation for
+    // the whole vector (most elements take a single byte)
+    let mut encoded = Vec::with_capacity(9 + vec.len());
+    encode_compact_to(vec.len() as u64, &mut encoded);
     for &item in vec {
-        encoded.extend_from_slice(&encode_compact(item));
+        encode_compact_to(item, &mut encoded);
     }
     encoded
 }
 
 /// Decodes SCALE bytes into a vector of u64 values, where each u64 element
-/// is compact-decoded.
-fn decode_vector_compact(bytes: &[u8]) -> Result<Vec<u64>, String> {
+/// is compact-decoded. A bad element is located by its index, e.g.
+/// `Vec<Compact<u64>>[2]`.
+fn decode_vector_compact(bytes: &[u8]) -> Result<Vec<u64>, DecodeError> {
+    const EXPECTED: &str = "Vec<Compact<u64>>";
     if bytes.is_empty() {
         return Ok(vec![]); // Empty vector case
     }
 
-    let (len, len_bytes_consumed) = decode_compact(bytes)?;
+    let (len, len_bytes_consumed) = decode_compact(bytes, DecodeMode::Strict).map_err(|e| {
+        let offset = e.offset().unwrap_or(0);
+        e.at(offset, EXPECTED)
+    })?;
+
+    // Every compact element takes at least one byte, so a length prefix
+    // larger than the remaining input is bogus. Checking this first stops a
+    // 5-byte input claiming 2^30 elements from reserving gigabytes below.
+    let remaining = bytes.len() - len_bytes_consumed;
+    if len > remaining as u64 {
+        let expected = usize::try_from(len).unwrap_or(usize::MAX);
+        let error = DecodeError::NotEnoughData { expected, remaining };
+        return Err(error.at(len_bytes_consumed as u64, EXPECTED));
+    }
+
     let mut decoded_vec = Vec::with_capacity(len as usize);
     let mut current_index = len_bytes_consumed;
 
-    for _ in 0..len {
-        if current_index >= bytes.len() {
-            return Err("Not enough bytes to decode compact elements.".to_string());
-        }
-        let (item_value, item_bytes_consumed) = decode_compact(&bytes[current_index..])?;
+    for index in 0..len as usize {
+        let (item_value, item_bytes_consumed) = decode_compact(&bytes[current_index..], DecodeMode::Strict)
+            .map_err(|e| {
+                let offset = current_index as u64 + e.offset().unwrap_or(0);
+                e.within(offset, "Compact<u64>", EXPECTED, vec![PathSegment::Index(index)])
+            })?;
         decoded_vec.push(item_value);
         current_index += item_bytes_consumed;
     }
@@ -197,6 +265,33 @@ fn main() {
     let decoded_compact_three = decode_vector_compact(&encoded_compact_three).unwrap();
     assert_eq!(decoded_compact_three, vec_compact_three);
 
+    // Example: large u64 values use the 0b11 mode
+    // [1073741824, u64::MAX] => 0x08 03 00000040 13 ffffffffffffffff
+    let vec_compact_large: Vec<u64> = vec![1_073_741_824, u64::MAX];
+    let encoded_compact_large = encode_vector_compact(&vec_compact_large);
+    println!("Vector(compact) [2^30, u64::MAX]: {:?} => 0x{}", vec_compact_large, bytes_to_hex(&encoded_compact_large));
+    assert_eq!(bytes_to_hex(&encoded_compact_large), "08030000004013ffffffffffffffff");
+    let decoded_compact_large = decode_vector_compact(&encoded_compact_large).unwrap();
+    assert_eq!(decoded_compact_large, vec_compact_large);
+
+    // Example: millisecond timestamps
+    let timestamps: Vec<u64> = vec![1_735_689_600_000, 1_735_689_606_000];
+    let encoded_timestamps = encode_vector_compact(&timestamps);
+    println!("Vector(compact) timestamps: {:?} => 0x{}", timestamps, bytes_to_hex(&encoded_timestamps));
+    let decoded_timestamps = decode_vector_compact(&encoded_timestamps).unwrap();
+    assert_eq!(decoded_timestamps, timestamps);
+
+    // --- Canonical Compact Encoding ---
+    println!("\n--- Canonical Compact Encoding ---");
+
+    // 0x0500 is the value 1 in the 2-byte mode; the canonical form is 0x04
+    let non_canonical = [0x05, 0x00];
+    let strict_result = decode_compact(&non_canonical, DecodeMode::Strict);
+    println!("Strict decode of 0x0500: {:?}", strict_result);
+    assert!(strict_result.is_err());
+    let lenient_result = decode_compact(&non_canonical, DecodeMode::Lenient);
+    println!("Lenient decode of 0x0500: {:?}", lenient_result);
+    assert_eq!(lenient_result, Ok((1, 2)));
     println!("\nAll SCALE Vector encoding examples passed!");
 }
 
@@ -204,3 +299,113 @@ fn main() {
 fn bytes_to_hex(bytes: &[u8]) -> String {
     bytes.iter().map(|b| format!("{:02x}", b)).collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Both sides of every compact mode boundary, plus each 0b11 byte length.
+    const BOUNDARIES: [u64; 16] = [
+        0,
+        63,
+        64,
+        16_383,
+        16_384,
+        1_073_741_823,
+        1_073_741_824,
+        (1 << 32) - 1,
+        1 << 32,
+        (1 << 40) - 1,
+        1 << 40,
+        (1 << 48) - 1,
+        1 << 48,
+        (1 << 56) - 1,
+        1 << 56,
+        u64::MAX,
+    ];
+
+    fn encode_compact(value: u64) -> Vec<u8> {
+        let mut encoded = Vec::new();
+        encode_compact_to(value, &mut encoded);
+        encoded
+    }
+
+    #[test]
+    fn test_compact_appends_to_existing_buffer() {
+        let mut buffer = vec![0xaa];
+        encode_compact_to(64, &mut buffer);
+        encode_compact_to(1, &mut buffer);
+        assert_eq!(buffer, vec![0xaa, 0x01, 0x01, 0x04]);
+    }
+
+    #[test]
+    fn test_compact_mode_boundaries() {
+        let expected_lengths = [1, 1, 2, 2, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9];
+        for (&value, &expected_len) in BOUNDARIES.iter().zip(expected_lengths.iter()) {
+            let encoded = encode_compact(value);
+            assert_eq!(encoded.len(), expected_len, "length for {}", value);
+            assert_eq!(decode_compact(&encoded, DecodeMode::Strict), Ok((value, expected_len)), "round trip for {}", value);
+        }
+    }
+
+    #[test]
+    fn test_vector_compact_round_trip() {
+        let encoded = encode_vector_compact(&BOUNDARIES);
+        assert_eq!(decode_vector_compact(&encoded), Ok(BOUNDARIES.to_vec()));
+    }
+
+    #[test]
+    fn test_compact_rejects_oversized_values() {
+        // 0b11 mode with 9 value bytes cannot fit into a u64
+        let mut bytes = vec![(5 << 2) | 0b11];
+        bytes.extend_from_slice(&[0xff; 9]);
+        assert!(decode_compact(&bytes, DecodeMode::Lenient).is_err());
+    }
+
+    #[test]
+    fn test_vectors_reject_bogus_length_prefix() {
+        // 0x03 00 00 00 40 claims 2^30 elements with nothing after it
+        let hostile = [0x03, 0x00, 0x00, 0x00, 0x40];
+        assert!(decode_vector_u8(&hostile).is_err());
+        assert!(decode_vector_compact(&hostile).is_err());
+
+        // u64::MAX elements must not overflow the bounds arithmetic
+        let huge = encode_compact(u64::MAX);
+        assert!(decode_vector_u8(&huge).is_err());
+        assert!(decode_vector_compact(&huge).is_err());
+    }
+
+    #[test]
+    fn test_compact_rejects_truncated_big_mode() {
+        assert!(decode_compact(&[0x03, 0x00, 0x00, 0x00], DecodeMode::Lenient).is_err());
+        assert!(decode_vector_compact(&[0x04, 0x13, 0xff]).is_err());
+    }
+
+    #[test]
+    fn test_strict_rejects_non_canonical() {
+        let cases: Vec<(Vec<u8>, u64)> = vec![
+            (vec![0x05, 0x00], 1),
+            (vec![0x06, 0x00, 0x00, 0x00], 1),
+            (vec![0x03, 0xff, 0xff, 0xff, 0x3f], 1_073_741_823),
+            (vec![0x07, 0x00, 0x00, 0x00, 0x40, 0x00], 1_073_741_824),
+        ];
+        for (bytes, value) in cases {
+            assert!(decode_compact(&bytes, DecodeMode::Strict).is_err(), "strict {:?}", bytes);
+            assert_eq!(decode_compact(&bytes, DecodeMode::Lenient), Ok((value, bytes.len())));
+        }
+        // Vector length prefixes are decoded strictly
+        assert!(decode_vector_u8(&[0x05, 0x00, 0x2a]).is_err());
+    }
+
+    #[test]
+    fn test_errors_are_located() {
+        // The second element is a 2-byte compact cut short
+        let error = decode_vector_compact(&[0x08, 0x04, 0x01]).unwrap_err();
+        assert_eq!(error.kind(), &DecodeError::NotEnoughData { expected: 1, remaining: 0 });
+        assert_eq!(error.offset(), Some(3));
+        assert_eq!(error.path_string().as_deref(), Some("Vec<Compact<u64>>[1]"));
+
+        let error = decode_vector_u8(&[0x0c, 0x01]).unwrap_err();
+        assert_eq!(error, DecodeError::NotEnoughData { expected: 3, remaining: 1 }.at(1, "Vec<u8>"));
+    }
+}
@@ -4,8 +4,11 @@
 // part of the type definition and is NOT encoded in the byte stream.
 // This is different from Vectors, which encode their length.
 
+use std::any::type_name;
 use std::fmt;
 
+use scale::{DecodeError, PathSegment};
+
 // --- Array Encoding Functions ---
 
 /// Encodes a fixed-size array of u8 values into SCALE format.
@@ -17,12 +20,10 @@ fn encode_array_u8<const N: usize>(arr: &[u8; N]) -> Vec<u8> {
 
 /// Decodes SCALE bytes into a fixed-size array of u8 values.
 /// The size is NOT decoded - it's implicit from the type definition.
-fn decode_array_u8<const N: usize>(bytes: &[u8]) -> Result<[u8; N], String> {
+fn decode_array_u8<const N: usize>(bytes: &[u8]) -> Result<[u8; N], DecodeError> {
     if bytes.len() < N {
-        return Err(format!(
-            "Not enough bytes to decode array of u8. Expected {} bytes, got {}.",
-            N, bytes.len()
-        ));
+        let error = DecodeError::NotEnoughData { expected: N, remaining: bytes.len() };
+        return Err(error.at(0, type_name::<[u8; N]>()));
     }
     
     let mut arr = [0u8; N];
@@ -44,25 +45,123 @@ fn encode_array_u16<const N: usize>(arr: &[u16; N]) -> Vec<u8> {
 /// Decodes SCALE bytes into a fixed-size array of u16 values.
 /// The size is NOT decoded - it's implicit from the type definition.
 /// Each u16 value is decoded from 2 bytes in little-endian format.
-fn decode_array_u16<const N: usize>(bytes: &[u8]) -> Result<[u16; N], String> {
+fn decode_array_u16<const N: usize>(bytes: &[u8]) -> Result<[u16; N], DecodeError> {
     if bytes.len() < N * 2 {
-        return Err(format!(
-            "Not enough bytes to decode array of u16. Expected {} bytes, got {}.",
-            N * 2, bytes.len()
-        ));
+        let error = DecodeError::NotEnoughData { expected: N * 2, remaining: bytes.len() };
+        return Err(error.at(0, type_name::<[u16; N]>()));
     }
     
     let mut arr = [0u16; N];
-    for i in 0..N {
+    for (i, value) in arr.iter_mut().enumerate() {
         let start = i * 2;
         let end = start + 2;
         let mut bytes_u16 = [0u8; 2];
         bytes_u16.copy_from_slice(&bytes[start..end]);
-        arr[i] = u16::from_le_bytes(bytes_u16);
+        *value = u16::from_le_bytes(bytes_u16);
     }
     Ok(arr)
 }
 
+// --- Generic Array Encoding ---
+
+/// A value that can be an array element. Arrays of any element type are
+/// encoded the same way: each element's encoding, back to back.
+trait Element: Sized {
+    fn encode_to(&self, out: &mut Vec<u8>);
+    /// Decodes one element from the front of `bytes`, returning it along
+    /// with the number of bytes consumed. Errors are located at an offset
+    /// into `bytes`.
+    fn decode_from(bytes: &[u8]) -> Result<(Self, usize), DecodeError>;
+}
+
+/// Moves an error located in a part of a `Root` value out to the whole:
+/// `offset` bytes further into the input, and one `segment` deeper if the
+/// part has a path of its own (array elements do, tuple fields do not).
+fn nest<Root>(error: DecodeError, offset: usize, segment: Option<PathSegment>) -> DecodeError {
+    match error {
+        DecodeError::At { error, offset: inner, expected, mut path, .. } => {
+            if let Some(segment) = segment {
+                path.insert(0, segment);
+            }
+            error.within(inner + offset as u64, &expected, type_name::<Root>(), path)
+        }
+        error => error.at(offset as u64, type_name::<Root>()),
+    }
+}
+
+macro_rules! impl_element_for_int {
+    ($($t:ty),*) => {
+        $(
+            impl Element for $t {
+                fn encode_to(&self, out: &mut Vec<u8>) {
+                    out.extend_from_slice(&self.to_le_bytes());
+                }
+
+                fn decode_from(bytes: &[u8]) -> Result<(Self, usize), DecodeError> {
+                    const SIZE: usize = std::mem::size_of::<$t>();
+                    let head = bytes.get(..SIZE).ok_or_else(|| {
+                        DecodeError::NotEnoughData { expected: SIZE, remaining: bytes.len() }
+                            .at(0, stringify!($t))
+                    })?;
+                    Ok((<$t>::from_le_bytes(head.try_into().unwrap()), SIZE))
+                }
+            }
+        )*
+    };
+}
+
+impl_element_for_int!(u8, u16, u32, u64);
+
+// Tuples encode their fields in order
+impl<A: Element, B: Element> Element for (A, B) {
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        self.0.encode_to(out);
+        self.1.encode_to(out);
+    }
+
+    fn decode_from(bytes: &[u8]) -> Result<(Self, usize), DecodeError> {
+        let (a, used_a) = A::decode_from(bytes).map_err(|e| nest::<Self>(e, 0, None))?;
+        let (b, used_b) = B::decode_from(&bytes[used_a..]).map_err(|e| nest::<Self>(e, used_a, None))?;
+        Ok(((a, b), used_a + used_b))
+    }
+}
+
+// Arrays are elements too, so arrays can be nested
+impl<T: Element, const N: usize> Element for [T; N] {
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        for value in self {
+            value.encode_to(out);
+        }
+    }
+
+    fn decode_from(bytes: &[u8]) -> Result<(Self, usize), DecodeError> {
+        let mut values = Vec::with_capacity(N);
+        let mut used = 0;
+        for index in 0..N {
+            let (value, n) = T::decode_from(&bytes[used..])
+                .map_err(|e| nest::<Self>(e, used, Some(PathSegment::Index(index))))?;
+            values.push(value);
+            used += n;
+        }
+        let array = values
+            .try_into()
+            .unwrap_or_else(|_| unreachable!("exactly N values were decoded"));
+        Ok((array, used))
+    }
+}
+
+/// Encodes a fixed-size array of any element type.
+fn encode_array<T: Element, const N: usize>(arr: &[T; N]) -> Vec<u8> {
+    let mut encoded = Vec::new();
+    arr.encode_to(&mut encoded);
+    encoded
+}
+
+/// Decodes a fixed-size array of any element type.
+fn decode_array<T: Element, const N: usize>(bytes: &[u8]) -> Result<[T; N], DecodeError> {
+    <[T; N]>::decode_from(bytes).map(|(arr, _)| arr)
+}
+
 // Helper function to print arrays in a readable format
 fn print_array<T: fmt::Debug>(arr: &[T], name: &str) {
     println!("{}: {:?}", name, arr);
@@ -174,6 +273,32 @@ fn main() {
     assert_eq!(decoded_u16_1, arr_u16_1);
     println!("  ✅ Encoding/decoding successful\n");
 
+    // --- Generic Array Examples ---
+    println!("--- Generic Array Examples ---");
+
+    // The generic encoder produces the same bytes as the specialised ones
+    assert_eq!(encode_array(&arr_u16_2), encoded_u16_2);
+
+    // Array<(u32, u32), 4>: each tuple is 8 bytes, 32 bytes in total
+    let pairs: [(u32, u32); 4] = [(1, 2), (3, 4), (5, 6), (7, 8)];
+    let encoded_pairs = encode_array(&pairs);
+    println!("Array<(u32, u32), 4> {:?}:", pairs);
+    println!("  Encoded: 0x{}", bytes_to_hex(&encoded_pairs));
+    assert_eq!(encoded_pairs.len(), 32);
+    let decoded_pairs = decode_array::<(u32, u32), 4>(&encoded_pairs).unwrap();
+    assert_eq!(decoded_pairs, pairs);
+    println!("  ✅ Encoding/decoding successful\n");
+
+    // Array<Array<u8, 2>, 3>: nested arrays are just their bytes in order
+    let nested: [[u8; 2]; 3] = [[1, 2], [3, 4], [5, 6]];
+    let encoded_nested = encode_array(&nested);
+    println!("Array<Array<u8, 2>, 3> {:?}:", nested);
+    println!("  Encoded: 0x{}", bytes_to_hex(&encoded_nested));
+    assert_eq!(bytes_to_hex(&encoded_nested), "010203040506");
+    let decoded_nested = decode_array::<[u8; 2], 3>(&encoded_nested).unwrap();
+    assert_eq!(decoded_nested, nested);
+    println!("  ✅ Encoding/decoding successful\n");
+
     // --- Error Handling Tests ---
     println!("--- Error Handling Tests ---");
 
@@ -191,6 +316,12 @@ fn main() {
         Err(e) => println!("✅ Expected error: {}", e),
     }
 
+    // Test insufficient bytes for a nested tuple array
+    match decode_array::<(u32, u32), 4>(&encoded_pairs[..30]) {
+        Ok(_) => println!("❌ Unexpected success with insufficient bytes"),
+        Err(e) => println!("✅ Expected error: {}", e),
+    }
+
     println!("\n=== SCALE Array Encoding Analysis ===");
     println!("Key differences between Arrays and Vectors:");
     println!("- Arrays: Fixed size, size NOT encoded in bytes");
@@ -0,0 +1,263 @@
+// Example 07: SCALE Bit Sequence Encoding
+// Demonstrates the SCALE encoding for bit sequences (bitvec's BitVec), as
+// used by parachain availability bitfields. A bit sequence is encoded as:
+// - the number of BITS, compact-encoded
+// - the bits packed into store words (u8, u32 or u64), each word encoded
+//   as a little-endian integer
+// The bit order decides where each bit goes inside its word:
+// - Lsb0: the first bit is the least significant bit of the word
+// - Msb0: the first bit is the most significant bit of the word
+
+use scale::DecodeError;
+
+/// Where the bits of a sequence go inside each store word.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BitOrder {
+    Lsb0,
+    Msb0,
+}
+
+/// Position of in-word bit `index` within a word of `word_bits` bits,
+/// counted from the least significant bit.
+fn bit_position(index: usize, word_bits: usize, order: BitOrder) -> usize {
+    match order {
+        BitOrder::Lsb0 => index,
+        BitOrder::Msb0 => word_bits - 1 - index,
+    }
+}
+
+/// Encodes a compact integer. Bit sequences are usually short, but a
+/// length of 2^30 bits or more needs the 0b11 big-integer mode.
+fn encode_compact(value: usize) -> Vec<u8> {
+    match value {
+        0..=0x3F => vec![(value as u8) << 2],
+        0x40..=0x3FFF => ((value as u16) << 2 | 0b01).to_le_bytes().to_vec(),
+        0x4000..=0x3FFF_FFFF => ((value as u32) << 2 | 0b10).to_le_bytes().to_vec(),
+        _ => {
+            // The 6 MSBs of the first byte hold the number of value bytes
+            // minus 4, followed by the value in little-endian
+            let value = value as u64;
+            let num_value_bytes = (8 - value.leading_zeros() as usize / 8).max(4);
+            let mut encoded = vec![(((num_value_bytes - 4) as u8) << 2) | 0b11];
+            encoded.extend_from_slice(&value.to_le_bytes()[..num_value_bytes]);
+            encoded
+        }
+    }
+}
+
+/// Decodes a compact integer, returning the value and the bytes consumed.
+fn decode_compact(bytes: &[u8]) -> Result<(usize, usize), DecodeError> {
+    const EXPECTED: &str = "Compact<u64>";
+    let first = *bytes.first().ok_or_else(|| {
+        DecodeError::NotEnoughData {
+            expected: 1,
+            remaining: 0,
+        }
+        .at(0, EXPECTED)
+    })?;
+    let size = match first & 0b11 {
+        0b00 => 1,
+        0b01 => 2,
+        0b10 => 4,
+        _ => 1 + (first >> 2) as usize + 4,
+    };
+    if bytes.len() < size {
+        let error = DecodeError::NotEnoughData {
+            expected: size - 1,
+            remaining: bytes.len() - 1,
+        };
+        return Err(error.at(1, EXPECTED));
+    }
+    if first & 0b11 != 0b11 {
+        let mut buf = [0u8; 4];
+        buf[..size].copy_from_slice(&bytes[..size]);
+        return Ok(((u32::from_le_bytes(buf) >> 2) as usize, size));
+    }
+    // Bit lengths are at most 8 bytes long, unless padded with zeros
+    let value_bytes = &bytes[1..size];
+    if value_bytes.iter().skip(8).any(|&b| b != 0) {
+        let error = DecodeError::CompactOverflow { type_name: "u64" };
+        return Err(error.at(1, EXPECTED));
+    }
+    let mut buf = [0u8; 8];
+    let significant = value_bytes.len().min(8);
+    buf[..significant].copy_from_slice(&value_bytes[..significant]);
+    let value = usize::try_from(u64::from_le_bytes(buf))
+        .map_err(|_| DecodeError::CompactOverflow { type_name: "usize" }.at(1, EXPECTED))?;
+    Ok((value, size))
+}
+
+/// Encodes `bits` packed into `word_bits`-wide store words (8, 32 or 64).
+fn encode_bitseq(bits: &[bool], word_bits: usize, order: BitOrder) -> Vec<u8> {
+    let mut encoded = encode_compact(bits.len());
+    for chunk in bits.chunks(word_bits) {
+        let mut word: u64 = 0;
+        for (i, &bit) in chunk.iter().enumerate() {
+            if bit {
+                word |= 1 << bit_position(i, word_bits, order);
+            }
+        }
+        // Each word is a little-endian integer of the store width
+        encoded.extend_from_slice(&word.to_le_bytes()[..word_bits / 8]);
+    }
+    encoded
+}
+
+/// Decodes a bit sequence stored in `word_bits`-wide words. Errors are
+/// located in e.g. `BitSeq<u32, Lsb0>`.
+fn decode_bitseq(
+    bytes: &[u8],
+    word_bits: usize,
+    order: BitOrder,
+) -> Result<Vec<bool>, DecodeError> {
+    let expected = format!("BitSeq<u{}, {:?}>", word_bits, order);
+    let (len, offset) = decode_compact(bytes).map_err(|e| {
+        let at = e.offset().unwrap_or(0);
+        e.at(at, &expected)
+    })?;
+    let word_bytes = word_bits / 8;
+    let num_words = len.div_ceil(word_bits);
+    if bytes.len() < offset + num_words * word_bytes {
+        let error = DecodeError::NotEnoughData {
+            expected: num_words * word_bytes,
+            remaining: bytes.len() - offset,
+        };
+        return Err(error.at(offset as u64, &expected));
+    }
+
+    let mut bits = Vec::with_capacity(len);
+    for w in 0..num_words {
+        let start = offset + w * word_bytes;
+        let mut buf = [0u8; 8];
+        buf[..word_bytes].copy_from_slice(&bytes[start..start + word_bytes]);
+        let word = u64::from_le_bytes(buf);
+        for i in 0..word_bits {
+            if bits.len() == len {
+                break; // Remaining bits are padding
+            }
+            bits.push(word >> bit_position(i, word_bits, order) & 1 == 1);
+        }
+    }
+    Ok(bits)
+}
+
+/// Helper function to print binary representation, one store word per
+/// line with the word's bytes in encoded (little-endian) order
+fn print_binary(bytes: &[u8], word_bits: usize) {
+    let (len, offset) = decode_compact(bytes).unwrap();
+    print!("Length:");
+    print_bytes(&bytes[..offset]);
+    println!(" [Compact: {} bits]", len);
+    for (w, word) in bytes[offset..].chunks(word_bits / 8).enumerate() {
+        print!("Word {}:", w);
+        print_bytes(word);
+        println!();
+    }
+}
+
+/// Prints bytes in binary followed by their hex form
+fn print_bytes(bytes: &[u8]) {
+    for byte in bytes {
+        print!(" {:08b}", byte);
+    }
+    print!(" (0x");
+    for byte in bytes {
+        print!("{:02x}", byte);
+    }
+    print!(")");
+}
+
+/// Renders bits as 1s and 0s in sequence order
+fn bits_to_string(bits: &[bool]) -> String {
+    bits.iter().map(|&b| if b { '1' } else { '0' }).collect()
+}
+
+fn main() {
+    println!("=== SCALE Bit Sequence Encoding Examples ===\n");
+
+    let bits = [true, false, true, true];
+
+    // --- Bit order within a u8 word ---
+    println!("--- BitVec<u8, Lsb0> vs BitVec<u8, Msb0> ---");
+    println!("Bits: {}", bits_to_string(&bits));
+
+    // Lsb0: bit 0 -> 0b0000_0001, bit 2 -> 0b0000_0100, bit 3 -> 0b0000_1000
+    let lsb0 = encode_bitseq(&bits, 8, BitOrder::Lsb0);
+    println!("Lsb0:");
+    print_binary(&lsb0, 8);
+    assert_eq!(lsb0, vec![0x10, 0b0000_1101]);
+
+    // Msb0: bit 0 -> 0b1000_0000, bit 2 -> 0b0010_0000, bit 3 -> 0b0001_0000
+    let msb0 = encode_bitseq(&bits, 8, BitOrder::Msb0);
+    println!("Msb0:");
+    print_binary(&msb0, 8);
+    assert_eq!(msb0, vec![0x10, 0b1011_0000]);
+    println!();
+
+    // --- Wider store words ---
+    println!("--- BitVec<u32, _> and BitVec<u64, _> ---");
+    // With Msb0 in a u32, the first bit is the top bit of the word, which
+    // ends up in the LAST byte because the word is little-endian
+    let msb0_u32 = encode_bitseq(&bits, 32, BitOrder::Msb0);
+    println!("u32, Msb0:");
+    print_binary(&msb0_u32, 32);
+    assert_eq!(msb0_u32, vec![0x10, 0x00, 0x00, 0x00, 0b1011_0000]);
+
+    // 70 bits need two u64 words; the unused 58 bits are zero padding
+    let long: Vec<bool> = (0..70).map(|i| i % 3 == 0).collect();
+    let lsb0_u64 = encode_bitseq(&long, 64, BitOrder::Lsb0);
+    println!("u64, Lsb0 ({} bits):", long.len());
+    print_binary(&lsb0_u64, 64);
+    assert_eq!(lsb0_u64.len(), 2 + 16); // 2-byte compact length, 2 words
+    println!();
+
+    // --- Long sequences ---
+    // 2^30 bits and more no longer fit the four-byte mode: the length
+    // switches to the big-integer mode instead of being cut to 30 bits
+    let huge = 1 << 30;
+    assert_eq!(encode_compact(huge), vec![0b11, 0x00, 0x00, 0x00, 0x40]);
+    assert_eq!(decode_compact(&encode_compact(huge)), Ok((huge, 5)));
+    let huge = usize::MAX;
+    assert_eq!(
+        decode_compact(&encode_compact(huge)),
+        Ok((huge, 1 + usize::BITS as usize / 8))
+    );
+
+    // --- Round trips for every store/order combination ---
+    println!("--- Round Trips ---");
+    for word_bits in [8, 32, 64] {
+        for order in [BitOrder::Lsb0, BitOrder::Msb0] {
+            for len in [0, 1, 8, 9, 33, 70] {
+                let bits: Vec<bool> = (0..len).map(|i| i % 3 == 0).collect();
+                let encoded = encode_bitseq(&bits, word_bits, order);
+                let decoded = decode_bitseq(&encoded, word_bits, order).unwrap();
+                assert_eq!(decoded, bits);
+            }
+            println!("✅ u{}, {:?}: all lengths round-trip", word_bits, order);
+        }
+    }
+    println!();
+
+    // --- Availability bitfield ---
+    println!("--- Availability Bitfield ---");
+    // One bit per availability core: which candidates this validator holds
+    let bitfield = [
+        true, true, false, true, false, false, true, true, true, false,
+    ];
+    let encoded = encode_bitseq(&bitfield, 8, BitOrder::Lsb0);
+    println!("Bitfield: {}", bits_to_string(&bitfield));
+    print_binary(&encoded, 8);
+    let decoded = decode_bitseq(&encoded, 8, BitOrder::Lsb0).unwrap();
+    println!("Decoded:  {}", bits_to_string(&decoded));
+    assert_eq!(decoded, bitfield);
+    println!();
+
+    // --- Error Handling ---
+    println!("--- Error Handling ---");
+    // Claims 33 bits (two u32 words) but holds only one word
+    let truncated = [0x84, 0xff, 0xff, 0xff, 0xff];
+    match decode_bitseq(&truncated, 32, BitOrder::Lsb0) {
+        Ok(_) => println!("❌ Unexpected success with truncated words"),
+        Err(e) => println!("✅ Expected error: {}", e),
+    }
+}
@@ -0,0 +1,15 @@
+// Array decoders of example 05, for flat, tuple and nested elements.
+
+#![no_main]
+#![allow(dead_code)]
+
+use libfuzzer_sys::fuzz_target;
+
+include!("../../examples/SCALE/05_scale_array_encoding.rs");
+
+fuzz_target!(|data: &[u8]| {
+    let _ = decode_array_u8::<4>(data);
+    let _ = decode_array_u16::<3>(data);
+    let _ = decode_array::<(u8, u32), 3>(data);
+    let _ = decode_array::<[u16; 2], 2>(data);
+});
@@ -0,0 +1,16 @@
+// Bit sequence decoder of example 07, for every store width and order.
+
+#![no_main]
+#![allow(dead_code)]
+
+use libfuzzer_sys::fuzz_target;
+
+include!("../../examples/SCALE/07_scale_bitseq_encoding.rs");
+
+fuzz_target!(|data: &[u8]| {
+    for word_bits in [8, 16, 32, 64] {
+        for order in [BitOrder::Lsb0, BitOrder::Msb0] {
+            let _ = decode_bitseq(data, word_bits, order);
+        }
+    }
+});
@@ -0,0 +1,15 @@
+// Compact decoders of example 02, in both modes.
+
+#![no_main]
+#![allow(dead_code)]
+
+use libfuzzer_sys::fuzz_target;
+
+include!("../../examples/SCALE/02_scale_compact_encoding.rs");
+
+fuzz_target!(|data: &[u8]| {
+    for mode in [DecodeMode::Strict, DecodeMode::Lenient] {
+        let _ = decode_compact(data, mode);
+        let _ = decode_compact_big(data, mode);
+    }
+});
@@ -0,0 +1,12 @@
+// Enum decoder of example 03.
+
+#![no_main]
+#![allow(dead_code)]
+
+use libfuzzer_sys::fuzz_target;
+
+include!("../../examples/SCALE/03_scale_enum_encoding.rs");
+
+fuzz_target!(|data: &[u8]| {
+    let _ = MyScaleEnum::decode(data);
+});
@@ -0,0 +1,14 @@
+// `parse_header_to_scale` of the JSON-RPC example, fed any JSON value.
+
+#![no_main]
+#![allow(dead_code)]
+
+use libfuzzer_sys::fuzz_target;
+
+include!("../../examples/JSON-RPC/01_polkadot_header_subscription.rs");
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(header) = serde_json::from_slice::<Value>(data) {
+        let _ = parse_header_to_scale(&header);
+    }
+});
@@ -0,0 +1,19 @@
+// The `scale` crate's decoders under the default limits, for a spread of
+// nested collections, compacts, options and bit sequences.
+
+#![no_main]
+
+use std::collections::BTreeMap;
+
+use libfuzzer_sys::fuzz_target;
+use scale::{BitSeq, Compact, CompactBig, Decode, DecodeLimits, Msb0};
+
+fuzz_target!(|data: &[u8]| {
+    let limits = DecodeLimits::default();
+    let _ = Vec::<(Compact<u64>, Option<bool>, String)>::decode_all_with_limits(data, limits);
+    let _ = BTreeMap::<u32, Vec<u8>>::decode_all_with_limits(data, limits);
+    let _ = BitSeq::<u32, Msb0>::decode_all_with_limits(data, limits);
+    let _ = CompactBig::decode_all_with_limits(data, limits);
+    let _ = Result::<[u16; 4], Vec<Compact<u128>>>::decode_all_with_limits(data, limits);
+    let _ = Vec::<Option<Vec<u8>>>::decode_all(data).map_err(|e| e.to_string());
+});
@@ -0,0 +1,16 @@
+// Vector and compact decoders of example 04.
+
+#![no_main]
+#![allow(dead_code)]
+
+use libfuzzer_sys::fuzz_target;
+
+include!("../../examples/SCALE/04_scale_vector_encoding.rs");
+
+fuzz_target!(|data: &[u8]| {
+    let _ = decode_vector_u8(data);
+    let _ = decode_vector_compact(data);
+    for mode in [DecodeMode::Strict, DecodeMode::Lenient] {
+        let _ = decode_compact(data, mode);
+    }
+});
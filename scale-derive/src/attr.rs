@@ -0,0 +1,63 @@
+// Parsing of the `#[codec(...)]` attributes understood by the derives.
+
+use syn::{Attribute, LitInt, Variant};
+
+/// Options set on a field with `#[codec(compact)]` or `#[codec(skip)]`.
+#[derive(Default)]
+pub struct FieldAttrs {
+    /// Encode the field with the compact integer encoding.
+    pub compact: bool,
+    /// Leave the field out of the encoding; decode it as `Default::default()`.
+    pub skip: bool,
+}
+
+/// Collects the `#[codec(...)]` options of a field.
+pub fn field_attrs(attrs: &[Attribute]) -> syn::Result<FieldAttrs> {
+    let mut field_attrs = FieldAttrs::default();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("codec")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("compact") {
+                field_attrs.compact = true;
+                Ok(())
+            } else if meta.path.is_ident("skip") {
+                field_attrs.skip = true;
+                Ok(())
+            } else {
+                Err(meta.error("expected `compact` or `skip`"))
+            }
+        })?;
+        if field_attrs.compact && field_attrs.skip {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "`compact` and `skip` cannot be combined",
+            ));
+        }
+    }
+    Ok(field_attrs)
+}
+
+/// Returns the variant index: `#[codec(index = N)]` if present, otherwise
+/// the position of the variant in the enum.
+pub fn variant_index(variant: &Variant, position: usize) -> syn::Result<u8> {
+    let mut index = None;
+    for attr in variant
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("codec"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("index") {
+                let lit: LitInt = meta.value()?.parse()?;
+                index = Some(lit.base10_parse::<u8>()?);
+                Ok(())
+            } else {
+                Err(meta.error("expected `index = N`"))
+            }
+        })?;
+    }
+    match index {
+        Some(index) => Ok(index),
+        None => u8::try_from(position)
+            .map_err(|_| syn::Error::new_spanned(variant, "enums can have at most 256 variants")),
+    }
+}
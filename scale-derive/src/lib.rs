@@ -0,0 +1,476 @@
+//! `#[derive(Encode, Decode)]` for the `scale` crate.
+//!
+//! Structs encode their fields in declaration order. Enums encode a
+//! one-byte variant index followed by the fields of that variant. The
+//! following attributes are supported, matching `parity-scale-codec`:
+//!
+//! - `#[codec(index = N)]` on a variant sets its index (default: position),
+//! - `#[codec(compact)]` on a field uses the compact integer encoding,
+//! - `#[codec(skip)]` on a field leaves it out; it decodes as `Default`.
+//!
+//! `#[derive(MaxEncodedLen)]` bounds a struct by the sum of its fields and
+//! an enum by its index byte plus its largest variant.
+//!
+//! `#[derive(TypeInfo)]` describes a struct as a composite and an enum as a
+//! variant type in a `scale::Registry`, with the same indexes, compact
+//! fields and skipped fields as the encoding.
+
+mod attr;
+
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, parse_quote, Data, DeriveInput, Fields, Generics, Ident, Index};
+
+use attr::{field_attrs, variant_index};
+
+#[proc_macro_derive(Encode, attributes(codec))]
+pub fn derive_encode(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_encode(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[proc_macro_derive(Decode, attributes(codec))]
+pub fn derive_decode(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_decode(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[proc_macro_derive(MaxEncodedLen, attributes(codec))]
+pub fn derive_max_encoded_len(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_max_encoded_len(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[proc_macro_derive(TypeInfo, attributes(codec))]
+pub fn derive_type_info(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_type_info(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Adds `bound` to every type parameter of `generics`.
+fn add_bounds(mut generics: Generics, bound: syn::Path) -> Generics {
+    for param in generics.type_params_mut() {
+        param.bounds.push(parse_quote!(#bound));
+    }
+    generics
+}
+
+/// Indexes of all variants, rejecting duplicates.
+fn variant_indexes(data: &syn::DataEnum) -> syn::Result<Vec<u8>> {
+    let mut indexes: Vec<u8> = Vec::with_capacity(data.variants.len());
+    for (position, variant) in data.variants.iter().enumerate() {
+        let index = variant_index(variant, position)?;
+        if indexes.contains(&index) {
+            return Err(syn::Error::new_spanned(
+                variant,
+                format!("duplicate variant index {}", index),
+            ));
+        }
+        indexes.push(index);
+    }
+    Ok(indexes)
+}
+
+/// Statements appending each field of `fields` to `__dest`. `access`
+/// yields the expression referring to the n-th field.
+fn encode_fields(
+    fields: &Fields,
+    access: impl Fn(usize, &syn::Field) -> TokenStream,
+) -> syn::Result<Vec<TokenStream>> {
+    let mut statements = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        let attrs = field_attrs(&field.attrs)?;
+        if attrs.skip {
+            continue;
+        }
+        let value = access(i, field);
+        statements.push(if attrs.compact {
+            quote! {
+                ::scale::Encode::encode_to(
+                    &::scale::Compact(::core::clone::Clone::clone(#value)),
+                    __dest,
+                );
+            }
+        } else {
+            quote! {
+                ::scale::Encode::encode_to(#value, __dest);
+            }
+        });
+    }
+    Ok(statements)
+}
+
+/// Expressions giving the encoded size of each field of `fields`, matching
+/// [`encode_fields`].
+fn size_fields(
+    fields: &Fields,
+    access: impl Fn(usize, &syn::Field) -> TokenStream,
+) -> syn::Result<Vec<TokenStream>> {
+    let mut sizes = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        let attrs = field_attrs(&field.attrs)?;
+        if attrs.skip {
+            continue;
+        }
+        let value = access(i, field);
+        sizes.push(if attrs.compact {
+            quote! {
+                ::scale::Encode::encoded_size(
+                    &::scale::Compact(::core::clone::Clone::clone(#value)),
+                )
+            }
+        } else {
+            quote! { ::scale::Encode::encoded_size(#value) }
+        });
+    }
+    Ok(sizes)
+}
+
+/// Expression summing the largest encoded size of each field of `fields`.
+fn max_len_fields(fields: &Fields) -> syn::Result<TokenStream> {
+    let mut lens = Vec::new();
+    for field in fields {
+        if field_attrs(&field.attrs)?.skip {
+            continue;
+        }
+        let ty = decoded_type(field)?;
+        lens.push(quote! { <#ty as ::scale::MaxEncodedLen>::max_encoded_len() });
+    }
+    Ok(quote! { 0usize #(.saturating_add(#lens))* })
+}
+
+/// Expression building the `Vec<scale::Field>` describing `fields`,
+/// registering their types in `__registry`.
+fn type_info_fields(fields: &Fields) -> syn::Result<TokenStream> {
+    let mut descriptions = Vec::new();
+    for field in fields {
+        if field_attrs(&field.attrs)?.skip {
+            continue;
+        }
+        let ty = decoded_type(field)?;
+        let id = quote! { ::scale::Registry::register::<#ty>(__registry) };
+        descriptions.push(match &field.ident {
+            Some(ident) => {
+                let name = ident.to_string();
+                quote! { ::scale::Field::named(#name, #id) }
+            }
+            None => quote! { ::scale::Field::unnamed(#id) },
+        });
+    }
+    Ok(quote! { ::scale::__private::vec![#(#descriptions),*] })
+}
+
+/// Wraps `value`, an expression decoding from `input`, in the
+/// `Input::enter`/`Input::leave` calls that record its path.
+fn traced(segment: TokenStream, expected: TokenStream, value: TokenStream) -> TokenStream {
+    quote! {{
+        ::scale::Input::enter(input, #segment, ::core::any::type_name::<#expected>());
+        let __value = #value;
+        ::scale::Input::leave(input);
+        __value
+    }}
+}
+
+/// The type decoded for `field`: its own, or `Compact<_>` around it.
+fn decoded_type(field: &syn::Field) -> syn::Result<TokenStream> {
+    let ty = &field.ty;
+    Ok(if field_attrs(&field.attrs)?.compact {
+        quote! { ::scale::Compact<#ty> }
+    } else {
+        quote! { #ty }
+    })
+}
+
+/// The only field of a newtype struct or variant. It gets no path segment
+/// of its own, so errors read `logs[2].Consensus` instead of
+/// `logs[2].Consensus.0`.
+fn newtype_field(fields: &Fields) -> Option<&syn::Field> {
+    match fields {
+        Fields::Unnamed(unnamed) if unnamed.unnamed.len() == 1 => unnamed.unnamed.first(),
+        _ => None,
+    }
+}
+
+/// Expression building `path` (a struct or variant) from `input`.
+fn decode_fields(path: TokenStream, fields: &Fields) -> syn::Result<TokenStream> {
+    let newtype = newtype_field(fields).is_some();
+    let mut values = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        let attrs = field_attrs(&field.attrs)?;
+        let ty = decoded_type(field)?;
+        let mut value = quote! { <#ty as ::scale::Decode>::decode(input)? };
+        if attrs.compact {
+            value = quote! { #value.0 };
+        }
+        values.push(if attrs.skip {
+            quote! { ::core::default::Default::default() }
+        } else if newtype {
+            value
+        } else {
+            let name = match &field.ident {
+                Some(ident) => ident.to_string(),
+                None => i.to_string(),
+            };
+            traced(quote! { ::scale::PathSegment::Field(#name) }, ty, value)
+        });
+    }
+    Ok(match fields {
+        Fields::Named(named) => {
+            let names = named.named.iter().map(|field| &field.ident);
+            quote! { #path { #(#names: #values),* } }
+        }
+        Fields::Unnamed(_) => quote! { #path(#(#values),*) },
+        Fields::Unit => quote! { #path },
+    })
+}
+
+/// Pattern binding the fields of a variant to `__field{n}`, with skipped
+/// fields ignored.
+fn variant_pattern(name: &Ident, fields: &Fields) -> syn::Result<TokenStream> {
+    let mut bindings = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        bindings.push(if field_attrs(&field.attrs)?.skip {
+            quote! { _ }
+        } else {
+            let binding = format_ident!("__field{}", i);
+            quote! { #binding }
+        });
+    }
+    Ok(match fields {
+        Fields::Named(named) => {
+            let names = named.named.iter().map(|field| &field.ident);
+            quote! { Self::#name { #(#names: #bindings),* } }
+        }
+        Fields::Unnamed(_) => quote! { Self::#name(#(#bindings),*) },
+        Fields::Unit => quote! { Self::#name },
+    })
+}
+
+fn expand_encode(input: DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let generics = add_bounds(input.generics.clone(), parse_quote!(::scale::Encode));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let (body, size) = match &input.data {
+        Data::Struct(data) => {
+            let access = |i: usize, field: &syn::Field| match &field.ident {
+                Some(ident) => quote! { &self.#ident },
+                None => {
+                    let index = Index::from(i);
+                    quote! { &self.#index }
+                }
+            };
+            let statements = encode_fields(&data.fields, access)?;
+            let sizes = size_fields(&data.fields, access)?;
+            (quote! { #(#statements)* }, quote! { 0 #(+ #sizes)* })
+        }
+        Data::Enum(data) => {
+            let indexes = variant_indexes(data)?;
+            let mut arms = Vec::new();
+            let mut size_arms = Vec::new();
+            for (variant, index) in data.variants.iter().zip(indexes) {
+                let pattern = variant_pattern(&variant.ident, &variant.fields)?;
+                let access = |i: usize, _: &syn::Field| {
+                    let binding = format_ident!("__field{}", i);
+                    quote! { #binding }
+                };
+                let statements = encode_fields(&variant.fields, access)?;
+                let sizes = size_fields(&variant.fields, access)?;
+                arms.push(quote! {
+                    #pattern => {
+                        ::scale::Output::push_byte(__dest, #index);
+                        #(#statements)*
+                    }
+                });
+                size_arms.push(quote! {
+                    #pattern => 1 #(+ #sizes)*,
+                });
+            }
+            (
+                quote! {
+                    match self {
+                        #(#arms)*
+                    }
+                },
+                quote! {
+                    match self {
+                        #(#size_arms)*
+                    }
+                },
+            )
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "unions cannot be SCALE-encoded",
+            ))
+        }
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::scale::Encode for #name #ty_generics #where_clause {
+            fn encode_to<__O: ::scale::Output + ?::core::marker::Sized>(&self, __dest: &mut __O) {
+                #body
+            }
+
+            fn encoded_size(&self) -> usize {
+                #size
+            }
+        }
+    })
+}
+
+fn expand_decode(input: DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let generics = add_bounds(input.generics.clone(), parse_quote!(::scale::Decode));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let value = decode_fields(quote! { Self }, &data.fields)?;
+            quote! { ::core::result::Result::Ok(#value) }
+        }
+        Data::Enum(data) => {
+            let indexes = variant_indexes(data)?;
+            let mut arms = Vec::new();
+            for (variant, index) in data.variants.iter().zip(indexes) {
+                let variant_name = &variant.ident;
+                let mut value = decode_fields(quote! { Self::#variant_name }, &variant.fields)?;
+                if !variant.fields.is_empty() {
+                    let name = variant_name.to_string();
+                    let expected = match newtype_field(&variant.fields) {
+                        Some(field) => decoded_type(field)?,
+                        None => quote! { Self },
+                    };
+                    value = traced(
+                        quote! { ::scale::PathSegment::Variant(#name) },
+                        expected,
+                        value,
+                    );
+                }
+                arms.push(quote! {
+                    #index => ::core::result::Result::Ok(#value),
+                });
+            }
+            let type_name = name.to_string();
+            quote! {
+                match <u8 as ::scale::Decode>::decode(input)? {
+                    #(#arms)*
+                    tag => ::core::result::Result::Err(::scale::DecodeError::UnknownVariant {
+                        type_name: #type_name,
+                        tag,
+                    }),
+                }
+            }
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "unions cannot be SCALE-decoded",
+            ))
+        }
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::scale::Decode for #name #ty_generics #where_clause {
+            fn decode<__I: ::scale::Input>(
+                input: &mut __I,
+            ) -> ::core::result::Result<Self, ::scale::DecodeError> {
+                ::scale::Input::descend(input)?;
+                let __decoded = (|| -> ::core::result::Result<Self, ::scale::DecodeError> {
+                    #body
+                })();
+                ::scale::Input::ascend(input);
+                __decoded
+            }
+        }
+    })
+}
+
+fn expand_max_encoded_len(input: DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let generics = add_bounds(input.generics.clone(), parse_quote!(::scale::MaxEncodedLen));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let body = match &input.data {
+        Data::Struct(data) => max_len_fields(&data.fields)?,
+        Data::Enum(data) => {
+            variant_indexes(data)?;
+            let mut lens = Vec::new();
+            for variant in &data.variants {
+                lens.push(max_len_fields(&variant.fields)?);
+            }
+            quote! {
+                let mut __max = 0usize;
+                #(__max = ::core::cmp::max(__max, #lens);)*
+                __max.saturating_add(1)
+            }
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "unions cannot be SCALE-encoded",
+            ))
+        }
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::scale::MaxEncodedLen for #name #ty_generics #where_clause {
+            fn max_encoded_len() -> usize {
+                #body
+            }
+        }
+    })
+}
+
+fn expand_type_info(input: DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let generics = add_bounds(input.generics.clone(), parse_quote!(::scale::TypeInfo));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let def = match &input.data {
+        Data::Struct(data) => {
+            let fields = type_info_fields(&data.fields)?;
+            quote! { ::scale::TypeDef::Composite(#fields) }
+        }
+        Data::Enum(data) => {
+            let indexes = variant_indexes(data)?;
+            let mut variants = Vec::new();
+            for (variant, index) in data.variants.iter().zip(indexes) {
+                let variant_name = variant.ident.to_string();
+                let fields = type_info_fields(&variant.fields)?;
+                variants.push(quote! { ::scale::Variant::new(#variant_name, #index, #fields) });
+            }
+            quote! { ::scale::TypeDef::Variant(::scale::__private::vec![#(#variants),*]) }
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "unions cannot be SCALE-encoded",
+            ))
+        }
+    };
+    let type_name = name.to_string();
+
+    Ok(quote! {
+        impl #impl_generics ::scale::TypeInfo for #name #ty_generics #where_clause {
+            fn type_info(__registry: &mut ::scale::Registry) -> ::scale::Type {
+                ::scale::Type::with_path(
+                    ::core::iter::Iterator::chain(
+                        ::core::module_path!().split("::"),
+                        [#type_name],
+                    ),
+                    #def,
+                )
+            }
+        }
+    })
+}
@@ -0,0 +1,203 @@
+// Differential tests of the examples' handwritten codecs against
+// `parity-scale-codec`, the codec the node uses. Random values must encode
+// to the same bytes, and corrupted encodings must decode to the same value
+// or fail in both.
+
+use std::fmt::Debug;
+
+/// Decodes a `T` from the front of `bytes` with parity-scale-codec,
+/// returning it with the number of bytes it took.
+fn parity_decode<T: codec::Decode>(bytes: &[u8]) -> Option<(T, usize)> {
+    let mut input = bytes;
+    let value = T::decode(&mut input).ok()?;
+    Some((value, bytes.len() - input.len()))
+}
+
+/// Asserts an example decoder and parity-scale-codec agree on `bytes`:
+/// the same value, or both fail.
+fn assert_agree<V: PartialEq + Debug>(
+    bytes: &[u8],
+    example: Result<V, scale::DecodeError>,
+    parity: Option<V>,
+) {
+    match (example, parity) {
+        (Ok(example), Some(parity)) => assert_eq!(example, parity, "input {:02x?}", bytes),
+        (Err(_), None) => {}
+        (example, parity) => panic!(
+            "example and parity disagree on {:02x?}: example {:?}, parity {:?}",
+            bytes, example, parity
+        ),
+    }
+}
+
+/// `bytes` truncated to `cut` bytes (if shorter) with the byte at `pos`
+/// (if any) XORed with `flip`.
+fn corrupt(mut bytes: Vec<u8>, cut: usize, pos: usize, flip: u8) -> Vec<u8> {
+    bytes.truncate(cut);
+    if !bytes.is_empty() {
+        let pos = pos % bytes.len();
+        bytes[pos] ^= flip;
+    }
+    bytes
+}
+
+// Each example goes in its own module, as in `fuzz_corpus.rs`, so its
+// private codecs can be called.
+
+#[allow(dead_code)]
+mod compact {
+    include!("../../examples/SCALE/02_scale_compact_encoding.rs");
+
+    use super::{assert_agree, corrupt, parity_decode};
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn differential(
+            value in (any::<u128>(), 0u32..128).prop_map(|(value, shift)| value >> shift),
+            cut in 0usize..20,
+            pos: usize,
+            flip: u8,
+        ) {
+            let encoded = encode_compact(value);
+            prop_assert_eq!(&encoded, &codec::Encode::encode(&codec::Compact(value)));
+
+            // Strict mode rejects what the node rejects; lenient mode also
+            // accepts longer encodings, but never a different value.
+            let corrupted = corrupt(encoded, cut, pos, flip);
+            let parity = parity_decode::<codec::Compact<u128>>(&corrupted).map(|(c, _)| c.0);
+            assert_agree(&corrupted, decode_compact(&corrupted, DecodeMode::Strict), parity);
+            if let Some(value) = parity {
+                prop_assert_eq!(decode_compact(&corrupted, DecodeMode::Lenient), Ok(value));
+            }
+        }
+    }
+}
+
+#[allow(dead_code)]
+mod enums {
+    include!("../../examples/SCALE/03_scale_enum_encoding.rs");
+
+    use super::{assert_agree, corrupt, parity_decode};
+    use proptest::prelude::*;
+
+    /// `MyScaleEnum` with parity-scale-codec's derived codec.
+    #[derive(Debug, Clone, codec::Encode, codec::Decode)]
+    enum Parity {
+        Foo(u16),
+        Bar(bool),
+        Baz,
+    }
+
+    impl From<Parity> for MyScaleEnum {
+        fn from(value: Parity) -> Self {
+            match value {
+                Parity::Foo(value) => MyScaleEnum::Foo(value),
+                Parity::Bar(value) => MyScaleEnum::Bar(value),
+                Parity::Baz => MyScaleEnum::Baz,
+            }
+        }
+    }
+
+    fn parity_enum() -> impl Strategy<Value = Parity> {
+        prop_oneof![
+            any::<u16>().prop_map(Parity::Foo),
+            any::<bool>().prop_map(Parity::Bar),
+            Just(Parity::Baz),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn differential(value in parity_enum(), cut in 0usize..4, pos: usize, flip: u8) {
+            let encoded = MyScaleEnum::from(value.clone()).encode();
+            prop_assert_eq!(&encoded, &codec::Encode::encode(&value));
+
+            let corrupted = corrupt(encoded, cut, pos, flip);
+            let parity = parity_decode::<Parity>(&corrupted).map(|(v, n)| (v.into(), n));
+            assert_agree(&corrupted, MyScaleEnum::decode(&corrupted), parity);
+        }
+    }
+}
+
+#[allow(dead_code)]
+mod vector {
+    include!("../../examples/SCALE/04_scale_vector_encoding.rs");
+
+    use super::{assert_agree, corrupt, parity_decode};
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn differential(
+            values in vec(any::<u64>().prop_map(|v| v >> (v % 64)), 0..20),
+            cut: usize,
+            pos: usize,
+            flip: u8,
+        ) {
+            let encoded = encode_vector_compact(&values);
+            let compacts: Vec<_> = values.iter().copied().map(codec::Compact).collect();
+            prop_assert_eq!(&encoded, &codec::Encode::encode(&compacts));
+
+            // The example reads empty input as an empty vector, where the
+            // node wants at least the length prefix.
+            let corrupted = corrupt(encoded, cut % 200, pos, flip);
+            prop_assume!(!corrupted.is_empty());
+            let parity = parity_decode::<Vec<codec::Compact<u64>>>(&corrupted)
+                .map(|(v, _)| v.into_iter().map(|c| c.0).collect::<Vec<_>>());
+            assert_agree(&corrupted, decode_vector_compact(&corrupted), parity);
+        }
+
+        #[test]
+        fn differential_u8(values in vec(any::<u8>(), 0..100), cut: usize, pos: usize, flip: u8) {
+            let encoded = encode_vector_u8(&values);
+            prop_assert_eq!(&encoded, &codec::Encode::encode(&values));
+
+            let corrupted = corrupt(encoded, cut % 120, pos, flip);
+            prop_assume!(!corrupted.is_empty());
+            let parity = parity_decode::<Vec<u8>>(&corrupted).map(|(v, _)| v);
+            assert_agree(&corrupted, decode_vector_u8(&corrupted), parity);
+        }
+    }
+}
+
+#[allow(dead_code)]
+mod array {
+    include!("../../examples/SCALE/05_scale_array_encoding.rs");
+
+    use super::{assert_agree, corrupt, parity_decode};
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn differential_tuples(value: [(u8, u32); 3], cut in 0usize..16, pos: usize, flip: u8) {
+            let encoded = encode_array(&value);
+            prop_assert_eq!(&encoded, &codec::Encode::encode(&value));
+
+            let corrupted = corrupt(encoded, cut, pos, flip);
+            let parity = parity_decode::<[(u8, u32); 3]>(&corrupted).map(|(v, _)| v);
+            assert_agree(&corrupted, decode_array::<(u8, u32), 3>(&corrupted), parity);
+        }
+
+        #[test]
+        fn differential_nested(value: [[u16; 2]; 2], cut in 0usize..10, pos: usize, flip: u8) {
+            let encoded = encode_array(&value);
+            prop_assert_eq!(&encoded, &codec::Encode::encode(&value));
+
+            let corrupted = corrupt(encoded, cut, pos, flip);
+            let parity = parity_decode::<[[u16; 2]; 2]>(&corrupted).map(|(v, _)| v);
+            assert_agree(&corrupted, decode_array::<[u16; 2], 2>(&corrupted), parity);
+        }
+
+        #[test]
+        fn differential_u64(value: [u64; 4], cut in 0usize..40, pos: usize, flip: u8) {
+            let encoded = encode_array(&value);
+            prop_assert_eq!(&encoded, &codec::Encode::encode(&value));
+
+            let corrupted = corrupt(encoded, cut, pos, flip);
+            let parity = parity_decode::<[u64; 4]>(&corrupted).map(|(v, _)| v);
+            assert_agree(&corrupted, decode_array::<u64, 4>(&corrupted), parity);
+        }
+    }
+}
@@ -0,0 +1,108 @@
+// Replays the committed fuzz corpus (see `fuzz/`) against the example
+// decoders on stable, so `cargo test` catches a decoder that panics instead
+// of returning an error. Every seed is also tried truncated at each length
+// and with each byte flipped.
+
+use std::fs;
+use std::path::Path;
+
+/// Calls `decode` on every seed of `fuzz/corpus/<target>` and on its
+/// truncations and single-byte corruptions.
+fn replay(target: &str, decode: impl Fn(&[u8])) {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../fuzz/corpus")
+        .join(target);
+    let mut seeds = 0;
+    for entry in fs::read_dir(&dir).unwrap_or_else(|e| panic!("{}: {}", dir.display(), e)) {
+        let bytes = fs::read(entry.unwrap().path()).unwrap();
+        for len in 0..=bytes.len() {
+            decode(&bytes[..len]);
+        }
+        for pos in 0..bytes.len() {
+            for flip in [0x01, 0x80, 0xff] {
+                let mut corrupted = bytes.clone();
+                corrupted[pos] ^= flip;
+                decode(&corrupted);
+            }
+        }
+        seeds += 1;
+    }
+    assert!(seeds > 0, "no seeds in {}", dir.display());
+}
+
+// Each example goes in its own module, next to the test that calls its
+// private decoders.
+
+#[allow(dead_code)]
+mod compact {
+    include!("../../examples/SCALE/02_scale_compact_encoding.rs");
+
+    #[test]
+    fn corpus() {
+        super::replay("compact", |data| {
+            for mode in [DecodeMode::Strict, DecodeMode::Lenient] {
+                let _ = decode_compact(data, mode);
+                let _ = decode_compact_big(data, mode);
+            }
+        });
+    }
+}
+
+#[allow(dead_code)]
+mod enums {
+    include!("../../examples/SCALE/03_scale_enum_encoding.rs");
+
+    #[test]
+    fn corpus() {
+        super::replay("enum", |data| {
+            let _ = MyScaleEnum::decode(data);
+        });
+    }
+}
+
+#[allow(dead_code)]
+mod vector {
+    include!("../../examples/SCALE/04_scale_vector_encoding.rs");
+
+    #[test]
+    fn corpus() {
+        super::replay("vector", |data| {
+            let _ = decode_vector_u8(data);
+            let _ = decode_vector_compact(data);
+            for mode in [DecodeMode::Strict, DecodeMode::Lenient] {
+                let _ = decode_compact(data, mode);
+            }
+        });
+    }
+}
+
+#[allow(dead_code)]
+mod array {
+    include!("../../examples/SCALE/05_scale_array_encoding.rs");
+
+    #[test]
+    fn corpus() {
+        super::replay("array", |data| {
+            let _ = decode_array_u8::<4>(data);
+            let _ = decode_array_u16::<3>(data);
+            let _ = decode_array::<(u8, u32), 3>(data);
+            let _ = decode_array::<[u16; 2], 2>(data);
+        });
+    }
+}
+
+#[allow(dead_code)]
+mod bitseq {
+    include!("../../examples/SCALE/07_scale_bitseq_encoding.rs");
+
+    #[test]
+    fn corpus() {
+        super::replay("bitseq", |data| {
+            for word_bits in [8, 16, 32, 64] {
+                for order in [BitOrder::Lsb0, BitOrder::Msb0] {
+                    let _ = decode_bitseq(data, word_bits, order);
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,101 @@
+//! A `#![no_std]` crate using the `scale` codec the way on-chain or
+//! embedded code would: compact integers, a derived enum, vectors and
+//! arrays, with nothing but `core` and `alloc`.
+//!
+//! Built on its own (`cargo build -p scale-no-std`), `scale` is compiled
+//! without its `std` feature, so any use of `std` in the codec core or in
+//! the code the derives generate fails the build. `tests/build.rs` does
+//! exactly that from `cargo test`.
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+use scale::{Compact, Decode, DecodeError, Encode, MaxEncodedLen};
+
+/// A runtime call, as a pallet would declare it.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub enum Call {
+    Remark(Vec<u8>),
+    Transfer {
+        dest: [u8; 32],
+        #[codec(compact)]
+        amount: u128,
+    },
+    #[codec(index = 5)]
+    SetKeys(Vec<[u8; 4]>),
+}
+
+/// A fixed-size record, e.g. for a device with no allocator to spare.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode, MaxEncodedLen)]
+pub struct Reading {
+    pub sensor: u8,
+    pub samples: [u16; 3],
+    pub ok: bool,
+}
+
+/// A batch of calls with their compact nonce, encoded for submission.
+pub fn encode_batch(nonce: u64, calls: &[Call]) -> Vec<u8> {
+    let mut encoded = Compact(nonce).encode();
+    calls.encode_to(&mut encoded);
+    encoded
+}
+
+/// The reverse of [`encode_batch`], rejecting trailing bytes.
+pub fn decode_batch(bytes: &[u8]) -> Result<(u64, Vec<Call>), DecodeError> {
+    let (Compact(nonce), calls) = <(Compact<u64>, Vec<Call>)>::decode_all(bytes)?;
+    Ok((nonce, calls))
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+    use scale::{ArrayOutput, PathSegment};
+
+    #[test]
+    fn batch_round_trips() {
+        let calls = vec![
+            Call::Remark(vec![0xde, 0xad]),
+            Call::Transfer {
+                dest: [7; 32],
+                amount: 1_000_000,
+            },
+            Call::SetKeys(vec![*b"aura", *b"gran"]),
+        ];
+        let encoded = encode_batch(300, &calls);
+        assert_eq!(encoded[..3], [0xb1, 0x04, 0x0c]);
+        assert_eq!(encoded[3..6], [0x00, 0x08, 0xde]);
+        assert_eq!(decode_batch(&encoded), Ok((300, calls)));
+        assert_eq!(
+            decode_batch(&[0x00, 0x04, 0x03]),
+            Err(DecodeError::UnknownVariant {
+                type_name: "Call",
+                tag: 3
+            }
+            .within(
+                2,
+                "Call",
+                "(Compact<u64>, Vec<Call>)",
+                vec![PathSegment::Index(0)]
+            ))
+        );
+    }
+
+    #[test]
+    fn encodes_into_a_stack_buffer() {
+        let reading = Reading {
+            sensor: 2,
+            samples: [1, 2, 0x0300],
+            ok: true,
+        };
+        assert_eq!(Reading::max_encoded_len(), 8);
+        let mut output = ArrayOutput::<8>::new();
+        reading.encode_to(&mut output);
+        assert_eq!(output.as_slice(), [2, 1, 0, 2, 0, 0, 3, 1]);
+        assert_eq!(Reading::decode_all(output.as_slice()), Ok(reading));
+    }
+}
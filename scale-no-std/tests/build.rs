@@ -0,0 +1,26 @@
+// Builds this crate on its own, in a separate target directory, so `scale`
+// is compiled without `std` even when `cargo test --workspace` unifies its
+// features with the packages that enable them.
+
+use std::process::Command;
+
+#[test]
+fn builds_without_std() {
+    let target_dir = concat!(env!("CARGO_TARGET_TMPDIR"), "/no-std");
+    let output = Command::new(env!("CARGO"))
+        .args([
+            "check",
+            "--package",
+            "scale-no-std",
+            "--target-dir",
+            target_dir,
+        ])
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .output()
+        .expect("failed to run cargo");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
@@ -0,0 +1,90 @@
+// SCALE Array encoding: fixed-size arrays encode their elements back to
+// back. The size is part of the type and is NOT written to the byte stream.
+
+use crate::{Decode, DecodeError, Encode, Input, Output};
+
+impl<T: Encode, const N: usize> Encode for [T; N] {
+    fn encode_to<O: Output + ?Sized>(&self, dest: &mut O) {
+        T::encode_slice(self, dest);
+    }
+
+    fn encoded_size(&self) -> usize {
+        T::encoded_size_slice(self)
+    }
+}
+
+impl<T: Decode, const N: usize> Decode for [T; N] {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, DecodeError> {
+        T::decode_array(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Compact;
+
+    #[test]
+    fn array_u8() {
+        let value: [u8; 4] = [2, 1, 3, 0];
+        assert_eq!(value.encode(), vec![0x02, 0x01, 0x03, 0x00]);
+        assert_eq!(<[u8; 4]>::decode_all(&value.encode()), Ok(value));
+    }
+
+    #[test]
+    fn array_u16() {
+        let value: [u16; 2] = [258, 3];
+        assert_eq!(value.encode(), vec![0x02, 0x01, 0x03, 0x00]);
+        assert_eq!(<[u16; 2]>::decode_all(&value.encode()), Ok(value));
+
+        let value: [u16; 4] = [2, 1, 3, 0];
+        assert_eq!(
+            value.encode(),
+            vec![0x02, 0x00, 0x01, 0x00, 0x03, 0x00, 0x00, 0x00]
+        );
+    }
+
+    #[test]
+    fn array_of_hashes() {
+        let value = [[0xaau8; 32], [0xbb; 32]];
+        let encoded = value.encode();
+        assert_eq!(encoded.len(), 64);
+        assert_eq!(<[[u8; 32]; 2]>::decode_all(&encoded), Ok(value));
+    }
+
+    #[test]
+    fn array_of_tuples() {
+        let value: [(u32, u32); 4] = [(1, 2), (3, 4), (5, 6), (7, 8)];
+        let encoded = value.encode();
+        assert_eq!(encoded.len(), 32);
+        assert_eq!(&encoded[..8], &[1, 0, 0, 0, 2, 0, 0, 0]);
+        assert_eq!(<[(u32, u32); 4]>::decode_all(&encoded), Ok(value));
+    }
+
+    #[test]
+    fn array_of_compacts() {
+        let value = [Compact(1u32), Compact(64), Compact(16384)];
+        let encoded = value.encode();
+        assert_eq!(encoded, vec![0x04, 0x01, 0x01, 0x02, 0x00, 0x01, 0x00]);
+        assert_eq!(<[Compact<u32>; 3]>::decode_all(&encoded), Ok(value));
+    }
+
+    #[test]
+    fn empty_array() {
+        assert_eq!(<[u64; 0]>::encode(&[]), Vec::<u8>::new());
+        assert_eq!(<[u64; 0]>::decode_all(&[]), Ok([]));
+    }
+
+    #[test]
+    fn array_too_short() {
+        assert!(<[u8; 4]>::decode_all(&[0x01, 0x02]).is_err());
+        assert!(<[u16; 2]>::decode_all(&[0x01, 0x02, 0x03]).is_err());
+        assert_eq!(
+            <[(u8, bool); 2]>::decode_all(&[0x01, 0x01, 0x02]).map_err(DecodeError::into_kind),
+            Err(DecodeError::NotEnoughData {
+                expected: 1,
+                remaining: 0
+            })
+        );
+    }
+}
@@ -0,0 +1,297 @@
+// SCALE bit sequences, laid out like `bitvec`'s `BitVec<Store, Order>`: a
+// compact bit count followed by the bits packed into store words, each word
+// encoded as a fixed-width little-endian integer. Used for parachain
+// availability bitfields.
+
+use core::fmt;
+use core::marker::PhantomData;
+
+use crate::compact::{compact_len, encode_compact};
+use crate::prelude::*;
+use crate::vector::decode_len;
+use crate::{Decode, DecodeError, Encode, Input, Output};
+
+/// An unsigned integer type that bits are packed into.
+pub trait BitStore: Copy + Default + Eq + Encode + Decode {
+    /// Number of bits in one word.
+    const BITS: usize;
+
+    /// Reads the bit at `position`, counted from the least significant bit.
+    fn bit(self, position: usize) -> bool;
+
+    /// Writes the bit at `position`, counted from the least significant bit.
+    fn set_bit(&mut self, position: usize, value: bool);
+}
+
+macro_rules! impl_bit_store {
+    ($($t:ty),*) => {
+        $(
+            impl BitStore for $t {
+                const BITS: usize = <$t>::BITS as usize;
+
+                fn bit(self, position: usize) -> bool {
+                    self >> position & 1 == 1
+                }
+
+                fn set_bit(&mut self, position: usize, value: bool) {
+                    if value {
+                        *self |= 1 << position;
+                    } else {
+                        *self &= !(1 << position);
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_bit_store!(u8, u16, u32, u64);
+
+/// The order in which the bits of a sequence fill each store word.
+pub trait BitOrder: Copy + Default + Eq {
+    /// Name used when printing a sequence.
+    const NAME: &'static str;
+
+    /// Position within a `bits`-wide word, counted from the least
+    /// significant bit, of the sequence bit with in-word index `index`.
+    fn position(index: usize, bits: usize) -> usize;
+}
+
+/// Fills each word from the least significant bit up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Lsb0;
+
+impl BitOrder for Lsb0 {
+    const NAME: &'static str = "Lsb0";
+
+    fn position(index: usize, _bits: usize) -> usize {
+        index
+    }
+}
+
+/// Fills each word from the most significant bit down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Msb0;
+
+impl BitOrder for Msb0 {
+    const NAME: &'static str = "Msb0";
+
+    fn position(index: usize, bits: usize) -> usize {
+        bits - 1 - index
+    }
+}
+
+/// A sequence of bits packed into `S` words in `O` order, encoded like
+/// `bitvec::BitVec<S, O>`.
+///
+/// Unused bits of the last word are always zero; decoding clears them.
+///
+/// ```
+/// use scale::{BitSeq, Encode, Msb0};
+///
+/// let bits: BitSeq<u8, Msb0> = [true, false, true].into_iter().collect();
+/// assert_eq!(bits.encode(), vec![0x0c, 0b1010_0000]);
+/// ```
+#[derive(Clone, PartialEq, Eq, Default)]
+pub struct BitSeq<S = u8, O = Lsb0> {
+    words: Vec<S>,
+    len: usize,
+    order: PhantomData<O>,
+}
+
+impl<S: BitStore, O: BitOrder> BitSeq<S, O> {
+    pub fn new() -> Self {
+        BitSeq {
+            words: Vec::new(),
+            len: 0,
+            order: PhantomData,
+        }
+    }
+
+    /// Number of bits in the sequence.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The packed store words.
+    pub fn as_words(&self) -> &[S] {
+        &self.words
+    }
+
+    /// Word index and in-word position of bit `index`.
+    fn locate(index: usize) -> (usize, usize) {
+        (index / S::BITS, O::position(index % S::BITS, S::BITS))
+    }
+
+    pub fn get(&self, index: usize) -> Option<bool> {
+        if index >= self.len {
+            return None;
+        }
+        let (word, position) = Self::locate(index);
+        Some(self.words[word].bit(position))
+    }
+
+    /// Sets bit `index`.
+    ///
+    /// # Panics
+    ///
+    /// If `index` is out of bounds.
+    pub fn set(&mut self, index: usize, value: bool) {
+        assert!(index < self.len, "bit index {} out of bounds", index);
+        let (word, position) = Self::locate(index);
+        self.words[word].set_bit(position, value);
+    }
+
+    pub fn push(&mut self, value: bool) {
+        if self.len.is_multiple_of(S::BITS) {
+            self.words.push(S::default());
+        }
+        self.len += 1;
+        self.set(self.len - 1, value);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.len).map(|index| {
+            let (word, position) = Self::locate(index);
+            self.words[word].bit(position)
+        })
+    }
+}
+
+impl<S: BitStore, O: BitOrder> FromIterator<bool> for BitSeq<S, O> {
+    fn from_iter<T: IntoIterator<Item = bool>>(iter: T) -> Self {
+        let mut bits = Self::new();
+        for value in iter {
+            bits.push(value);
+        }
+        bits
+    }
+}
+
+// Prints the bits in sequence order, e.g. `BitSeq<u8, Lsb0>[101]`.
+impl<S: BitStore, O: BitOrder> fmt::Debug for BitSeq<S, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "BitSeq<{}, {}>[", core::any::type_name::<S>(), O::NAME)?;
+        for value in self.iter() {
+            f.write_str(if value { "1" } else { "0" })?;
+        }
+        f.write_str("]")
+    }
+}
+
+impl<S: BitStore, O: BitOrder> Encode for BitSeq<S, O> {
+    fn encode_to<Out: Output + ?Sized>(&self, dest: &mut Out) {
+        encode_compact(self.len as u128, dest);
+        S::encode_slice(&self.words, dest);
+    }
+
+    fn encoded_size(&self) -> usize {
+        compact_len(self.len as u128) + self.words.len() * core::mem::size_of::<S>()
+    }
+}
+
+impl<S: BitStore, O: BitOrder> Decode for BitSeq<S, O> {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, DecodeError> {
+        let len = decode_len(input)?;
+        let num_words = len.div_ceil(S::BITS);
+        input.on_collection(len, num_words * core::mem::size_of::<S>())?;
+        let mut bits = BitSeq {
+            words: S::decode_vec(input, num_words)?,
+            len,
+            order: PhantomData,
+        };
+        // Padding bits carry no meaning; clear them so equal sequences
+        // compare equal.
+        for index in len..bits.words.len() * S::BITS {
+            let (word, position) = Self::locate(index);
+            bits.words[word].set_bit(position, false);
+        }
+        Ok(bits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern<S: BitStore, O: BitOrder>(len: usize) -> BitSeq<S, O> {
+        (0..len).map(|i| i % 3 == 0).collect()
+    }
+
+    #[test]
+    fn lsb0_u8() {
+        let bits: BitSeq<u8, Lsb0> = [true, false, true, true].into_iter().collect();
+        assert_eq!(bits.encode(), vec![0x10, 0b0000_1101]);
+        assert_eq!(BitSeq::<u8, Lsb0>::decode_all(&bits.encode()), Ok(bits));
+    }
+
+    #[test]
+    fn msb0_u8() {
+        let bits: BitSeq<u8, Msb0> = [true, false, true, true].into_iter().collect();
+        assert_eq!(bits.encode(), vec![0x10, 0b1011_0000]);
+    }
+
+    #[test]
+    fn wide_stores_are_little_endian_words() {
+        // Bit 9 lands in the second byte of the word.
+        let mut lsb: BitSeq<u32, Lsb0> = std::iter::repeat_n(false, 10).collect();
+        lsb.set(9, true);
+        assert_eq!(lsb.encode(), vec![0x28, 0x00, 0x02, 0x00, 0x00]);
+
+        // With Msb0, bit 0 is the top bit of the word, i.e. the last byte.
+        let msb: BitSeq<u64, Msb0> = [true].into_iter().collect();
+        assert_eq!(msb.encode(), vec![0x04, 0, 0, 0, 0, 0, 0, 0, 0x80]);
+    }
+
+    #[test]
+    fn all_stores_and_orders_round_trip() {
+        fn check<S: BitStore, O: BitOrder>() {
+            for len in [0, 1, 7, 8, 9, 31, 32, 33, 64, 65, 200] {
+                let bits = pattern::<S, O>(len);
+                assert_eq!(bits.as_words().len(), len.div_ceil(S::BITS));
+                let decoded = BitSeq::<S, O>::decode_all(&bits.encode()).unwrap();
+                assert!(decoded.iter().eq(bits.iter()));
+                assert_eq!(decoded, bits);
+            }
+        }
+        check::<u8, Lsb0>();
+        check::<u8, Msb0>();
+        check::<u16, Lsb0>();
+        check::<u16, Msb0>();
+        check::<u32, Lsb0>();
+        check::<u32, Msb0>();
+        check::<u64, Lsb0>();
+        check::<u64, Msb0>();
+    }
+
+    #[test]
+    fn decode_clears_padding() {
+        let decoded = BitSeq::<u8, Lsb0>::decode_all(&[0x08, 0xff]).unwrap();
+        assert_eq!(decoded.as_words(), &[0b0000_0011]);
+        assert_eq!(decoded, [true, true].into_iter().collect());
+    }
+
+    #[test]
+    fn truncated_words() {
+        assert_eq!(
+            BitSeq::<u32, Lsb0>::decode_all(&[0x84, 0xff, 0xff, 0xff, 0xff, 0x01])
+                .map_err(DecodeError::into_kind),
+            Err(DecodeError::NotEnoughData {
+                expected: 4,
+                remaining: 1
+            })
+        );
+    }
+
+    #[test]
+    fn debug_shows_bits() {
+        let bits: BitSeq<u32, Msb0> = [true, false, true].into_iter().collect();
+        assert_eq!(format!("{:?}", bits), "BitSeq<u32, Msb0>[101]");
+        assert_eq!(bits.get(2), Some(true));
+        assert_eq!(bits.get(3), None);
+    }
+}
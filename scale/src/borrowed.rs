@@ -0,0 +1,202 @@
+// Zero-copy decoding: byte vectors, strings and byte arrays returned as
+// slices into the input instead of freshly allocated copies.
+
+use crate::context::decode_all_tracked;
+use crate::input::read_bytes;
+use crate::vector::decode_len;
+use crate::{Decode, DecodeError, DecodeLimits, Input};
+
+/// A type that can be decoded by borrowing from an in-memory input.
+///
+/// `&'a [u8]` and `&'a str` read the same bytes as `Vec<u8>` and `String`
+/// (a compact length followed by the data), and `&'a [u8; N]` the same as
+/// `[u8; N]`, but point into the input rather than copying it. Every
+/// [`Decode`] type is also `DecodeBorrowed`, so borrowed and owned fields
+/// can be decoded side by side.
+///
+/// ```
+/// use scale::DecodeBorrowed;
+///
+/// let bytes = [0x0c, 0x01, 0x02, 0x03, 0xff];
+/// let mut input = &bytes[..];
+/// let payload = <&[u8]>::decode_borrowed(&mut input).unwrap();
+/// assert_eq!(payload, &[0x01, 0x02, 0x03]);
+/// assert_eq!(u8::decode_borrowed(&mut input), Ok(0xff));
+/// ```
+pub trait DecodeBorrowed<'a>: Sized {
+    /// Decodes a value from the front of `input`, advancing it past the
+    /// consumed bytes.
+    fn decode_borrowed(input: &mut &'a [u8]) -> Result<Self, DecodeError>;
+
+    /// Decodes a value that must span the whole of `bytes`. The types of
+    /// this crate do so like [`Decode::decode_all`]: within
+    /// [`DecodeLimits::default`] and with errors located.
+    fn decode_all_borrowed(mut bytes: &'a [u8]) -> Result<Self, DecodeError> {
+        let value = Self::decode_borrowed(&mut bytes)?;
+        if !bytes.is_empty() {
+            return Err(DecodeError::TrailingBytes(bytes.len()));
+        }
+        Ok(value)
+    }
+}
+
+impl<'a, T: Decode> DecodeBorrowed<'a> for T {
+    fn decode_borrowed(input: &mut &'a [u8]) -> Result<Self, DecodeError> {
+        T::decode(input)
+    }
+
+    fn decode_all_borrowed(bytes: &'a [u8]) -> Result<Self, DecodeError> {
+        T::decode_all(bytes)
+    }
+}
+
+impl<'a> DecodeBorrowed<'a> for &'a [u8] {
+    fn decode_borrowed(input: &mut &'a [u8]) -> Result<Self, DecodeError> {
+        borrow_bytes(input)
+    }
+
+    /// Within [`DecodeLimits::default`] and with errors located, as
+    /// [`Decode::decode_all`] of a `Vec<u8>`.
+    fn decode_all_borrowed(bytes: &'a [u8]) -> Result<Self, DecodeError> {
+        decode_all_tracked(bytes, DecodeLimits::default(), borrow_bytes)
+    }
+}
+
+impl<'a> DecodeBorrowed<'a> for &'a str {
+    fn decode_borrowed(input: &mut &'a [u8]) -> Result<Self, DecodeError> {
+        borrow_str(input)
+    }
+
+    /// Within [`DecodeLimits::default`] and with errors located, as
+    /// [`Decode::decode_all`] of a `String`.
+    fn decode_all_borrowed(bytes: &'a [u8]) -> Result<Self, DecodeError> {
+        decode_all_tracked(bytes, DecodeLimits::default(), borrow_str)
+    }
+}
+
+impl<'a, const N: usize> DecodeBorrowed<'a> for &'a [u8; N] {
+    fn decode_borrowed(input: &mut &'a [u8]) -> Result<Self, DecodeError> {
+        borrow_array(input)
+    }
+
+    /// With errors located, as [`Decode::decode_all`] of a `[u8; N]`.
+    fn decode_all_borrowed(bytes: &'a [u8]) -> Result<Self, DecodeError> {
+        decode_all_tracked(bytes, DecodeLimits::default(), borrow_array)
+    }
+}
+
+/// An input that can lend out the bytes it reads instead of copying them:
+/// a byte slice, and the inputs [`Decode::decode_all`] wraps around one.
+pub(crate) trait BorrowInput<'a>: Input {
+    /// Reads `len` bytes as a slice of the underlying bytes.
+    fn read_borrowed(&mut self, len: usize) -> Result<&'a [u8], DecodeError>;
+}
+
+impl<'a> BorrowInput<'a> for &'a [u8] {
+    fn read_borrowed(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        read_bytes(self, len)
+    }
+}
+
+fn borrow_bytes<'a, I: BorrowInput<'a>>(input: &mut I) -> Result<&'a [u8], DecodeError> {
+    let len = decode_len(input)?;
+    // Nothing is allocated, but the length is bounded as a `Vec<u8>`'s.
+    input.on_collection(len, 0)?;
+    input.read_borrowed(len)
+}
+
+fn borrow_str<'a, I: BorrowInput<'a>>(input: &mut I) -> Result<&'a str, DecodeError> {
+    let bytes = borrow_bytes(input)?;
+    core::str::from_utf8(bytes).map_err(|e| DecodeError::InvalidUtf8 {
+        valid_up_to: e.valid_up_to(),
+    })
+}
+
+fn borrow_array<'a, I: BorrowInput<'a>, const N: usize>(
+    input: &mut I,
+) -> Result<&'a [u8; N], DecodeError> {
+    let bytes = input.read_borrowed(N)?;
+    Ok(bytes
+        .try_into()
+        .expect("read_borrowed returns exactly N bytes"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Compact, Encode};
+
+    #[test]
+    fn borrowed_bytes_point_into_input() {
+        let encoded = vec![1u8, 2, 3].encode();
+        let bytes = <&[u8]>::decode_all_borrowed(&encoded).unwrap();
+        assert_eq!(bytes, &[1, 2, 3]);
+        assert!(std::ptr::eq(bytes.as_ptr(), encoded[1..].as_ptr()));
+    }
+
+    #[test]
+    fn borrowed_str() {
+        let encoded = [0x14, b'h', b'e', b'l', b'l', b'o'];
+        assert_eq!(<&str>::decode_all_borrowed(&encoded), Ok("hello"));
+
+        let invalid = [0x0c, b'a', 0xff, b'b'];
+        assert_eq!(
+            <&str>::decode_all_borrowed(&invalid),
+            Err(DecodeError::InvalidUtf8 { valid_up_to: 1 }.at(1, "&str"))
+        );
+    }
+
+    #[test]
+    fn borrowed_array() {
+        let encoded = [0xaa; 32];
+        let hash = <&[u8; 32]>::decode_all_borrowed(&encoded).unwrap();
+        assert_eq!(hash, &[0xaa; 32]);
+        assert!(<&[u8; 32]>::decode_all_borrowed(&encoded[..31]).is_err());
+    }
+
+    #[test]
+    fn mixed_with_owned_values() {
+        let mut encoded = Compact(7u32).encode();
+        encoded.extend(vec![9u8, 9].encode());
+        let mut input = &encoded[..];
+        assert_eq!(Compact::<u32>::decode_borrowed(&mut input), Ok(Compact(7)));
+        assert_eq!(<&[u8]>::decode_borrowed(&mut input), Ok(&[9u8, 9][..]));
+        assert!(input.is_empty());
+    }
+
+    #[test]
+    fn truncated_payload() {
+        assert_eq!(
+            <&[u8]>::decode_all_borrowed(&[0x0c, 0x01]),
+            Err(DecodeError::NotEnoughData {
+                expected: 3,
+                remaining: 1
+            }
+            .at(1, "&[u8]"))
+        );
+        assert_eq!(
+            <&[u8; 2]>::decode_all_borrowed(&[0x01, 0x02, 0x03]),
+            Err(DecodeError::TrailingBytes(1).at(2, "&[u8; 2]"))
+        );
+        // The slice on its own is neither located nor limited.
+        let mut input = &[0x0c, 0x01][..];
+        assert_eq!(
+            <&[u8]>::decode_borrowed(&mut input),
+            Err(DecodeError::NotEnoughData {
+                expected: 3,
+                remaining: 1
+            })
+        );
+    }
+
+    #[test]
+    fn length_is_limited() {
+        // Five bytes claiming 2^30 bytes.
+        let hostile = [0x03, 0x00, 0x00, 0x00, 0x40];
+        let error = <&[u8]>::decode_all_borrowed(&hostile).unwrap_err();
+        assert!(matches!(
+            error.kind(),
+            DecodeError::CollectionTooLong { len, .. } if *len == 1 << 30
+        ));
+    }
+}
@@ -0,0 +1,174 @@
+// SCALE encoding of ordered maps and sets: a compact length prefix (as for
+// vectors) followed by the entries in ascending key order. Map entries are
+// encoded as `(key, value)` tuples.
+
+use alloc::collections::{BTreeMap, BTreeSet};
+
+use crate::compact::{compact_len, encode_compact};
+use crate::input::nested;
+use crate::vector::decode_len;
+use crate::{Decode, DecodeError, DecodeMode, DecodeWithMode, Encode, Input, Output, PathSegment};
+
+impl<K: Encode, V: Encode> Encode for BTreeMap<K, V> {
+    fn encode_to<O: Output + ?Sized>(&self, dest: &mut O) {
+        encode_compact(self.len() as u128, dest);
+        for (key, value) in self {
+            key.encode_to(dest);
+            value.encode_to(dest);
+        }
+    }
+
+    fn encoded_size(&self) -> usize {
+        let entries: usize = self
+            .iter()
+            .map(|(key, value)| key.encoded_size() + value.encoded_size())
+            .sum();
+        compact_len(self.len() as u128) + entries
+    }
+}
+
+impl<K: Decode + Ord, V: Decode> Decode for BTreeMap<K, V> {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, DecodeError> {
+        Self::decode_with_mode(input, DecodeMode::Strict)
+    }
+}
+
+/// In [`DecodeMode::Strict`] keys must be strictly ascending, as produced
+/// by the encoder. [`DecodeMode::Lenient`] accepts any order, with later
+/// duplicates replacing earlier ones.
+impl<K: Decode + Ord, V: Decode> DecodeWithMode for BTreeMap<K, V> {
+    fn decode_with_mode<I: Input>(input: &mut I, mode: DecodeMode) -> Result<Self, DecodeError> {
+        let len = decode_len(input)?;
+        input.on_collection(len, len.saturating_mul(core::mem::size_of::<(K, V)>()))?;
+        nested(input, |input| {
+            let mut map = BTreeMap::new();
+            for index in 0..len {
+                input.enter(PathSegment::Index(index), core::any::type_name::<(K, V)>());
+                let key = K::decode(input)?;
+                let value = V::decode(input)?;
+                if mode == DecodeMode::Strict {
+                    if let Some((last, _)) = map.last_key_value() {
+                        if key <= *last {
+                            return Err(DecodeError::UnorderedKeys);
+                        }
+                    }
+                }
+                map.insert(key, value);
+                input.leave();
+            }
+            Ok(map)
+        })
+    }
+}
+
+impl<T: Encode> Encode for BTreeSet<T> {
+    fn encode_to<O: Output + ?Sized>(&self, dest: &mut O) {
+        encode_compact(self.len() as u128, dest);
+        for value in self {
+            value.encode_to(dest);
+        }
+    }
+
+    fn encoded_size(&self) -> usize {
+        compact_len(self.len() as u128) + self.iter().map(Encode::encoded_size).sum::<usize>()
+    }
+}
+
+impl<T: Decode + Ord> Decode for BTreeSet<T> {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, DecodeError> {
+        Self::decode_with_mode(input, DecodeMode::Strict)
+    }
+}
+
+/// Same ordering rules as for `BTreeMap` keys.
+impl<T: Decode + Ord> DecodeWithMode for BTreeSet<T> {
+    fn decode_with_mode<I: Input>(input: &mut I, mode: DecodeMode) -> Result<Self, DecodeError> {
+        let len = decode_len(input)?;
+        input.on_collection(len, len.saturating_mul(core::mem::size_of::<T>()))?;
+        nested(input, |input| {
+            let mut set = BTreeSet::new();
+            for index in 0..len {
+                input.enter(PathSegment::Index(index), core::any::type_name::<T>());
+                let value = T::decode(input)?;
+                if mode == DecodeMode::Strict {
+                    if let Some(last) = set.last() {
+                        if value <= *last {
+                            return Err(DecodeError::UnorderedKeys);
+                        }
+                    }
+                }
+                set.insert(value);
+                input.leave();
+            }
+            Ok(set)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn map_is_sorted_entries() {
+        let map = BTreeMap::from([(3u8, true), (1, false)]);
+        let encoded = map.encode();
+        assert_eq!(encoded, vec![0x08, 0x01, 0x00, 0x03, 0x01]);
+        assert_eq!(BTreeMap::<u8, bool>::decode_all(&encoded), Ok(map));
+    }
+
+    #[test]
+    fn map_matches_vector_of_tuples() {
+        let map = BTreeMap::from([(b"alice".to_vec(), 10u64), (b"bob".to_vec(), 20)]);
+        let entries: Vec<(Vec<u8>, u64)> = map.clone().into_iter().collect();
+        assert_eq!(map.encode(), entries.encode());
+    }
+
+    #[test]
+    fn set_round_trip() {
+        let set = BTreeSet::from([300u16, 2, 40]);
+        let encoded = set.encode();
+        assert_eq!(encoded, vec![0x0c, 0x02, 0x00, 0x28, 0x00, 0x2c, 0x01]);
+        assert_eq!(BTreeSet::<u16>::decode_all(&encoded), Ok(set));
+        assert_eq!(BTreeSet::<u16>::decode_all(&[0x00]), Ok(BTreeSet::new()));
+    }
+
+    #[test]
+    fn strict_rejects_unsorted_and_duplicate_keys() {
+        let unsorted = [0x08, 0x03, 0x01, 0x01, 0x00];
+        let duplicate = [0x08, 0x01, 0x01, 0x01, 0x00];
+        for bytes in [&unsorted[..], &duplicate[..]] {
+            assert_eq!(
+                BTreeMap::<u8, bool>::decode_all(bytes).map_err(DecodeError::into_kind),
+                Err(DecodeError::UnorderedKeys)
+            );
+        }
+        assert_eq!(
+            BTreeSet::<u8>::decode_all(&[0x0c, 0x01, 0x02, 0x02]).map_err(DecodeError::into_kind),
+            Err(DecodeError::UnorderedKeys)
+        );
+    }
+
+    #[test]
+    fn lenient_accepts_any_order() {
+        let unsorted = [0x08, 0x03, 0x01, 0x01, 0x00];
+        assert_eq!(
+            BTreeMap::<u8, bool>::decode_with_mode(&mut &unsorted[..], DecodeMode::Lenient),
+            Ok(BTreeMap::from([(1, false), (3, true)]))
+        );
+
+        // The later duplicate wins.
+        let duplicate = [0x08, 0x01, 0x01, 0x01, 0x00];
+        assert_eq!(
+            BTreeMap::<u8, bool>::decode_with_mode(&mut &duplicate[..], DecodeMode::Lenient),
+            Ok(BTreeMap::from([(1, false)]))
+        );
+        assert_eq!(
+            BTreeSet::<u8>::decode_with_mode(
+                &mut &[0x0c, 0x02, 0x01, 0x02][..],
+                DecodeMode::Lenient
+            ),
+            Ok(BTreeSet::from([1, 2]))
+        );
+    }
+}
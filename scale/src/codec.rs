@@ -0,0 +1,389 @@
+// Core Encode/Decode traits and the fixed-width primitive implementations.
+
+use crate::input::{read_vec, MAX_PREALLOCATION};
+use crate::prelude::*;
+use crate::{DecodeError, DecodeLimits, Input, Output, PathSegment};
+
+/// A type that can be converted into SCALE bytes.
+///
+/// Implementations provide [`Encode::encode_to`] or [`Encode::encode`]; each
+/// defaults to the other.
+pub trait Encode {
+    /// Appends the encoding of `self` to `dest`.
+    fn encode_to<O: Output + ?Sized>(&self, dest: &mut O) {
+        dest.write(&self.encode());
+    }
+
+    /// Encodes `self` into a freshly allocated byte vector.
+    fn encode(&self) -> Vec<u8> {
+        let mut encoded = Vec::new();
+        self.encode_to(&mut encoded);
+        encoded
+    }
+
+    /// Exact number of bytes [`Encode::encode`] produces, computed without
+    /// encoding.
+    fn encoded_size(&self) -> usize {
+        self.encode().len()
+    }
+
+    /// Encodes an `Option<Self>`. Only `bool` overrides this, for the
+    /// single-byte `Option<bool>` form.
+    #[doc(hidden)]
+    fn encode_option<O: Output + ?Sized>(value: Option<&Self>, dest: &mut O)
+    where
+        Self: Sized,
+    {
+        match value {
+            None => dest.push_byte(0x00),
+            Some(value) => {
+                dest.push_byte(0x01);
+                value.encode_to(dest);
+            }
+        }
+    }
+
+    /// Encoded size of an `Option<Self>`, matching
+    /// [`Encode::encode_option`].
+    #[doc(hidden)]
+    fn encoded_size_option(value: Option<&Self>) -> usize
+    where
+        Self: Sized,
+    {
+        match value {
+            None => 1,
+            Some(value) => 1 + value.encoded_size(),
+        }
+    }
+
+    /// Encoded size of the elements of `values`, matching
+    /// [`Encode::encode_slice`].
+    #[doc(hidden)]
+    fn encoded_size_slice(values: &[Self]) -> usize
+    where
+        Self: Sized,
+    {
+        values.iter().map(Encode::encoded_size).sum()
+    }
+
+    /// Encodes the elements of `values` back to back, as used by arrays and
+    /// vectors. Only `u8` overrides this, to copy byte slices in one go.
+    #[doc(hidden)]
+    fn encode_slice<O: Output + ?Sized>(values: &[Self], dest: &mut O)
+    where
+        Self: Sized,
+    {
+        for value in values {
+            value.encode_to(dest);
+        }
+    }
+}
+
+/// A type that can be read back from SCALE bytes.
+pub trait Decode: Sized {
+    /// Decodes a value from the front of `input`, advancing it past the
+    /// consumed bytes.
+    fn decode<I: Input>(input: &mut I) -> Result<Self, DecodeError>;
+
+    /// Decodes an `Option<Self>`. Only `bool` overrides this, for the
+    /// single-byte `Option<bool>` form.
+    #[doc(hidden)]
+    fn decode_option<I: Input>(input: &mut I) -> Result<Option<Self>, DecodeError> {
+        match input.read_byte()? {
+            0x00 => Ok(None),
+            0x01 => Ok(Some(Self::decode(input)?)),
+            tag => Err(DecodeError::UnknownVariant {
+                type_name: "Option",
+                tag,
+            }),
+        }
+    }
+
+    /// Decodes `N` values stored back to back. Only `u8` overrides this,
+    /// to fill byte arrays with a single read.
+    #[doc(hidden)]
+    fn decode_array<I: Input, const N: usize>(input: &mut I) -> Result<[Self; N], DecodeError> {
+        let mut values = Vec::with_capacity(N);
+        for index in 0..N {
+            input.enter(PathSegment::Index(index), core::any::type_name::<Self>());
+            values.push(Self::decode(input)?);
+            input.leave();
+        }
+        match values.try_into() {
+            Ok(array) => Ok(array),
+            Err(_) => unreachable!("exactly N values were decoded"),
+        }
+    }
+
+    /// Decodes `len` values stored back to back. Only `u8` overrides this,
+    /// to read byte vectors in bulk.
+    ///
+    /// `len` comes from the input, so capacity is reserved for at most
+    /// [`MAX_PREALLOCATION`] bytes up front; a bogus length then fails when
+    /// the input runs out rather than by exhausting memory.
+    #[doc(hidden)]
+    fn decode_vec<I: Input>(input: &mut I, len: usize) -> Result<Vec<Self>, DecodeError> {
+        let max_prealloc = MAX_PREALLOCATION / core::mem::size_of::<Self>().max(1);
+        let mut values = Vec::with_capacity(len.min(max_prealloc));
+        for index in 0..len {
+            input.enter(PathSegment::Index(index), core::any::type_name::<Self>());
+            values.push(Self::decode(input)?);
+            input.leave();
+        }
+        Ok(values)
+    }
+
+    /// Decodes a value that must span the whole of `bytes`, within
+    /// [`DecodeLimits::default`]: a value claiming more than 16 MiB, such
+    /// as a larger `Vec<u8>`, fails where a bare `&[u8]` would decode it;
+    /// use [`Decode::decode_all_with_limits`] for those. A failure is located as a
+    /// [`DecodeError::At`], with the byte offset, the expected type and the
+    /// path to the failing field, e.g. `Header.digest.logs[2].Consensus`.
+    fn decode_all(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Self::decode_all_with_limits(bytes, DecodeLimits::default())
+    }
+
+    /// Like [`Decode::decode_all`], but fails as soon as decoding would
+    /// exceed `limits` instead; [`DecodeLimits::UNLIMITED`] turns the
+    /// checks off.
+    fn decode_all_with_limits(bytes: &[u8], limits: DecodeLimits) -> Result<Self, DecodeError> {
+        crate::context::decode_all_with_limits(bytes, limits)
+    }
+}
+
+/// How strictly decoders validate their input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DecodeMode {
+    /// Reject anything the node would reject, such as compact values that
+    /// are not minimally encoded. Used when validating untrusted data.
+    #[default]
+    Strict,
+    /// Accept any well-formed encoding, even if it is not canonical.
+    Lenient,
+}
+
+/// A type whose decoder has checks that [`DecodeMode::Lenient`] relaxes,
+/// such as compact integers and ordered maps. [`Decode::decode`] always
+/// uses [`DecodeMode::Strict`].
+pub trait DecodeWithMode: Decode {
+    /// Decodes a value, validating it according to `mode`.
+    fn decode_with_mode<I: Input>(input: &mut I, mode: DecodeMode) -> Result<Self, DecodeError>;
+}
+
+// Fixed-width integers are encoded as their little-endian bytes (see
+// example 01); signed integers use two's complement. Decoding reads
+// exactly `size_of::<T>()` bytes, so it can neither overflow nor read past
+// the end of the input.
+macro_rules! impl_fixed_width {
+    ($($t:ty),*) => {
+        $(
+            impl Encode for $t {
+                fn encode_to<O: Output + ?Sized>(&self, dest: &mut O) {
+                    dest.write(&self.to_le_bytes());
+                }
+
+                fn encoded_size(&self) -> usize {
+                    core::mem::size_of::<$t>()
+                }
+            }
+
+            impl Decode for $t {
+                fn decode<I: Input>(input: &mut I) -> Result<Self, DecodeError> {
+                    let mut buf = [0u8; core::mem::size_of::<$t>()];
+                    input.read(&mut buf)?;
+                    Ok(<$t>::from_le_bytes(buf))
+                }
+            }
+        )*
+    };
+}
+
+impl_fixed_width!(u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+// Bytes are fixed-width too, but copy whole slices and arrays at once.
+impl Encode for u8 {
+    fn encode_to<O: Output + ?Sized>(&self, dest: &mut O) {
+        dest.push_byte(*self);
+    }
+
+    fn encoded_size(&self) -> usize {
+        1
+    }
+
+    fn encoded_size_slice(values: &[Self]) -> usize {
+        values.len()
+    }
+
+    fn encode_slice<O: Output + ?Sized>(values: &[Self], dest: &mut O) {
+        dest.write(values);
+    }
+}
+
+impl Decode for u8 {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, DecodeError> {
+        input.read_byte()
+    }
+
+    fn decode_array<I: Input, const N: usize>(input: &mut I) -> Result<[Self; N], DecodeError> {
+        let mut array = [0u8; N];
+        input.read(&mut array)?;
+        Ok(array)
+    }
+
+    fn decode_vec<I: Input>(input: &mut I, len: usize) -> Result<Vec<Self>, DecodeError> {
+        read_vec(input, len)
+    }
+}
+
+// Booleans are a single byte: 0x00 for false, 0x01 for true.
+impl Encode for bool {
+    fn encode_to<O: Output + ?Sized>(&self, dest: &mut O) {
+        dest.push_byte(*self as u8);
+    }
+
+    fn encoded_size(&self) -> usize {
+        1
+    }
+
+    fn encoded_size_option(_value: Option<&Self>) -> usize {
+        1
+    }
+
+    // Option<bool> packs into one byte: None, Some(true), Some(false).
+    fn encode_option<O: Output + ?Sized>(value: Option<&Self>, dest: &mut O) {
+        dest.push_byte(match value {
+            None => 0x00,
+            Some(true) => 0x01,
+            Some(false) => 0x02,
+        });
+    }
+}
+
+impl Decode for bool {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, DecodeError> {
+        match input.read_byte()? {
+            0x00 => Ok(false),
+            0x01 => Ok(true),
+            byte => Err(DecodeError::InvalidBool(byte)),
+        }
+    }
+
+    fn decode_option<I: Input>(input: &mut I) -> Result<Option<Self>, DecodeError> {
+        match input.read_byte()? {
+            0x00 => Ok(None),
+            0x01 => Ok(Some(true)),
+            0x02 => Ok(Some(false)),
+            tag => Err(DecodeError::UnknownVariant {
+                type_name: "Option<bool>",
+                tag,
+            }),
+        }
+    }
+}
+
+// References encode like the value they point to.
+impl<T: Encode + ?Sized> Encode for &T {
+    fn encode_to<O: Output + ?Sized>(&self, dest: &mut O) {
+        (**self).encode_to(dest);
+    }
+
+    fn encoded_size(&self) -> usize {
+        (**self).encoded_size()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_width_is_little_endian() {
+        assert_eq!(0x2au8.encode(), vec![0x2a]);
+        assert_eq!(0x1234u16.encode(), vec![0x34, 0x12]);
+        assert_eq!(
+            0x0102030405060708u64.encode(),
+            vec![0x08, 0x07, 0x06, 0x05, 0x04, 0x03, 0x02, 0x01]
+        );
+    }
+
+    #[test]
+    fn fixed_width_round_trip() {
+        assert_eq!(u16::decode_all(&[0x34, 0x12]), Ok(0x1234));
+        assert_eq!(u32::decode_all(&[0x78, 0x56, 0x34, 0x12]), Ok(0x12345678));
+        assert_eq!(u64::decode_all(&u64::MAX.encode()), Ok(u64::MAX));
+        assert_eq!(u128::decode_all(&u128::MAX.encode()), Ok(u128::MAX));
+    }
+
+    #[test]
+    fn signed_is_twos_complement() {
+        assert_eq!((-1i8).encode(), vec![0xff]);
+        assert_eq!(i16::MIN.encode(), vec![0x00, 0x80]);
+        assert_eq!((-2i32).encode(), vec![0xfe, 0xff, 0xff, 0xff]);
+        assert_eq!(
+            i64::MAX.encode(),
+            vec![0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f]
+        );
+        assert_eq!(i128::decode_all(&(-1i128).encode()), Ok(-1));
+        assert_eq!(i128::decode_all(&i128::MIN.encode()), Ok(i128::MIN));
+        assert_eq!(i32::decode_all(&[0xfe, 0xff, 0xff, 0xff]), Ok(-2));
+    }
+
+    #[test]
+    fn wide_integers_need_every_byte() {
+        assert_eq!(
+            u128::decode_all(&[0xff; 15]).map_err(DecodeError::into_kind),
+            Err(DecodeError::NotEnoughData {
+                expected: 16,
+                remaining: 15
+            })
+        );
+        assert_eq!(
+            i32::decode_all(&[0x00; 5]).map_err(DecodeError::into_kind),
+            Err(DecodeError::TrailingBytes(1))
+        );
+    }
+
+    #[test]
+    fn decode_advances_input() {
+        let bytes = [0x01, 0x02, 0x03];
+        let mut input = &bytes[..];
+        assert_eq!(u16::decode(&mut input), Ok(0x0201));
+        assert_eq!(input, &[0x03]);
+    }
+
+    #[test]
+    fn decode_errors() {
+        assert_eq!(
+            u16::decode(&mut &[0x01][..]),
+            Err(DecodeError::NotEnoughData {
+                expected: 2,
+                remaining: 1
+            })
+        );
+        assert_eq!(
+            u8::decode_all(&[0x01, 0x02]).map_err(DecodeError::into_kind),
+            Err(DecodeError::TrailingBytes(1))
+        );
+    }
+
+    #[test]
+    fn bool_is_one_byte() {
+        assert_eq!(true.encode(), vec![0x01]);
+        assert_eq!(false.encode(), vec![0x00]);
+        assert_eq!(bool::decode_all(&[0x01]), Ok(true));
+        assert_eq!(
+            bool::decode_all(&[0x05]).map_err(DecodeError::into_kind),
+            Err(DecodeError::InvalidBool(0x05))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn decode_from_reader() {
+        let bytes = [0x34, 0x12, 0x2a];
+        let mut reader = crate::IoReader::new(std::io::Cursor::new(bytes));
+        assert_eq!(u16::decode(&mut reader), Ok(0x1234));
+        assert_eq!(u8::decode(&mut reader), Ok(0x2a));
+        assert!(u8::decode(&mut reader).is_err());
+    }
+}
@@ -0,0 +1,459 @@
+// SCALE Compact (variable-length) integer encoding.
+//
+// Encoding Rules (based on the least significant 2 bits of the first byte):
+// - 0b00: 1 byte, for values 0 to 63 (2^6 - 1)
+// - 0b01: 2 bytes, for values 64 to 16383 (2^14 - 1)
+// - 0b10: 4 bytes, for values 16384 to 1073741823 (2^30 - 1)
+// - 0b11: Variable bytes, remaining 6 bits indicate the number of
+//   value bytes minus 4: [LLLLLL11, VVVVVVVV, ...]
+
+use crate::prelude::*;
+use crate::{Decode, DecodeError, DecodeMode, DecodeWithMode, Encode, Input, Output};
+
+/// Wrapper selecting the compact encoding for an unsigned integer.
+///
+/// `Compact(1u64).encode()` produces `[0x04]`, whereas `1u64.encode()`
+/// produces the 8 fixed-width bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Compact<T>(pub T);
+
+impl<T> From<T> for Compact<T> {
+    fn from(value: T) -> Self {
+        Compact(value)
+    }
+}
+
+/// Appends the SCALE Compact encoding of a u128 to `dest`.
+pub(crate) fn encode_compact<O: Output + ?Sized>(value: u128, dest: &mut O) {
+    if value <= 0x3F {
+        dest.push_byte((value as u8) << 2);
+    } else if value <= 0x3FFF {
+        dest.write(&(((value as u16) << 2) | 0b01).to_le_bytes());
+    } else if value <= 0x3FFF_FFFF {
+        dest.write(&(((value as u32) << 2) | 0b10).to_le_bytes());
+    } else {
+        let value_bytes = value.to_le_bytes();
+        encode_big_mode(&value_bytes[..significant_len(&value_bytes)], dest);
+    }
+}
+
+/// Number of bytes [`Compact`] encoding uses for `value`: 1, 2 or 4 in the
+/// small modes, otherwise 1 plus the value bytes (at least 4).
+///
+/// ```
+/// assert_eq!(scale::compact_len(63), 1);
+/// assert_eq!(scale::compact_len(64), 2);
+/// assert_eq!(scale::compact_len(1 << 30), 5);
+/// assert_eq!(scale::compact_len(u128::MAX), 17);
+/// ```
+pub fn compact_len(value: u128) -> usize {
+    match value {
+        0..=0x3F => 1,
+        0x40..=0x3FFF => 2,
+        0x4000..=0x3FFF_FFFF => 4,
+        _ => 1 + significant_len(&value.to_le_bytes()).max(4),
+    }
+}
+
+/// Number of bytes left once trailing (most significant) zeros are dropped.
+fn significant_len(le_bytes: &[u8]) -> usize {
+    le_bytes.iter().rposition(|&b| b != 0).map_or(0, |i| i + 1)
+}
+
+/// Encodes a trimmed little-endian magnitude in the 0b11 mode.
+///
+/// At least 4 value bytes are always written, so magnitudes shorter than
+/// that are zero-padded.
+fn encode_big_mode<O: Output + ?Sized>(magnitude: &[u8], dest: &mut O) {
+    let num_value_bytes = magnitude.len().max(4);
+    debug_assert!(num_value_bytes <= CompactBig::MAX_BYTES);
+
+    dest.push_byte((((num_value_bytes - 4) as u8) << 2) | 0b11);
+    dest.write(magnitude);
+    dest.write(&[0; 4][..num_value_bytes - magnitude.len()]);
+}
+
+/// Decodes SCALE Compact bytes into a u128, advancing `input`.
+///
+/// In [`DecodeMode::Strict`] a value must use the smallest mode (and, in
+/// the 0b11 mode, the fewest value bytes) that can hold it.
+pub(crate) fn decode_compact<I: Input>(
+    input: &mut I,
+    mode: DecodeMode,
+) -> Result<u128, DecodeError> {
+    let first_byte = input.read_byte()?;
+    decode_compact_rest(input, first_byte, mode)
+}
+
+/// Finishes decoding a compact value whose first byte has already been
+/// consumed.
+fn decode_compact_rest<I: Input>(
+    input: &mut I,
+    first_byte: u8,
+    mode: DecodeMode,
+) -> Result<u128, DecodeError> {
+    let (value, min_value) = match first_byte & 0b11 {
+        0b00 => return Ok((first_byte >> 2) as u128),
+        0b01 => {
+            let next = input.read_byte()?;
+            ((u16::from_le_bytes([first_byte, next]) >> 2) as u128, 0x40)
+        }
+        0b10 => {
+            let mut rest = [0u8; 3];
+            input.read(&mut rest)?;
+            let value = u32::from_le_bytes([first_byte, rest[0], rest[1], rest[2]]) >> 2;
+            (value as u128, 0x4000)
+        }
+        _ => {
+            let mut buf = [0u8; CompactBig::MAX_BYTES];
+            let value_bytes = read_big_mode(input, first_byte, mode, &mut buf)?;
+            let significant = significant_len(value_bytes);
+            if significant > 16 {
+                return Err(DecodeError::CompactOverflow { type_name: "u128" });
+            }
+            let mut value = [0u8; 16];
+            value[..significant].copy_from_slice(&value_bytes[..significant]);
+            return Ok(u128::from_le_bytes(value));
+        }
+    };
+    if mode == DecodeMode::Strict && value < min_value {
+        return Err(DecodeError::NonCanonicalCompact);
+    }
+    Ok(value)
+}
+
+/// Reads the value bytes of a 0b11-mode encoding whose first byte has
+/// already been consumed, using `buf` as storage.
+fn read_big_mode<'b, I: Input>(
+    input: &mut I,
+    first_byte: u8,
+    mode: DecodeMode,
+    buf: &'b mut [u8; CompactBig::MAX_BYTES],
+) -> Result<&'b [u8], DecodeError> {
+    let num_value_bytes = (first_byte >> 2) as usize + 4;
+    let value_bytes = &mut buf[..num_value_bytes];
+    input.read(value_bytes)?;
+    // The most significant byte must be in use, and 4-byte values must not
+    // fit into the 0b10 mode.
+    let canonical =
+        value_bytes[num_value_bytes - 1] != 0 && (num_value_bytes > 4 || value_bytes[3] >= 0x40);
+    if mode == DecodeMode::Strict && !canonical {
+        return Err(DecodeError::NonCanonicalCompact);
+    }
+    Ok(value_bytes)
+}
+
+macro_rules! impl_compact {
+    ($($t:ty),*) => {
+        $(
+            impl Encode for Compact<$t> {
+                fn encode_to<O: Output + ?Sized>(&self, dest: &mut O) {
+                    encode_compact(self.0 as u128, dest);
+                }
+
+                fn encoded_size(&self) -> usize {
+                    compact_len(self.0 as u128)
+                }
+            }
+
+            impl Decode for Compact<$t> {
+                fn decode<I: Input>(input: &mut I) -> Result<Self, DecodeError> {
+                    Self::decode_with_mode(input, DecodeMode::Strict)
+                }
+            }
+
+            impl DecodeWithMode for Compact<$t> {
+                fn decode_with_mode<I: Input>(input: &mut I, mode: DecodeMode) -> Result<Self, DecodeError> {
+                    let value = decode_compact(input, mode)?;
+                    <$t>::try_from(value)
+                        .map(Compact)
+                        .map_err(|_| DecodeError::CompactOverflow { type_name: stringify!($t) })
+                }
+            }
+        )*
+    };
+}
+
+impl_compact!(u8, u16, u32, u64, u128);
+
+/// An unsigned integer of up to 536 bits, the full range of the compact
+/// 0b11 mode (2^536 - 1).
+///
+/// Used for values that do not fit into `u128`, such as U256 balances. The
+/// magnitude is stored little-endian without trailing zero bytes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct CompactBig(Vec<u8>);
+
+impl CompactBig {
+    /// Maximum number of value bytes the 0b11 mode can describe (63 + 4).
+    pub const MAX_BYTES: usize = 67;
+
+    /// Builds a value from a little-endian magnitude of any length.
+    ///
+    /// Leading (most significant) zero bytes are ignored; the remaining
+    /// magnitude must fit into [`CompactBig::MAX_BYTES`] bytes.
+    pub fn from_le_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let magnitude = &bytes[..significant_len(bytes)];
+        if magnitude.len() > Self::MAX_BYTES {
+            return Err(DecodeError::CompactOverflow {
+                type_name: "CompactBig",
+            });
+        }
+        Ok(CompactBig(magnitude.to_vec()))
+    }
+
+    /// Builds a value from a big-endian magnitude of any length.
+    pub fn from_be_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut le_bytes = bytes.to_vec();
+        le_bytes.reverse();
+        Self::from_le_bytes(&le_bytes)
+    }
+
+    /// The little-endian magnitude, without trailing zero bytes.
+    pub fn as_le_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Converts to `u128` if the value fits.
+    pub fn to_u128(&self) -> Option<u128> {
+        if self.0.len() > 16 {
+            return None;
+        }
+        let mut buf = [0u8; 16];
+        buf[..self.0.len()].copy_from_slice(&self.0);
+        Some(u128::from_le_bytes(buf))
+    }
+}
+
+impl From<u128> for CompactBig {
+    fn from(value: u128) -> Self {
+        let bytes = value.to_le_bytes();
+        CompactBig(bytes[..significant_len(&bytes)].to_vec())
+    }
+}
+
+impl Encode for CompactBig {
+    fn encode_to<O: Output + ?Sized>(&self, dest: &mut O) {
+        match self.to_u128() {
+            Some(value) if value <= 0x3FFF_FFFF => encode_compact(value, dest),
+            _ => encode_big_mode(&self.0, dest),
+        }
+    }
+
+    fn encoded_size(&self) -> usize {
+        match self.to_u128() {
+            Some(value) => compact_len(value),
+            None => 1 + self.0.len(),
+        }
+    }
+}
+
+impl Decode for CompactBig {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, DecodeError> {
+        Self::decode_with_mode(input, DecodeMode::Strict)
+    }
+}
+
+impl DecodeWithMode for CompactBig {
+    fn decode_with_mode<I: Input>(input: &mut I, mode: DecodeMode) -> Result<Self, DecodeError> {
+        let first_byte = input.read_byte()?;
+        if first_byte & 0b11 != 0b11 {
+            return decode_compact_rest(input, first_byte, mode).map(CompactBig::from);
+        }
+
+        let mut buf = [0u8; CompactBig::MAX_BYTES];
+        let value_bytes = read_big_mode(input, first_byte, mode, &mut buf)?;
+        Ok(CompactBig(
+            value_bytes[..significant_len(value_bytes)].to_vec(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_every_mode() {
+        let cases: &[(u128, &[u8])] = &[
+            (0, &[0x00]),
+            (1, &[0x04]),
+            (63, &[0xfc]),
+            (64, &[0x01, 0x01]),
+            (16383, &[0xfd, 0xff]),
+            (16384, &[0x02, 0x00, 0x01, 0x00]),
+            (1073741823, &[0xfe, 0xff, 0xff, 0xff]),
+            (1073741824, &[0x03, 0x00, 0x00, 0x00, 0x40]),
+            (u32::MAX as u128 + 1, &[0x07, 0x00, 0x00, 0x00, 0x00, 0x01]),
+        ];
+        for &(value, expected) in cases {
+            assert_eq!(Compact(value).encode(), expected, "value {}", value);
+            assert_eq!(Compact::<u128>::decode_all(expected), Ok(Compact(value)));
+        }
+    }
+
+    #[test]
+    fn round_trips_u128_max() {
+        let encoded = Compact(u128::MAX).encode();
+        assert_eq!(encoded.len(), 17);
+        assert_eq!(encoded[0], 0b0011_0011);
+        assert_eq!(
+            Compact::<u128>::decode_all(&encoded),
+            Ok(Compact(u128::MAX))
+        );
+    }
+
+    #[test]
+    fn rejects_truncated_input() {
+        let cases: &[&[u8]] = &[&[], &[0x01], &[0x02, 0x00, 0x00], &[0x03, 0x00, 0x00, 0x00]];
+        for bytes in cases {
+            assert!(matches!(
+                Compact::<u128>::decode_all(bytes).map_err(DecodeError::into_kind),
+                Err(DecodeError::NotEnoughData { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn big_matches_u128_encoding() {
+        for value in [
+            0,
+            63,
+            64,
+            16384,
+            1073741823,
+            1073741824,
+            u64::MAX as u128,
+            u128::MAX,
+        ] {
+            let big = CompactBig::from(value);
+            assert_eq!(big.encode(), Compact(value).encode(), "value {}", value);
+            assert_eq!(CompactBig::decode_all(&big.encode()), Ok(big));
+        }
+    }
+
+    #[test]
+    fn big_u256_max() {
+        let big = CompactBig::from_le_bytes(&[0xff; 32]).unwrap();
+        let encoded = big.encode();
+        assert_eq!(encoded[0], (28 << 2) | 0b11);
+        assert_eq!(&encoded[1..], &[0xff; 32]);
+        assert_eq!(CompactBig::decode_all(&encoded), Ok(big.clone()));
+        assert_eq!(big.to_u128(), None);
+    }
+
+    #[test]
+    fn big_full_range() {
+        // 2^536 - 1 is the largest value the 0b11 mode can hold.
+        let max = CompactBig::from_le_bytes(&[0xff; 67]).unwrap();
+        let encoded = max.encode();
+        assert_eq!(encoded.len(), 68);
+        assert_eq!(encoded[0], 0xff);
+        assert_eq!(CompactBig::decode_all(&encoded), Ok(max));
+
+        // 2^536 needs 68 bytes.
+        let mut too_big = vec![0u8; 68];
+        too_big[67] = 1;
+        assert_eq!(
+            CompactBig::from_le_bytes(&too_big),
+            Err(DecodeError::CompactOverflow {
+                type_name: "CompactBig"
+            })
+        );
+    }
+
+    #[test]
+    fn big_ignores_leading_zeros() {
+        let big = CompactBig::from_be_bytes(&[0x00, 0x00, 0x01, 0x02]).unwrap();
+        assert_eq!(big.as_le_bytes(), &[0x02, 0x01]);
+        assert_eq!(big.to_u128(), Some(0x0102));
+        assert!(CompactBig::from_le_bytes(&[0u8; 100]).is_ok());
+    }
+
+    #[test]
+    fn big_rejects_truncated_input() {
+        let encoded = CompactBig::from_le_bytes(&[0xff; 32]).unwrap().encode();
+        assert!(matches!(
+            CompactBig::decode_all(&encoded[..20]).map_err(DecodeError::into_kind),
+            Err(DecodeError::NotEnoughData { .. })
+        ));
+    }
+
+    #[test]
+    fn strict_rejects_non_canonical() {
+        let cases: &[(&[u8], u128)] = &[
+            // 1 in the 2-byte and 4-byte modes
+            (&[0x05, 0x00], 1),
+            (&[0x06, 0x00, 0x00, 0x00], 1),
+            // 2^14 - 1 in the 4-byte mode
+            (&[0xfe, 0xff, 0x00, 0x00], 16383),
+            // 2^30 - 1 in the 0b11 mode
+            (&[0x03, 0xff, 0xff, 0xff, 0x3f], 1073741823),
+            // 2^30 with a trailing zero byte
+            (&[0x07, 0x00, 0x00, 0x00, 0x40, 0x00], 1073741824),
+        ];
+        for &(bytes, value) in cases {
+            assert_eq!(
+                Compact::<u128>::decode_all(bytes).map_err(DecodeError::into_kind),
+                Err(DecodeError::NonCanonicalCompact),
+                "bytes {:02x?}",
+                bytes
+            );
+            assert_eq!(
+                Compact::<u128>::decode_with_mode(&mut &bytes[..], DecodeMode::Lenient),
+                Ok(Compact(value))
+            );
+        }
+    }
+
+    #[test]
+    fn lenient_accepts_zero_padded_u128() {
+        // u128::MAX followed by a zero byte: 17 value bytes.
+        let mut bytes = vec![(13 << 2) | 0b11];
+        bytes.extend_from_slice(&[0xff; 16]);
+        bytes.push(0x00);
+        assert_eq!(
+            Compact::<u128>::decode_with_mode(&mut &bytes[..], DecodeMode::Lenient),
+            Ok(Compact(u128::MAX))
+        );
+        assert_eq!(
+            CompactBig::decode_all(&bytes).map_err(DecodeError::into_kind),
+            Err(DecodeError::NonCanonicalCompact)
+        );
+    }
+
+    #[test]
+    fn rejects_values_wider_than_target() {
+        let encoded = Compact(256u32).encode();
+        assert_eq!(
+            Compact::<u8>::decode_all(&encoded).map_err(DecodeError::into_kind),
+            Err(DecodeError::CompactOverflow { type_name: "u8" })
+        );
+    }
+
+    #[test]
+    fn compact_lengths_per_mode() {
+        let values = [
+            0u128,
+            63,
+            64,
+            16_383,
+            16_384,
+            (1 << 30) - 1,
+            1 << 30,
+            u64::MAX as u128,
+            u128::MAX,
+        ];
+        for value in values {
+            assert_eq!(
+                compact_len(value),
+                Compact(value).encode().len(),
+                "{}",
+                value
+            );
+        }
+        let big = CompactBig::from_be_bytes(&[0xff; 67]).unwrap();
+        assert_eq!(big.encoded_size(), 68);
+        assert_eq!(CompactBig::from(1u128 << 30).encoded_size(), 5);
+    }
+}
@@ -0,0 +1,284 @@
+// Locating decode failures: which byte, which type, and where in the value
+// tree, e.g. `Header.digest.logs[2].Consensus`.
+
+use core::fmt;
+
+use crate::borrowed::BorrowInput;
+use crate::prelude::*;
+use crate::{Decode, DecodeError, DecodeLimits, Input, LimitedInput};
+
+/// One step from a value to a part of it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathSegment {
+    /// A struct field, or a positional field such as `0`.
+    Field(&'static str),
+    /// An element of a vector, array, map or set.
+    Index(usize),
+    /// The variant of an enum.
+    Variant(&'static str),
+}
+
+impl fmt::Display for PathSegment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathSegment::Field(name) | PathSegment::Variant(name) => write!(f, ".{}", name),
+            PathSegment::Index(index) => write!(f, "[{}]", index),
+        }
+    }
+}
+
+/// Wraps an [`Input`] and keeps track of the position and the path being
+/// decoded, so a failure can be located as a [`DecodeError::At`].
+///
+/// ```
+/// use scale::{Decode, TrackedInput};
+///
+/// let bytes = [0x08, 0x01, 0x05];
+/// let mut input = TrackedInput::new(&bytes[..]);
+/// let error = Vec::<bool>::decode(&mut input).unwrap_err();
+/// let error = input.describe::<Vec<bool>>(error);
+/// assert_eq!(error.offset(), Some(2));
+/// assert_eq!(error.path_string().unwrap(), "Vec<bool>[1]");
+/// ```
+pub struct TrackedInput<I> {
+    inner: I,
+    position: u64,
+    last_read: u64,
+    path: Vec<(PathSegment, &'static str)>,
+}
+
+impl<I: Input> TrackedInput<I> {
+    pub fn new(inner: I) -> Self {
+        TrackedInput {
+            inner,
+            position: 0,
+            last_read: 0,
+            path: Vec::new(),
+        }
+    }
+
+    /// Number of bytes consumed so far.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Returns the wrapped input.
+    pub fn into_inner(self) -> I {
+        self.inner
+    }
+
+    /// Attaches the current position and path to `error`, which must have
+    /// just been returned by decoding a `T` from this input. An error that
+    /// is already located is returned as it is.
+    pub fn describe<T: ?Sized>(&self, error: DecodeError) -> DecodeError {
+        if let DecodeError::At { .. } = error {
+            return error;
+        }
+        let root = short_type_name(core::any::type_name::<T>());
+        let expected = match self.path.last() {
+            Some((_, expected)) => short_type_name(expected),
+            None => root.clone(),
+        };
+        let path = self
+            .path
+            .iter()
+            .map(|(segment, _)| segment.clone())
+            .collect();
+        error.within(self.last_read, &expected, &root, path)
+    }
+}
+
+impl<I: Input> Input for TrackedInput<I> {
+    fn remaining_len(&mut self) -> Result<Option<usize>, DecodeError> {
+        self.inner.remaining_len()
+    }
+
+    fn read(&mut self, into: &mut [u8]) -> Result<(), DecodeError> {
+        self.last_read = self.position;
+        self.inner.read(into)?;
+        self.position += into.len() as u64;
+        Ok(())
+    }
+
+    fn read_byte(&mut self) -> Result<u8, DecodeError> {
+        self.last_read = self.position;
+        let byte = self.inner.read_byte()?;
+        self.position += 1;
+        Ok(byte)
+    }
+
+    fn on_collection(&mut self, len: usize, alloc_bytes: usize) -> Result<(), DecodeError> {
+        self.inner.on_collection(len, alloc_bytes)
+    }
+
+    fn descend(&mut self) -> Result<(), DecodeError> {
+        self.inner.descend()
+    }
+
+    fn ascend(&mut self) {
+        self.inner.ascend();
+    }
+
+    fn enter(&mut self, segment: PathSegment, expected: &'static str) {
+        self.inner.enter(segment.clone(), expected);
+        self.path.push((segment, expected));
+    }
+
+    fn leave(&mut self) {
+        self.path.pop();
+        self.inner.leave();
+    }
+}
+
+impl<'a, I: BorrowInput<'a>> BorrowInput<'a> for TrackedInput<I> {
+    fn read_borrowed(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        self.last_read = self.position;
+        let bytes = self.inner.read_borrowed(len)?;
+        self.position += len as u64;
+        Ok(bytes)
+    }
+}
+
+/// Decodes a value that must span the whole of `bytes`, within `limits`,
+/// locating any failure.
+pub(crate) fn decode_all_with_limits<T: Decode>(
+    bytes: &[u8],
+    limits: DecodeLimits,
+) -> Result<T, DecodeError> {
+    decode_all_tracked(bytes, limits, T::decode)
+}
+
+/// [`decode_all_with_limits`] with `decode` in place of `T::decode`, for
+/// the zero-copy types that borrow from `bytes`.
+pub(crate) fn decode_all_tracked<'a, T>(
+    bytes: &'a [u8],
+    limits: DecodeLimits,
+    decode: impl FnOnce(&mut TrackedInput<LimitedInput<&'a [u8]>>) -> Result<T, DecodeError>,
+) -> Result<T, DecodeError> {
+    let mut input = TrackedInput::new(LimitedInput::new(bytes, limits));
+    let value = decode(&mut input).map_err(|e| input.describe::<T>(e))?;
+    let trailing = input.remaining_len().ok().flatten().unwrap_or(0);
+    if trailing > 0 {
+        input.last_read = input.position;
+        return Err(input.describe::<T>(DecodeError::TrailingBytes(trailing)));
+    }
+    Ok(value)
+}
+
+/// Strips module paths from a `std::any::type_name`, turning
+/// `alloc::vec::Vec<my_crate::Item>` into `Vec<Item>`.
+fn short_type_name(full: &str) -> String {
+    let mut short = String::with_capacity(full.len());
+    let mut path = String::new();
+    for c in full.chars() {
+        if c.is_alphanumeric() || c == '_' || c == ':' {
+            path.push(c);
+        } else {
+            short.push_str(path.rsplit("::").next().unwrap_or_default());
+            path.clear();
+            short.push(c);
+        }
+    }
+    short.push_str(path.rsplit("::").next().unwrap_or_default());
+    short
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Compact, DecodeLimits, Encode, LimitedInput};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn short_names() {
+        assert_eq!(short_type_name("u32"), "u32");
+        assert_eq!(
+            short_type_name("alloc::vec::Vec<(alloc::vec::Vec<u8>, u64)>"),
+            "Vec<(Vec<u8>, u64)>"
+        );
+        assert_eq!(
+            short_type_name("[scale::compact::Compact<u8>; 4]"),
+            "[Compact<u8>; 4]"
+        );
+    }
+
+    #[test]
+    fn vector_element_failure() {
+        let error = Vec::<bool>::decode_all(&[0x0c, 0x01, 0x00, 0x07]).unwrap_err();
+        assert_eq!(
+            error,
+            DecodeError::At {
+                error: Box::new(DecodeError::InvalidBool(0x07)),
+                offset: 3,
+                expected: "bool".to_string(),
+                root: "Vec<bool>".to_string(),
+                path: vec![PathSegment::Index(2)],
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "Invalid boolean byte: 0x07 at byte 3 while decoding `bool` at Vec<bool>[2]"
+        );
+    }
+
+    #[test]
+    fn nested_collections() {
+        let map = BTreeMap::from([
+            (1u8, vec![Compact(1u32)]),
+            (2, vec![Compact(2), Compact(3)]),
+        ]);
+        let mut encoded = map.encode();
+        encoded.truncate(encoded.len() - 1);
+        let error = BTreeMap::<u8, Vec<Compact<u32>>>::decode_all(&encoded).unwrap_err();
+        assert_eq!(
+            error.kind(),
+            &DecodeError::NotEnoughData {
+                expected: 1,
+                remaining: 0
+            }
+        );
+        assert_eq!(error.offset(), Some(encoded.len() as u64));
+        assert_eq!(
+            error.path_string().as_deref(),
+            Some("BTreeMap<u8, Vec<Compact<u32>>>[1][1]")
+        );
+        assert!(error.to_string().contains("while decoding `Compact<u32>`"));
+    }
+
+    #[test]
+    fn trailing_bytes() {
+        let error = u16::decode_all(&[0x01, 0x02, 0x03]).unwrap_err();
+        assert_eq!(error, DecodeError::TrailingBytes(1).at(2, "u16"));
+        assert_eq!(error.path_string().as_deref(), Some("u16"));
+    }
+
+    #[test]
+    fn composes_with_limits() {
+        let encoded = vec![vec![0u8; 10], vec![0u8; 10]].encode();
+        let limits = DecodeLimits {
+            max_collection_len: 5,
+            ..DecodeLimits::default()
+        };
+        let mut input = TrackedInput::new(LimitedInput::new(&encoded[..], limits));
+        let error = Vec::<Vec<u8>>::decode(&mut input).unwrap_err();
+        let error = input.describe::<Vec<Vec<u8>>>(error);
+        assert_eq!(
+            error.kind(),
+            &DecodeError::CollectionTooLong { len: 10, max: 5 }
+        );
+        assert_eq!(error.offset(), Some(1));
+        assert_eq!(error.path_string().as_deref(), Some("Vec<Vec<u8>>[0]"));
+        // Describing it again keeps the original location.
+        assert_eq!(input.describe::<u8>(error.clone()), error);
+    }
+
+    #[test]
+    fn unlocated_errors() {
+        let error = DecodeError::InvalidBool(0x02);
+        assert_eq!(error.kind(), &error);
+        assert_eq!(error.offset(), None);
+        assert_eq!(error.path_string(), None);
+        let located = error.clone().at(4, "bool").at(9, "u8");
+        assert_eq!(located.into_kind(), error);
+    }
+}
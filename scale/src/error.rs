@@ -0,0 +1,236 @@
+// Error type shared by every decoder in the crate, and by hand-written
+// decoders such as the ones in the examples.
+
+use core::fmt;
+#[cfg(feature = "std")]
+use std::io::ErrorKind;
+
+use crate::prelude::*;
+use crate::PathSegment;
+
+/// Errors returned while decoding SCALE bytes.
+///
+/// Decoders return what went wrong; [`Decode::decode_all`](crate::Decode::decode_all)
+/// and [`TrackedInput`](crate::TrackedInput) wrap it in [`DecodeError::At`]
+/// to say where:
+///
+/// ```
+/// use scale::{Decode, DecodeError, PathSegment};
+///
+/// let error = Vec::<bool>::decode_all(&[0x08, 0x01, 0x05]).unwrap_err();
+/// assert_eq!(error.kind(), &DecodeError::InvalidBool(0x05));
+/// assert_eq!(error.offset(), Some(2));
+/// assert_eq!(
+///     error.to_string(),
+///     "Invalid boolean byte: 0x05 at byte 2 while decoding `bool` at Vec<bool>[1]"
+/// );
+/// ```
+///
+/// The `Io` variant only exists with the `std` feature, so the enum is
+/// `#[non_exhaustive]`: a dependency turning `std` on must not break
+/// `match`es in `no_std` crates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DecodeError {
+    /// The input ended before the value was fully decoded.
+    NotEnoughData { expected: usize, remaining: usize },
+    /// A boolean byte was neither `0x00` nor `0x01`.
+    InvalidBool(u8),
+    /// String bytes were not valid UTF-8.
+    InvalidUtf8 { valid_up_to: usize },
+    /// An enum tag did not match any variant of the target type.
+    UnknownVariant { type_name: &'static str, tag: u8 },
+    /// A compact-encoded value does not fit into the target integer type.
+    CompactOverflow { type_name: &'static str },
+    /// A compact value used more bytes than its minimal encoding.
+    NonCanonicalCompact,
+    /// Map or set keys were not in strictly ascending order, i.e. unsorted
+    /// or duplicated.
+    UnorderedKeys,
+    /// A collection is longer than [`DecodeLimits::max_collection_len`](crate::DecodeLimits).
+    CollectionTooLong { len: usize, max: usize },
+    /// Decoding would allocate more than is left of
+    /// [`DecodeLimits::max_allocation`](crate::DecodeLimits).
+    AllocationLimit { requested: usize, remaining: usize },
+    /// Values are nested deeper than [`DecodeLimits::max_depth`](crate::DecodeLimits).
+    DepthLimit(usize),
+    /// Bytes were left over after decoding a complete value.
+    TrailingBytes(usize),
+    /// The underlying reader of an [`IoReader`](crate::IoReader) failed.
+    #[cfg(feature = "std")]
+    Io(ErrorKind),
+    /// A type ID is not in the [`Registry`](crate::Registry) being decoded
+    /// with.
+    UnknownTypeId(u32),
+    /// A registry type has no SCALE decoding, e.g. a compact of a string.
+    UnsupportedType { id: u32, reason: &'static str },
+    /// A check of a hand-written decoder failed, such as a field missing
+    /// from the JSON a value is built from.
+    Custom(&'static str),
+    /// `error`, located in the input and in the value being decoded.
+    At {
+        /// What went wrong; never itself an `At`.
+        error: Box<DecodeError>,
+        /// Offset, from the start of the input, of the read that failed or
+        /// that returned the offending bytes.
+        offset: u64,
+        /// The type that was being decoded, e.g. `u32` or `Vec<DigestItem>`.
+        expected: String,
+        /// The type decoding started from.
+        root: String,
+        /// Steps from the root value to the failing part.
+        path: Vec<PathSegment>,
+    },
+}
+
+impl DecodeError {
+    /// Locates `self` at `offset` bytes into the input, while decoding
+    /// `expected` at the top level. Used by hand-written decoders; the
+    /// crate's own decoders are located by a [`TrackedInput`](crate::TrackedInput).
+    pub fn at(self, offset: u64, expected: &str) -> DecodeError {
+        self.within(offset, expected, expected, Vec::new())
+    }
+
+    /// Like [`DecodeError::at`], but for a part of a `root` value reached
+    /// through `path`.
+    pub fn within(
+        self,
+        offset: u64,
+        expected: &str,
+        root: &str,
+        path: Vec<PathSegment>,
+    ) -> DecodeError {
+        DecodeError::At {
+            error: Box::new(self.into_kind()),
+            offset,
+            expected: expected.to_string(),
+            root: root.to_string(),
+            path,
+        }
+    }
+
+    /// What went wrong, without where.
+    pub fn kind(&self) -> &DecodeError {
+        match self {
+            DecodeError::At { error, .. } => error,
+            error => error,
+        }
+    }
+
+    /// Like [`DecodeError::kind`], but takes ownership.
+    pub fn into_kind(self) -> DecodeError {
+        match self {
+            DecodeError::At { error, .. } => *error,
+            error => error,
+        }
+    }
+
+    /// Offset of the failure from the start of the input, if located.
+    pub fn offset(&self) -> Option<u64> {
+        match self {
+            DecodeError::At { offset, .. } => Some(*offset),
+            _ => None,
+        }
+    }
+
+    /// The path rendered from the root, e.g. `Header.digest.logs[2]`, if
+    /// located.
+    pub fn path_string(&self) -> Option<String> {
+        match self {
+            DecodeError::At { root, path, .. } => {
+                let mut rendered = root.clone();
+                for segment in path {
+                    rendered.push_str(&segment.to_string());
+                }
+                Some(rendered)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl From<&'static str> for DecodeError {
+    fn from(reason: &'static str) -> Self {
+        DecodeError::Custom(reason)
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::NotEnoughData {
+                expected,
+                remaining,
+            } => write!(
+                f,
+                "Not enough bytes. Expected {}, got {}",
+                expected, remaining
+            ),
+            DecodeError::InvalidBool(byte) => write!(f, "Invalid boolean byte: 0x{:02x}", byte),
+            DecodeError::InvalidUtf8 { valid_up_to } => {
+                write!(f, "Invalid UTF-8 after {} valid bytes", valid_up_to)
+            }
+            DecodeError::UnknownVariant { type_name, tag } => {
+                write!(f, "Unknown {} variant tag: 0x{:02x}", type_name, tag)
+            }
+            DecodeError::CompactOverflow { type_name } => {
+                write!(f, "Compact value exceeds {} capacity", type_name)
+            }
+            DecodeError::NonCanonicalCompact => {
+                write!(f, "Compact value is not canonically (minimally) encoded")
+            }
+            DecodeError::UnorderedKeys => {
+                write!(f, "Map or set keys are unsorted or duplicated")
+            }
+            DecodeError::CollectionTooLong { len, max } => {
+                write!(
+                    f,
+                    "Collection of {} elements exceeds the limit of {}",
+                    len, max
+                )
+            }
+            DecodeError::AllocationLimit {
+                requested,
+                remaining,
+            } => write!(
+                f,
+                "Allocating {} bytes exceeds the remaining limit of {}",
+                requested, remaining
+            ),
+            DecodeError::DepthLimit(max) => write!(f, "Nesting exceeds the depth limit of {}", max),
+            DecodeError::TrailingBytes(count) => {
+                write!(f, "{} trailing bytes after decoded value", count)
+            }
+            #[cfg(feature = "std")]
+            DecodeError::Io(kind) => write!(f, "I/O error while reading input: {}", kind),
+            DecodeError::UnknownTypeId(id) => write!(f, "No type with ID {} in the registry", id),
+            DecodeError::UnsupportedType { id, reason } => {
+                write!(f, "Type {} cannot be decoded: {}", id, reason)
+            }
+            DecodeError::Custom(reason) => f.write_str(reason),
+            DecodeError::At {
+                error,
+                offset,
+                expected,
+                ..
+            } => write!(
+                f,
+                "{} at byte {} while decoding `{}` at {}",
+                error,
+                offset,
+                expected,
+                self.path_string().unwrap_or_default()
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DecodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DecodeError::At { error, .. } => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,642 @@
+// Annotated byte layouts: decodes SCALE bytes following a registry type and
+// records which bytes encode what, e.g.
+//
+//     bytes 0..1 = Vec<u16>: compact length (mode 0b00, value 3)
+//     bytes 1..3 = Vec<u16>[0]: u16 LE (value 1)
+//
+// for printing as an annotated hex dump or a Markdown table.
+
+use core::fmt;
+use core::ops::Range;
+
+use crate::compact::decode_compact;
+use crate::{
+    BitOrder, BitSeq, BitStore, Decode, DecodeError, DecodeLimits, DecodeMode, Field, Lsb0, Msb0,
+    Primitive, Registry, TypeDef,
+};
+
+/// Bytes shown per line of [`Explanation::hex_dump`], and per cell of
+/// [`Explanation::to_markdown`] before the hex is cut short.
+const BYTES_PER_LINE: usize = 16;
+
+/// A run of bytes and what they encode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Span {
+    /// Offsets of the bytes in the explained input.
+    pub range: Range<usize>,
+    /// Where in the value the bytes belong, in the form of
+    /// [`DecodeError::path_string`](crate::DecodeError::path_string), e.g.
+    /// `Header.digest.logs[2].Consensus.0`.
+    pub path: String,
+    /// How deeply `path` is nested below the root value.
+    pub depth: usize,
+    /// What the bytes are, e.g. `compact length (mode 0b00, value 3)`.
+    pub description: String,
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "bytes {}..{} = {}",
+            self.range.start,
+            self.range.end,
+            self.label()
+        )
+    }
+}
+
+impl Span {
+    fn label(&self) -> String {
+        if self.path.is_empty() {
+            self.description.clone()
+        } else {
+            format!("{}: {}", self.path, self.description)
+        }
+    }
+}
+
+/// The byte layout of an encoded value, as recorded by [`explain`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explanation {
+    /// The explained input.
+    pub bytes: Vec<u8>,
+    /// Every non-empty run of bytes that was decoded, in input order.
+    pub spans: Vec<Span>,
+    /// Why decoding stopped early, if it did. Spans are recorded up to the
+    /// last complete read, so the bytes after them are where it failed.
+    pub error: Option<DecodeError>,
+}
+
+impl Explanation {
+    /// Number of bytes covered by spans.
+    pub fn explained_len(&self) -> usize {
+        self.spans.last().map_or(0, |span| span.range.end)
+    }
+
+    /// The spans as a hex dump for the terminal: offset, bytes (wrapped
+    /// after 16) and the annotation, indented by nesting depth.
+    ///
+    /// ```text
+    /// 0000  0c     Vec<u16>: compact length (mode 0b00, value 3)
+    /// 0001  01 00    Vec<u16>[0]: u16 LE (value 1)
+    /// ```
+    pub fn hex_dump(&self) -> String {
+        let width = self
+            .spans
+            .iter()
+            .map(|span| span.range.len().min(BYTES_PER_LINE) * 3)
+            .max()
+            .unwrap_or(0);
+        let mut dump = String::new();
+        for span in &self.spans {
+            let chunks = self.bytes[span.range.clone()].chunks(BYTES_PER_LINE);
+            for (line, chunk) in chunks.enumerate() {
+                let offset = span.range.start + line * BYTES_PER_LINE;
+                let hex = spaced_hex(chunk);
+                if line == 0 {
+                    let indent = "  ".repeat(span.depth);
+                    dump.push_str(&format!(
+                        "{:04x}  {:<width$}  {}{}\n",
+                        offset,
+                        hex,
+                        indent,
+                        span.label(),
+                        width = width.saturating_sub(1)
+                    ));
+                } else {
+                    dump.push_str(&format!("{:04x}  {}\n", offset, hex));
+                }
+            }
+        }
+        dump.push_str(&self.unexplained(|offset, hex| format!("{:04x}  {}", offset, hex)));
+        dump
+    }
+
+    /// The spans as a Markdown table, one row per span. Hex longer than
+    /// 16 bytes is cut short with `…`.
+    ///
+    /// ```text
+    /// | Bytes | Hex | Field | Meaning |
+    /// | --- | --- | --- | --- |
+    /// | 0..1 | `0c` | `Vec<u16>` | compact length (mode 0b00, value 3) |
+    /// ```
+    pub fn to_markdown(&self) -> String {
+        let mut table =
+            String::from("| Bytes | Hex | Field | Meaning |\n| --- | --- | --- | --- |\n");
+        for span in &self.spans {
+            let bytes = &self.bytes[span.range.clone()];
+            let mut hex = spaced_hex(&bytes[..bytes.len().min(BYTES_PER_LINE)]);
+            if bytes.len() > BYTES_PER_LINE {
+                hex.push_str(" …");
+            }
+            let field = if span.path.is_empty() {
+                String::new()
+            } else {
+                format!("`{}`", span.path)
+            };
+            table.push_str(&format!(
+                "| {}..{} | `{}` | {} | {} |\n",
+                span.range.start,
+                span.range.end,
+                hex,
+                escape_cell(&field),
+                escape_cell(&span.description)
+            ));
+        }
+        let footer = self.unexplained(|offset, _| format!("\nUnexplained from byte {}", offset));
+        table.push_str(&footer);
+        table
+    }
+
+    /// A line for the bytes not covered by spans and the error, if any.
+    fn unexplained(&self, bytes_line: impl Fn(usize, &str) -> String) -> String {
+        let Some(error) = &self.error else {
+            return String::new();
+        };
+        let offset = self.explained_len();
+        let rest = &self.bytes[offset..];
+        let mut hex = spaced_hex(&rest[..rest.len().min(BYTES_PER_LINE)]);
+        if rest.len() > BYTES_PER_LINE {
+            hex.push_str(" …");
+        }
+        format!("{}: {}\n", bytes_line(offset, &hex), error)
+    }
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for span in &self.spans {
+            writeln!(f, "{}", span)?;
+        }
+        if let Some(error) = &self.error {
+            writeln!(f, "error after byte {}: {}", self.explained_len(), error)?;
+        }
+        Ok(())
+    }
+}
+
+/// Decodes `bytes` as registry type `ty`, recording the span of every read.
+///
+/// Decoding is bounded by [`DecodeLimits::default`]. A failure does not
+/// discard what was explained before it; it is returned in
+/// [`Explanation::error`], as are bytes left over after the value.
+///
+/// ```
+/// use scale::{explain, Registry};
+///
+/// let mut registry = Registry::new();
+/// let ty = registry.register::<Vec<u16>>();
+/// let explanation = explain(&registry, ty, &[0x0c, 0x01, 0x00, 0x02, 0x00, 0x03, 0x00]);
+/// assert_eq!(
+///     explanation.spans[0].to_string(),
+///     "bytes 0..1 = Vec<u16>: compact length (mode 0b00, value 3)"
+/// );
+/// assert_eq!(
+///     explanation.spans[1].to_string(),
+///     "bytes 1..3 = Vec<u16>[0]: u16 LE (value 1)"
+/// );
+/// assert_eq!(explanation.error, None);
+/// ```
+pub fn explain(registry: &Registry, ty: u32, bytes: &[u8]) -> Explanation {
+    let mut explainer = Explainer {
+        registry,
+        bytes,
+        position: 0,
+        limits: DecodeLimits::default(),
+        // Bounded even for types that contain themselves.
+        path: registry.type_name(ty),
+        depth: 0,
+        spans: Vec::new(),
+    };
+    let mut error = explainer.explain(ty).err();
+    if error.is_none() && explainer.position < bytes.len() {
+        error = Some(DecodeError::TrailingBytes(bytes.len() - explainer.position));
+    }
+    Explanation {
+        bytes: bytes.to_vec(),
+        spans: explainer.spans,
+        error,
+    }
+}
+
+struct Explainer<'a> {
+    registry: &'a Registry,
+    bytes: &'a [u8],
+    position: usize,
+    limits: DecodeLimits,
+    path: String,
+    depth: usize,
+    spans: Vec<Span>,
+}
+
+impl<'a> Explainer<'a> {
+    fn explain(&mut self, ty: u32) -> Result<(), DecodeError> {
+        let registry = self.registry;
+        match &registry.get(ty)?.def {
+            TypeDef::Primitive(primitive) => self.primitive(*primitive),
+            TypeDef::Compact(inner) => {
+                let primitive = registry.compact_primitive(*inner)?;
+                let value = self.compact(&format!("compact {}", primitive.name()))?;
+                if value > primitive.max_unsigned() {
+                    return Err(DecodeError::CompactOverflow {
+                        type_name: primitive.name(),
+                    });
+                }
+                Ok(())
+            }
+            TypeDef::Sequence(element) => {
+                let len = self.length("compact length")?;
+                self.elements(*element, len)
+            }
+            TypeDef::Array { len, element } => self.elements(*element, *len as usize),
+            TypeDef::Tuple(elements) => {
+                for (index, &element) in elements.iter().enumerate() {
+                    self.nested(&format!(".{}", index), |this| this.explain(element))?;
+                }
+                Ok(())
+            }
+            TypeDef::Composite(fields) => self.fields(fields),
+            TypeDef::Variant(variants) => {
+                let start = self.position;
+                let tag = self.read::<u8>()?;
+                let Some(variant) = variants.iter().find(|variant| variant.index == tag) else {
+                    self.record(start, format!("variant index {} (unknown)", tag));
+                    return Err(DecodeError::UnknownVariant {
+                        type_name: "registry enum",
+                        tag,
+                    });
+                };
+                self.record(start, format!("variant index {} ({})", tag, variant.name));
+                self.nested(&format!(".{}", variant.name), |this| {
+                    this.fields(&variant.fields)
+                })
+            }
+            TypeDef::BitSequence { store, order } => {
+                let msb0 = registry.is_msb0(*order)?;
+                let store = registry.store_primitive(*store)?;
+                self.bits(store, msb0)
+            }
+        }
+    }
+
+    fn primitive(&mut self, primitive: Primitive) -> Result<(), DecodeError> {
+        let start = self.position;
+        let description = match primitive {
+            Primitive::Bool => format!("bool ({})", self.read::<bool>()?),
+            Primitive::Str => {
+                let len = self.length("compact length")?;
+                let start = self.position;
+                let text = core::str::from_utf8(self.take(len)?).map_err(|error| {
+                    DecodeError::InvalidUtf8 {
+                        valid_up_to: error.valid_up_to(),
+                    }
+                })?;
+                let description = format!("UTF-8 {:?}", text);
+                self.record(start, description);
+                return Ok(());
+            }
+            Primitive::U8 => self.integer::<u8>(primitive)?,
+            Primitive::U16 => self.integer::<u16>(primitive)?,
+            Primitive::U32 => self.integer::<u32>(primitive)?,
+            Primitive::U64 => self.integer::<u64>(primitive)?,
+            Primitive::U128 => self.integer::<u128>(primitive)?,
+            Primitive::I8 => self.integer::<i8>(primitive)?,
+            Primitive::I16 => self.integer::<i16>(primitive)?,
+            Primitive::I32 => self.integer::<i32>(primitive)?,
+            Primitive::I64 => self.integer::<i64>(primitive)?,
+            Primitive::I128 => self.integer::<i128>(primitive)?,
+        };
+        self.record(start, description);
+        Ok(())
+    }
+
+    fn integer<T: Decode + fmt::Display>(
+        &mut self,
+        primitive: Primitive,
+    ) -> Result<String, DecodeError> {
+        let value = self.read::<T>()?;
+        let endianness = if core::mem::size_of::<T>() > 1 {
+            " LE"
+        } else {
+            ""
+        };
+        Ok(format!(
+            "{}{} (value {})",
+            primitive.name(),
+            endianness,
+            value
+        ))
+    }
+
+    fn elements(&mut self, element: u32, len: usize) -> Result<(), DecodeError> {
+        if self.registry.is_u8(element) {
+            let start = self.position;
+            self.take(len)?;
+            self.record(start, format!("{} bytes", len));
+            return Ok(());
+        }
+        for index in 0..len {
+            self.nested(&format!("[{}]", index), |this| this.explain(element))?;
+        }
+        Ok(())
+    }
+
+    fn fields(&mut self, fields: &[Field]) -> Result<(), DecodeError> {
+        for (index, field) in fields.iter().enumerate() {
+            let segment = match &field.name {
+                Some(name) => format!(".{}", name),
+                None => format!(".{}", index),
+            };
+            self.nested(&segment, |this| this.explain(field.ty))?;
+        }
+        Ok(())
+    }
+
+    fn bits(&mut self, store: Primitive, msb0: bool) -> Result<(), DecodeError> {
+        let start = self.position;
+        let len = self.length("compact bit count")?;
+        let word_bytes = match store {
+            Primitive::U8 => 1,
+            Primitive::U16 => 2,
+            Primitive::U32 => 4,
+            _ => 8,
+        };
+        let words = len.div_ceil(word_bytes * 8);
+        let words_start = self.position;
+        self.take(words * word_bytes)?;
+        // Decode the whole sequence again so the bits come out exactly as
+        // `BitSeq` reads them.
+        let encoded = &self.bytes[start..self.position];
+        let bits = match (store, msb0) {
+            (Primitive::U8, false) => bit_string::<u8, Lsb0>(encoded)?,
+            (Primitive::U8, true) => bit_string::<u8, Msb0>(encoded)?,
+            (Primitive::U16, false) => bit_string::<u16, Lsb0>(encoded)?,
+            (Primitive::U16, true) => bit_string::<u16, Msb0>(encoded)?,
+            (Primitive::U32, false) => bit_string::<u32, Lsb0>(encoded)?,
+            (Primitive::U32, true) => bit_string::<u32, Msb0>(encoded)?,
+            (_, false) => bit_string::<u64, Lsb0>(encoded)?,
+            (_, true) => bit_string::<u64, Msb0>(encoded)?,
+        };
+        self.record(
+            words_start,
+            format!(
+                "{} {} word{}, {} (bits {})",
+                words,
+                store.name(),
+                if words == 1 { "" } else { "s" },
+                if msb0 { "Msb0" } else { "Lsb0" },
+                bits
+            ),
+        );
+        Ok(())
+    }
+
+    /// Runs `f` one level deeper, with `segment` appended to the path.
+    fn nested<T>(
+        &mut self,
+        segment: &str,
+        f: impl FnOnce(&mut Self) -> Result<T, DecodeError>,
+    ) -> Result<T, DecodeError> {
+        if self.depth >= self.limits.max_depth {
+            return Err(DecodeError::DepthLimit(self.limits.max_depth));
+        }
+        let path_len = self.path.len();
+        self.path.push_str(segment);
+        self.depth += 1;
+        let result = f(self);
+        self.depth -= 1;
+        self.path.truncate(path_len);
+        result
+    }
+
+    /// Reads a compact integer and records it as `what`, with its mode.
+    fn compact(&mut self, what: &str) -> Result<u128, DecodeError> {
+        let start = self.position;
+        let mode = self.bytes.get(start).map_or(0, |byte| byte & 0b11);
+        let mut rest = &self.bytes[start..];
+        let value = decode_compact(&mut rest, DecodeMode::Strict)?;
+        self.position = self.bytes.len() - rest.len();
+        self.record(
+            start,
+            format!("{} (mode 0b{:02b}, value {})", what, mode, value),
+        );
+        Ok(value)
+    }
+
+    /// Reads a compact collection length, bounded like [`LimitedInput`](crate::LimitedInput)
+    /// bounds it.
+    fn length(&mut self, what: &str) -> Result<usize, DecodeError> {
+        let len = usize::try_from(self.compact(what)?).unwrap_or(usize::MAX);
+        if len > self.limits.max_collection_len {
+            return Err(DecodeError::CollectionTooLong {
+                len,
+                max: self.limits.max_collection_len,
+            });
+        }
+        Ok(len)
+    }
+
+    fn read<T: Decode>(&mut self) -> Result<T, DecodeError> {
+        let mut rest = &self.bytes[self.position..];
+        let value = T::decode(&mut rest)?;
+        self.position = self.bytes.len() - rest.len();
+        Ok(value)
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        let remaining = self.bytes.len() - self.position;
+        if len > remaining {
+            return Err(DecodeError::NotEnoughData {
+                expected: len,
+                remaining,
+            });
+        }
+        let bytes = &self.bytes[self.position..self.position + len];
+        self.position += len;
+        Ok(bytes)
+    }
+
+    /// Records the bytes from `start` to the current position, unless
+    /// there are none.
+    fn record(&mut self, start: usize, description: String) {
+        if self.position > start {
+            self.spans.push(Span {
+                range: start..self.position,
+                path: self.path.clone(),
+                depth: self.depth,
+                description,
+            });
+        }
+    }
+}
+
+fn bit_string<S: BitStore, O: BitOrder>(encoded: &[u8]) -> Result<String, DecodeError> {
+    let bits = BitSeq::<S, O>::decode_all(encoded)?;
+    Ok(bits.iter().map(|bit| if bit { '1' } else { '0' }).collect())
+}
+
+fn spaced_hex(bytes: &[u8]) -> String {
+    let hex: Vec<_> = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+    hex.join(" ")
+}
+
+fn escape_cell(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Compact, Encode, Field, Type, Variant};
+
+    fn registry_with<T: crate::TypeInfo>() -> (Registry, u32) {
+        let mut registry = Registry::new();
+        let ty = registry.register::<T>();
+        (registry, ty)
+    }
+
+    fn lines(explanation: &Explanation) -> Vec<String> {
+        explanation.spans.iter().map(Span::to_string).collect()
+    }
+
+    #[test]
+    fn vector_of_integers() {
+        let (registry, ty) = registry_with::<Vec<u16>>();
+        let explanation = explain(&registry, ty, &vec![1u16, 2, 0x0300].encode());
+        assert_eq!(
+            lines(&explanation),
+            [
+                "bytes 0..1 = Vec<u16>: compact length (mode 0b00, value 3)",
+                "bytes 1..3 = Vec<u16>[0]: u16 LE (value 1)",
+                "bytes 3..5 = Vec<u16>[1]: u16 LE (value 2)",
+                "bytes 5..7 = Vec<u16>[2]: u16 LE (value 768)",
+            ]
+        );
+        assert_eq!(explanation.explained_len(), 7);
+        assert_eq!(
+            explanation.hex_dump(),
+            "0000  0c     Vec<u16>: compact length (mode 0b00, value 3)\n\
+             0001  01 00    Vec<u16>[0]: u16 LE (value 1)\n\
+             0003  02 00    Vec<u16>[1]: u16 LE (value 2)\n\
+             0005  00 03    Vec<u16>[2]: u16 LE (value 768)\n"
+        );
+    }
+
+    #[test]
+    fn compacts_strings_and_options() {
+        let (registry, ty) = registry_with::<(Compact<u64>, String, Option<bool>, Option<u8>)>();
+        let value = (
+            Compact(300u64),
+            String::from("dot"),
+            Some(false),
+            None::<u8>,
+        );
+        let explanation = explain(&registry, ty, &value.encode());
+        assert_eq!(
+            lines(&explanation),
+            [
+                "bytes 0..2 = (Compact<u64>, String, OptionBool, Option).0: compact u64 (mode 0b01, value 300)",
+                "bytes 2..3 = (Compact<u64>, String, OptionBool, Option).1: compact length (mode 0b00, value 3)",
+                "bytes 3..6 = (Compact<u64>, String, OptionBool, Option).1: UTF-8 \"dot\"",
+                "bytes 6..7 = (Compact<u64>, String, OptionBool, Option).2: variant index 2 (False)",
+                "bytes 7..8 = (Compact<u64>, String, OptionBool, Option).3: variant index 0 (None)",
+            ]
+        );
+    }
+
+    #[test]
+    fn bytes_and_bits() {
+        let (registry, ty) = registry_with::<([u8; 20], BitSeq<u8, Lsb0>)>();
+        let bits: BitSeq<u8, Lsb0> = [true, false, true].into_iter().collect();
+        let explanation = explain(&registry, ty, &([7u8; 20], bits).encode());
+        assert_eq!(
+            lines(&explanation)[1..],
+            [
+                "bytes 20..21 = ([u8; 20], BitSeq<u8, Lsb0>).1: compact bit count (mode 0b00, value 3)",
+                "bytes 21..22 = ([u8; 20], BitSeq<u8, Lsb0>).1: 1 u8 word, Lsb0 (bits 101)",
+            ]
+        );
+        // Long spans wrap in the dump and are cut short in tables.
+        let dump = explanation.hex_dump();
+        assert!(dump.starts_with("0000  07 07 07 07 07 07 07 07 07 07 07 07 07 07 07 07  "));
+        assert!(dump.contains("\n0010  07 07 07 07\n"));
+        let table = explanation.to_markdown();
+        assert!(table.starts_with("| Bytes | Hex | Field | Meaning |\n| --- | --- | --- | --- |\n"));
+        assert!(table.contains(
+            "| 0..20 | `07 07 07 07 07 07 07 07 07 07 07 07 07 07 07 07 …` \
+             | `([u8; 20], BitSeq<u8, Lsb0>).0` | 20 bytes |\n"
+        ));
+    }
+
+    #[test]
+    fn failures_keep_the_spans_before_them() {
+        let mut registry = Registry::new();
+        let u32_ty = registry.register::<u32>();
+        let ty = registry.add(Type::with_path(
+            ["Event"],
+            TypeDef::Variant(vec![
+                Variant::new("Ping", 0, vec![]),
+                Variant::new("Transfer", 3, vec![Field::named("amount", u32_ty)]),
+            ]),
+        ));
+
+        let explanation = explain(&registry, ty, &[0x03, 0x01, 0x02]);
+        assert_eq!(
+            lines(&explanation),
+            ["bytes 0..1 = Event: variant index 3 (Transfer)"]
+        );
+        assert_eq!(
+            explanation.error,
+            Some(DecodeError::NotEnoughData {
+                expected: 4,
+                remaining: 2
+            })
+        );
+        assert!(explanation
+            .to_string()
+            .ends_with("error after byte 1: Not enough bytes. Expected 4, got 2\n"));
+        assert!(explanation
+            .hex_dump()
+            .ends_with("0001  01 02: Not enough bytes. Expected 4, got 2\n"));
+
+        let explanation = explain(&registry, ty, &[0x03, 0x01, 0x00, 0x00, 0x00, 0xff]);
+        assert_eq!(
+            explanation.spans[1].to_string(),
+            "bytes 1..5 = Event.Transfer.amount: u32 LE (value 1)"
+        );
+        assert_eq!(explanation.error, Some(DecodeError::TrailingBytes(1)));
+
+        let explanation = explain(&registry, ty, &[0x01]);
+        assert_eq!(
+            lines(&explanation),
+            ["bytes 0..1 = Event: variant index 1 (unknown)"]
+        );
+        assert!(explanation
+            .to_markdown()
+            .ends_with("\nUnexplained from byte 1: Unknown registry enum variant tag: 0x01\n"));
+    }
+
+    #[test]
+    fn recursion_is_bounded() {
+        let mut registry = Registry::new();
+        let ty = registry.add(Type::new(TypeDef::Composite(vec![Field::unnamed(0)])));
+        let explanation = explain(&registry, ty, &[]);
+        assert_eq!(explanation.error, Some(DecodeError::DepthLimit(64)));
+
+        // A built-in containing itself, which also has no finite name.
+        let ty = registry.add(Type::new(TypeDef::Sequence(1)));
+        let explanation = explain(&registry, ty, &[0x04, 0x00]);
+        assert_eq!(
+            explanation.spans[0].to_string(),
+            "bytes 0..1 = Vec<<recursive type 1>>: compact length (mode 0b00, value 1)"
+        );
+        assert_eq!(
+            explanation.spans[1].to_string(),
+            "bytes 1..2 = Vec<<recursive type 1>>[0]: compact length (mode 0b00, value 0)"
+        );
+        assert_eq!(explanation.error, None);
+        let explanation = explain(&registry, ty, &[0x04].repeat(100));
+        assert_eq!(explanation.error, Some(DecodeError::DepthLimit(64)));
+    }
+}
@@ -0,0 +1,36 @@
+// Hex strings for byte blobs, as RPC nodes, type specs and the `scale` tool
+// write them.
+
+use crate::prelude::*;
+
+/// `bytes` as `0x`-prefixed lowercase hex.
+///
+/// ```
+/// assert_eq!(scale::to_hex(&[0x0c, 0xab]), "0x0cab");
+/// ```
+pub fn to_hex(bytes: &[u8]) -> String {
+    let mut hex = String::with_capacity(2 + 2 * bytes.len());
+    hex.push_str("0x");
+    for byte in bytes {
+        hex.push_str(&format!("{:02x}", byte));
+    }
+    hex
+}
+
+/// Decodes hex `digits` (without a `0x` prefix), two per byte. Returns
+/// `None` for an odd number of digits or anything that is not a hex digit.
+///
+/// ```
+/// assert_eq!(scale::from_hex("0cAB"), Some(vec![0x0c, 0xab]));
+/// assert_eq!(scale::from_hex("+f"), None);
+/// ```
+pub fn from_hex(digits: &str) -> Option<Vec<u8>> {
+    // `u8::from_str_radix` alone would also take a sign, e.g. `+f`.
+    if !digits.len().is_multiple_of(2) || !digits.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return None;
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).ok())
+        .collect()
+}
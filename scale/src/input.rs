@@ -0,0 +1,296 @@
+// Sources of SCALE bytes for the decoders: in-memory slices and, with the
+// `std` feature, any `std::io::Read`.
+
+#[cfg(feature = "std")]
+use std::io::{ErrorKind, Read};
+
+use crate::prelude::*;
+#[cfg(feature = "std")]
+use crate::{limits::Budget, DecodeLimits};
+use crate::{DecodeError, PathSegment};
+
+/// Largest chunk, in bytes, allocated at once for a collection whose length
+/// comes from the (untrusted) input.
+pub(crate) const MAX_PREALLOCATION: usize = 16 * 1024;
+
+/// A cursor over SCALE bytes. Every successful read advances the cursor
+/// past the bytes it returned.
+pub trait Input {
+    /// Number of bytes left, if the source knows it up front.
+    fn remaining_len(&mut self) -> Result<Option<usize>, DecodeError>;
+
+    /// Fills `into` completely, or fails with [`DecodeError::NotEnoughData`] if
+    /// the input ends first.
+    fn read(&mut self, into: &mut [u8]) -> Result<(), DecodeError>;
+
+    /// Reads a single byte.
+    fn read_byte(&mut self) -> Result<u8, DecodeError> {
+        let mut buf = [0u8];
+        self.read(&mut buf)?;
+        Ok(buf[0])
+    }
+
+    /// Called by collection decoders once the length prefix is known,
+    /// before any element is decoded. `alloc_bytes` is how much memory the
+    /// collection will take. Plain inputs accept anything;
+    /// [`LimitedInput`](crate::LimitedInput) enforces its limits here.
+    fn on_collection(&mut self, len: usize, alloc_bytes: usize) -> Result<(), DecodeError> {
+        let _ = (len, alloc_bytes);
+        Ok(())
+    }
+
+    /// Called before decoding a nested value (the elements of a
+    /// collection, the payload of an `Option` or the fields of a derived
+    /// type), so recursion depth can be bounded.
+    fn descend(&mut self) -> Result<(), DecodeError> {
+        Ok(())
+    }
+
+    /// Called after a nested value entered with [`Input::descend`] has been
+    /// decoded.
+    fn ascend(&mut self) {}
+
+    /// Called before decoding a part of a larger value: a field, an enum
+    /// variant or a collection element. `expected` is the part's
+    /// `std::any::type_name`. [`TrackedInput`](crate::TrackedInput) uses
+    /// this to report where decoding failed.
+    fn enter(&mut self, segment: PathSegment, expected: &'static str) {
+        let _ = (segment, expected);
+    }
+
+    /// Called once the part passed to [`Input::enter`] has been decoded.
+    /// Skipped when decoding fails, leaving the failing path in place.
+    fn leave(&mut self) {}
+}
+
+/// A bare slice has nowhere to count nesting, so decoding from one with
+/// [`Decode::decode`](crate::Decode::decode) is unbounded. Use
+/// [`Decode::decode_all`](crate::Decode::decode_all) or wrap the slice in a
+/// [`LimitedInput`](crate::LimitedInput) for untrusted bytes.
+impl Input for &[u8] {
+    fn remaining_len(&mut self) -> Result<Option<usize>, DecodeError> {
+        Ok(Some(self.len()))
+    }
+
+    fn read(&mut self, into: &mut [u8]) -> Result<(), DecodeError> {
+        into.copy_from_slice(read_bytes(self, into.len())?);
+        Ok(())
+    }
+}
+
+/// Adapts any [`std::io::Read`] (a file, a socket, ...) into an [`Input`],
+/// so large blobs can be decoded without buffering them whole.
+///
+/// Wrap the reader in a [`std::io::BufReader`] first if it is unbuffered;
+/// decoders issue many small reads. Decoding is bounded by
+/// [`DecodeLimits::default`] unless [`IoReader::with_limits`] says
+/// otherwise.
+#[cfg(feature = "std")]
+pub struct IoReader<R> {
+    reader: R,
+    position: u64,
+    budget: Budget,
+}
+
+#[cfg(feature = "std")]
+impl<R: Read> IoReader<R> {
+    pub fn new(reader: R) -> Self {
+        IoReader::with_limits(reader, DecodeLimits::default())
+    }
+
+    /// Like [`IoReader::new`], but bounded by `limits` instead;
+    /// [`DecodeLimits::UNLIMITED`] turns the checks off.
+    pub fn with_limits(reader: R, limits: DecodeLimits) -> Self {
+        IoReader {
+            reader,
+            position: 0,
+            budget: Budget::new(limits),
+        }
+    }
+
+    /// Number of bytes consumed so far.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Returns the wrapped reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: Read> Input for IoReader<R> {
+    fn remaining_len(&mut self) -> Result<Option<usize>, DecodeError> {
+        Ok(None)
+    }
+
+    fn read(&mut self, into: &mut [u8]) -> Result<(), DecodeError> {
+        let mut filled = 0;
+        while filled < into.len() {
+            match self.reader.read(&mut into[filled..]) {
+                Ok(0) => {
+                    self.position += filled as u64;
+                    return Err(DecodeError::NotEnoughData {
+                        expected: into.len(),
+                        remaining: filled,
+                    });
+                }
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(DecodeError::Io(e.kind())),
+            }
+        }
+        self.position += filled as u64;
+        Ok(())
+    }
+
+    fn on_collection(&mut self, len: usize, alloc_bytes: usize) -> Result<(), DecodeError> {
+        self.budget.on_collection(len, alloc_bytes)
+    }
+
+    fn descend(&mut self) -> Result<(), DecodeError> {
+        self.budget.descend()
+    }
+
+    fn ascend(&mut self) {
+        self.budget.ascend();
+    }
+}
+
+/// Runs `decode` one nesting level deeper.
+pub(crate) fn nested<I: Input, T>(
+    input: &mut I,
+    decode: impl FnOnce(&mut I) -> Result<T, DecodeError>,
+) -> Result<T, DecodeError> {
+    input.descend()?;
+    let value = decode(input);
+    input.ascend();
+    value
+}
+
+/// Splits `len` bytes off the front of `input`.
+pub(crate) fn read_bytes<'a>(input: &mut &'a [u8], len: usize) -> Result<&'a [u8], DecodeError> {
+    if input.len() < len {
+        return Err(DecodeError::NotEnoughData {
+            expected: len,
+            remaining: input.len(),
+        });
+    }
+    let (head, tail) = input.split_at(len);
+    *input = tail;
+    Ok(head)
+}
+
+/// Reads `len` bytes into a new vector.
+///
+/// When the input cannot tell how much is left, the vector grows in
+/// bounded chunks so a bogus length prefix fails at the end of the input
+/// instead of allocating `len` bytes up front.
+pub(crate) fn read_vec<I: Input>(input: &mut I, len: usize) -> Result<Vec<u8>, DecodeError> {
+    if let Some(remaining) = input.remaining_len()? {
+        if remaining < len {
+            return Err(DecodeError::NotEnoughData {
+                expected: len,
+                remaining,
+            });
+        }
+        let mut bytes = vec![0u8; len];
+        input.read(&mut bytes)?;
+        return Ok(bytes);
+    }
+
+    let mut bytes = Vec::with_capacity(len.min(MAX_PREALLOCATION));
+    while bytes.len() < len {
+        let start = bytes.len();
+        let chunk = (len - start).min(MAX_PREALLOCATION);
+        bytes.resize(start + chunk, 0);
+        input.read(&mut bytes[start..]).map_err(|e| match e {
+            DecodeError::NotEnoughData { remaining, .. } => DecodeError::NotEnoughData {
+                expected: len,
+                remaining: start + remaining,
+            },
+            e => e,
+        })?;
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Hands out at most one byte per `read` call, like a slow socket.
+    #[cfg(feature = "std")]
+    struct OneByteAtATime<'a>(&'a [u8]);
+
+    #[cfg(feature = "std")]
+    impl Read for OneByteAtATime<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.0.is_empty() || buf.is_empty() {
+                return Ok(0);
+            }
+            buf[0] = self.0[0];
+            self.0 = &self.0[1..];
+            Ok(1)
+        }
+    }
+
+    #[test]
+    fn slice_input_advances() {
+        let bytes = [1, 2, 3];
+        let mut input = &bytes[..];
+        let mut buf = [0u8; 2];
+        Input::read(&mut input, &mut buf).unwrap();
+        assert_eq!(buf, [1, 2]);
+        assert_eq!(input.remaining_len(), Ok(Some(1)));
+        assert_eq!(input.read_byte(), Ok(3));
+        assert!(input.read_byte().is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn io_reader_handles_partial_reads() {
+        let mut reader = IoReader::new(OneByteAtATime(&[1, 2, 3, 4]));
+        let mut buf = [0u8; 3];
+        reader.read(&mut buf).unwrap();
+        assert_eq!(buf, [1, 2, 3]);
+        assert_eq!(reader.position(), 3);
+        assert_eq!(
+            reader.read(&mut buf),
+            Err(DecodeError::NotEnoughData {
+                expected: 3,
+                remaining: 1
+            })
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn read_vec_reports_total_shortfall() {
+        let data = vec![7u8; MAX_PREALLOCATION + 10];
+        let mut reader = IoReader::new(&data[..]);
+        assert_eq!(
+            read_vec(&mut reader, 2 * MAX_PREALLOCATION),
+            Err(DecodeError::NotEnoughData {
+                expected: 2 * MAX_PREALLOCATION,
+                remaining: MAX_PREALLOCATION + 10
+            })
+        );
+
+        let mut reader = IoReader::new(&data[..]);
+        assert_eq!(read_vec(&mut reader, data.len()), Ok(data));
+    }
+
+    #[test]
+    fn read_vec_rejects_short_slice_up_front() {
+        let mut input = &[1u8, 2][..];
+        assert_eq!(
+            read_vec(&mut input, 1 << 30),
+            Err(DecodeError::NotEnoughData {
+                expected: 1 << 30,
+                remaining: 2
+            })
+        );
+    }
+}
@@ -0,0 +1,1110 @@
+// Dynamic conversions between SCALE bytes and `serde_json` values, driven
+// by the type descriptions in a [`Registry`] rather than by Rust types, plus
+// reading and writing registries as JSON type spec files.
+//
+// Values map to JSON as follows:
+//
+// - integers up to 64 bits and compacts of them are numbers; `u128` and
+//   `i128` are decimal strings, since JSON numbers cannot hold them,
+// - `Vec<u8>` and `[u8; N]` are `0x`-prefixed hex strings, other sequences,
+//   arrays and tuples are arrays, and `()` is `null`,
+// - structs with named fields are objects, newtypes are their only field,
+//   tuple structs are arrays and unit structs are `null`,
+// - unit variants are their name, e.g. `"Baz"`; other variants are an
+//   object with one key, e.g. `{"Foo": 4660}`,
+// - bit sequences are strings of `0`s and `1`s in sequence order.
+
+use std::fmt;
+
+use serde_json::{Map, Value};
+
+use crate::compact::{decode_compact, encode_compact};
+use crate::input::{nested, read_vec};
+use crate::vector::decode_len;
+use crate::{
+    from_hex, to_hex, BitSeq, BitStore, Decode, DecodeError, DecodeLimits, DecodeMode, Encode,
+    Field, Input, LimitedInput, Lsb0, Msb0, Output, Primitive, Registry, Type, TypeDef, Variant,
+};
+
+/// A JSON value or type spec that does not have the expected shape.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonError {
+    /// Where in the JSON document, e.g. `$.digest.logs[2]`.
+    pub path: String,
+    pub message: String,
+}
+
+impl JsonError {
+    fn new(path: &str, message: impl Into<String>) -> Self {
+        JsonError {
+            path: path.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at {}", self.message, self.path)
+    }
+}
+
+impl std::error::Error for JsonError {}
+
+/// Decodes a value of registry type `ty` from `input` into JSON.
+///
+/// ```
+/// use scale::{decode_value, Compact, Registry};
+/// use serde_json::json;
+///
+/// let mut registry = Registry::new();
+/// let ty = registry.register::<(Vec<Compact<u64>>, [u8; 2])>();
+/// let value = decode_value(&registry, ty, &mut &[0x08, 0x04, 0x00, 0xab, 0xcd][..]);
+/// assert_eq!(value, Ok(json!([[1, 0], "0xabcd"])));
+/// ```
+pub fn decode_value<I: Input>(
+    registry: &Registry,
+    ty: u32,
+    input: &mut I,
+) -> Result<Value, DecodeError> {
+    let def = &registry.get(ty)?.def;
+    match def {
+        TypeDef::Primitive(primitive) => decode_primitive(*primitive, input),
+        TypeDef::Compact(inner) => {
+            let primitive = registry.compact_primitive(*inner)?;
+            let value = decode_compact(input, DecodeMode::Strict)?;
+            if value > primitive.max_unsigned() {
+                return Err(DecodeError::CompactOverflow {
+                    type_name: primitive.name(),
+                });
+            }
+            Ok(unsigned_to_json(primitive, value))
+        }
+        TypeDef::Sequence(element) => {
+            let len = decode_len(input)?;
+            if registry.is_u8(*element) {
+                input.on_collection(len, len)?;
+                return Ok(Value::String(to_hex(&read_vec(input, len)?)));
+            }
+            input.on_collection(len, len.saturating_mul(std::mem::size_of::<Value>()))?;
+            nested(input, |input| {
+                decode_elements(registry, *element, len, input)
+            })
+        }
+        TypeDef::Array { len, element } => {
+            let len = *len as usize;
+            if registry.is_u8(*element) {
+                return Ok(Value::String(to_hex(&read_vec(input, len)?)));
+            }
+            nested(input, |input| {
+                decode_elements(registry, *element, len, input)
+            })
+        }
+        TypeDef::Tuple(elements) if elements.is_empty() => Ok(Value::Null),
+        TypeDef::Tuple(elements) => nested(input, |input| {
+            elements
+                .iter()
+                .map(|&element| decode_value(registry, element, input))
+                .collect()
+        }),
+        TypeDef::Composite(fields) => nested(input, |input| decode_fields(registry, fields, input)),
+        TypeDef::Variant(variants) => nested(input, |input| {
+            let tag = input.read_byte()?;
+            let variant = variants.iter().find(|variant| variant.index == tag).ok_or(
+                DecodeError::UnknownVariant {
+                    type_name: "registry enum",
+                    tag,
+                },
+            )?;
+            if variant.fields.is_empty() {
+                return Ok(Value::String(variant.name.clone()));
+            }
+            let fields = decode_fields(registry, &variant.fields, input)?;
+            Ok(Value::Object(Map::from_iter([(
+                variant.name.clone(),
+                fields,
+            )])))
+        }),
+        TypeDef::BitSequence { store, order } => {
+            let msb0 = registry.is_msb0(*order)?;
+            let bits = match (registry.store_primitive(*store)?, msb0) {
+                (Primitive::U8, false) => bits_to_string(BitSeq::<u8, Lsb0>::decode(input)?.iter()),
+                (Primitive::U8, true) => bits_to_string(BitSeq::<u8, Msb0>::decode(input)?.iter()),
+                (Primitive::U16, false) => {
+                    bits_to_string(BitSeq::<u16, Lsb0>::decode(input)?.iter())
+                }
+                (Primitive::U16, true) => {
+                    bits_to_string(BitSeq::<u16, Msb0>::decode(input)?.iter())
+                }
+                (Primitive::U32, false) => {
+                    bits_to_string(BitSeq::<u32, Lsb0>::decode(input)?.iter())
+                }
+                (Primitive::U32, true) => {
+                    bits_to_string(BitSeq::<u32, Msb0>::decode(input)?.iter())
+                }
+                (_, false) => bits_to_string(BitSeq::<u64, Lsb0>::decode(input)?.iter()),
+                (_, true) => bits_to_string(BitSeq::<u64, Msb0>::decode(input)?.iter()),
+            };
+            Ok(Value::String(bits))
+        }
+    }
+}
+
+/// [`decode_value`] from a byte slice under the default [`DecodeLimits`],
+/// as [`Decode::decode_all`] does, failing with
+/// [`DecodeError::TrailingBytes`] if anything is left over.
+pub fn decode_value_all(registry: &Registry, ty: u32, bytes: &[u8]) -> Result<Value, DecodeError> {
+    let mut input = LimitedInput::new(bytes, DecodeLimits::default());
+    let value = decode_value(registry, ty, &mut input)?;
+    let bytes = input.into_inner();
+    if !bytes.is_empty() {
+        return Err(DecodeError::TrailingBytes(bytes.len()));
+    }
+    Ok(value)
+}
+
+/// Encodes `value` as registry type `ty`, the reverse of [`decode_value`].
+///
+/// Integers may also be given as decimal strings and byte strings as
+/// arrays of numbers.
+///
+/// ```
+/// use scale::{encode_value, Compact, Registry};
+/// use serde_json::json;
+///
+/// let mut registry = Registry::new();
+/// let ty = registry.register::<(Vec<Compact<u64>>, [u8; 2])>();
+/// let encoded = encode_value(&registry, ty, &json!([[1, "0"], "0xabcd"]));
+/// assert_eq!(encoded, Ok(vec![0x08, 0x04, 0x00, 0xab, 0xcd]));
+/// ```
+pub fn encode_value(registry: &Registry, ty: u32, value: &Value) -> Result<Vec<u8>, JsonError> {
+    let mut dest = Vec::new();
+    encode_value_to(registry, ty, value, &mut dest)?;
+    Ok(dest)
+}
+
+/// [`encode_value`] appending to `dest`. On error, part of the value may
+/// already have been written.
+pub fn encode_value_to<O: Output + ?Sized>(
+    registry: &Registry,
+    ty: u32,
+    value: &Value,
+    dest: &mut O,
+) -> Result<(), JsonError> {
+    encode_at(registry, ty, value, dest, "$", 0)
+}
+
+fn decode_primitive<I: Input>(primitive: Primitive, input: &mut I) -> Result<Value, DecodeError> {
+    Ok(match primitive {
+        Primitive::Bool => Value::Bool(bool::decode(input)?),
+        Primitive::Str => Value::String(String::decode(input)?),
+        Primitive::U8 => u8::decode(input)?.into(),
+        Primitive::U16 => u16::decode(input)?.into(),
+        Primitive::U32 => u32::decode(input)?.into(),
+        Primitive::U64 => u64::decode(input)?.into(),
+        Primitive::U128 => Value::String(u128::decode(input)?.to_string()),
+        Primitive::I8 => i8::decode(input)?.into(),
+        Primitive::I16 => i16::decode(input)?.into(),
+        Primitive::I32 => i32::decode(input)?.into(),
+        Primitive::I64 => i64::decode(input)?.into(),
+        Primitive::I128 => Value::String(i128::decode(input)?.to_string()),
+    })
+}
+
+fn decode_elements<I: Input>(
+    registry: &Registry,
+    element: u32,
+    len: usize,
+    input: &mut I,
+) -> Result<Value, DecodeError> {
+    // The length may be bogus; let the elements run out instead of
+    // reserving room for all of them.
+    let mut values = Vec::with_capacity(len.min(1024));
+    for _ in 0..len {
+        values.push(decode_value(registry, element, input)?);
+    }
+    Ok(Value::Array(values))
+}
+
+fn decode_fields<I: Input>(
+    registry: &Registry,
+    fields: &[Field],
+    input: &mut I,
+) -> Result<Value, DecodeError> {
+    match fields {
+        [] => Ok(Value::Null),
+        [Field { name: None, ty }] => decode_value(registry, *ty, input),
+        _ if fields[0].name.is_some() => {
+            let mut object = Map::new();
+            for field in fields {
+                let name = field.name.clone().unwrap_or_default();
+                object.insert(name, decode_value(registry, field.ty, input)?);
+            }
+            Ok(Value::Object(object))
+        }
+        _ => fields
+            .iter()
+            .map(|field| decode_value(registry, field.ty, input))
+            .collect(),
+    }
+}
+
+fn unsigned_to_json(primitive: Primitive, value: u128) -> Value {
+    match primitive {
+        Primitive::U128 => Value::String(value.to_string()),
+        _ => (value as u64).into(),
+    }
+}
+
+fn bits_to_string(bits: impl Iterator<Item = bool>) -> String {
+    bits.map(|bit| if bit { '1' } else { '0' }).collect()
+}
+
+/// A `0x`-prefixed hex string, the form byte strings take in JSON.
+fn prefixed_hex(hex: &str) -> Option<Vec<u8>> {
+    hex.strip_prefix("0x").and_then(from_hex)
+}
+
+/// Encodes `value` at `path`, `depth` types below the root. A spec can
+/// describe a type that contains itself, such as a newtype of its own ID,
+/// whose JSON never gets smaller, so depth is bounded like decoding is.
+fn encode_at<O: Output + ?Sized>(
+    registry: &Registry,
+    ty: u32,
+    value: &Value,
+    dest: &mut O,
+    path: &str,
+    depth: usize,
+) -> Result<(), JsonError> {
+    let max_depth = DecodeLimits::default().max_depth;
+    if depth > max_depth {
+        return Err(JsonError::new(
+            path,
+            DecodeError::DepthLimit(max_depth).to_string(),
+        ));
+    }
+    let def = &registry
+        .resolve(ty)
+        .ok_or_else(|| JsonError::new(path, format!("no type with ID {} in the registry", ty)))?
+        .def;
+    match def {
+        TypeDef::Primitive(primitive) => encode_primitive(*primitive, value, dest, path),
+        TypeDef::Compact(inner) => {
+            let primitive = registry
+                .compact_primitive(*inner)
+                .map_err(|error| JsonError::new(path, error.to_string()))?;
+            let number = parse_integer(value, path)?;
+            let number = u128::try_from(number)
+                .ok()
+                .filter(|&n| n <= primitive.max_unsigned())
+                .ok_or_else(|| out_of_range(value, primitive, path))?;
+            encode_compact(number, dest);
+            Ok(())
+        }
+        TypeDef::Sequence(element) => {
+            if let (true, Value::String(hex)) = (registry.is_u8(*element), value) {
+                let bytes =
+                    prefixed_hex(hex).ok_or_else(|| JsonError::new(path, "invalid hex string"))?;
+                bytes.encode_to(dest);
+                return Ok(());
+            }
+            let values = expect_array(value, path)?;
+            encode_compact(values.len() as u128, dest);
+            encode_elements(registry, *element, values, dest, path, depth + 1)
+        }
+        TypeDef::Array { len, element } => {
+            if let (true, Value::String(hex)) = (registry.is_u8(*element), value) {
+                let bytes =
+                    prefixed_hex(hex).ok_or_else(|| JsonError::new(path, "invalid hex string"))?;
+                expect_len(bytes.len(), *len as usize, "bytes", path)?;
+                dest.write(&bytes);
+                return Ok(());
+            }
+            let values = expect_array(value, path)?;
+            expect_len(values.len(), *len as usize, "elements", path)?;
+            encode_elements(registry, *element, values, dest, path, depth + 1)
+        }
+        TypeDef::Tuple(elements) if elements.is_empty() && value.is_null() => Ok(()),
+        TypeDef::Tuple(elements) => {
+            let values = expect_array(value, path)?;
+            expect_len(values.len(), elements.len(), "elements", path)?;
+            for (i, (&element, value)) in elements.iter().zip(values).enumerate() {
+                let path = format!("{}[{}]", path, i);
+                encode_at(registry, element, value, dest, &path, depth + 1)?;
+            }
+            Ok(())
+        }
+        TypeDef::Composite(fields) => encode_fields(registry, fields, value, dest, path, depth + 1),
+        TypeDef::Variant(variants) => {
+            let (name, fields) = match value {
+                Value::String(name) => (name, &Value::Null),
+                Value::Object(object) if object.len() == 1 => object.iter().next().unwrap(),
+                _ => {
+                    return Err(JsonError::new(
+                        path,
+                        "expected a variant name or an object with one key",
+                    ))
+                }
+            };
+            let variant = find_variant(variants, name, path)?;
+            dest.push_byte(variant.index);
+            encode_fields(
+                registry,
+                &variant.fields,
+                fields,
+                dest,
+                &format!("{}.{}", path, name),
+                depth + 1,
+            )
+        }
+        TypeDef::BitSequence { store, order } => {
+            let error = |e: DecodeError| JsonError::new(path, e.to_string());
+            let msb0 = registry.is_msb0(*order).map_err(error)?;
+            let store = registry.store_primitive(*store).map_err(error)?;
+            let bits = parse_bits(value, path)?;
+            match (store, msb0) {
+                (Primitive::U8, false) => encode_bits::<u8, Lsb0, O>(&bits, dest),
+                (Primitive::U8, true) => encode_bits::<u8, Msb0, O>(&bits, dest),
+                (Primitive::U16, false) => encode_bits::<u16, Lsb0, O>(&bits, dest),
+                (Primitive::U16, true) => encode_bits::<u16, Msb0, O>(&bits, dest),
+                (Primitive::U32, false) => encode_bits::<u32, Lsb0, O>(&bits, dest),
+                (Primitive::U32, true) => encode_bits::<u32, Msb0, O>(&bits, dest),
+                (_, false) => encode_bits::<u64, Lsb0, O>(&bits, dest),
+                (_, true) => encode_bits::<u64, Msb0, O>(&bits, dest),
+            }
+            Ok(())
+        }
+    }
+}
+
+fn encode_elements<O: Output + ?Sized>(
+    registry: &Registry,
+    element: u32,
+    values: &[Value],
+    dest: &mut O,
+    path: &str,
+    depth: usize,
+) -> Result<(), JsonError> {
+    for (i, value) in values.iter().enumerate() {
+        let path = format!("{}[{}]", path, i);
+        encode_at(registry, element, value, dest, &path, depth)?;
+    }
+    Ok(())
+}
+
+fn encode_fields<O: Output + ?Sized>(
+    registry: &Registry,
+    fields: &[Field],
+    value: &Value,
+    dest: &mut O,
+    path: &str,
+    depth: usize,
+) -> Result<(), JsonError> {
+    match fields {
+        [] if value.is_null() => Ok(()),
+        [] => Err(JsonError::new(
+            path,
+            "expected null for a type without fields",
+        )),
+        [Field { name: None, ty }] => encode_at(registry, *ty, value, dest, path, depth),
+        _ if fields[0].name.is_some() => {
+            let Value::Object(object) = value else {
+                return Err(JsonError::new(path, "expected an object"));
+            };
+            if let Some(unknown) = object
+                .keys()
+                .find(|key| !fields.iter().any(|f| f.name.as_ref() == Some(*key)))
+            {
+                return Err(JsonError::new(path, format!("unknown field `{}`", unknown)));
+            }
+            for field in fields {
+                let name = field.name.as_deref().unwrap_or_default();
+                let field_path = format!("{}.{}", path, name);
+                let value = object
+                    .get(name)
+                    .ok_or_else(|| JsonError::new(path, format!("missing field `{}`", name)))?;
+                encode_at(registry, field.ty, value, dest, &field_path, depth)?;
+            }
+            Ok(())
+        }
+        _ => {
+            let values = expect_array(value, path)?;
+            expect_len(values.len(), fields.len(), "fields", path)?;
+            for (i, (field, value)) in fields.iter().zip(values).enumerate() {
+                let path = format!("{}[{}]", path, i);
+                encode_at(registry, field.ty, value, dest, &path, depth)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+fn find_variant<'a>(
+    variants: &'a [Variant],
+    name: &str,
+    path: &str,
+) -> Result<&'a Variant, JsonError> {
+    variants
+        .iter()
+        .find(|variant| variant.name == name)
+        .ok_or_else(|| JsonError::new(path, format!("unknown variant `{}`", name)))
+}
+
+fn encode_primitive<O: Output + ?Sized>(
+    primitive: Primitive,
+    value: &Value,
+    dest: &mut O,
+    path: &str,
+) -> Result<(), JsonError> {
+    macro_rules! integer {
+        ($t:ty) => {{
+            let number = parse_integer(value, path)?;
+            <$t>::try_from(number)
+                .map_err(|_| out_of_range(value, primitive, path))?
+                .encode_to(dest)
+        }};
+    }
+    match primitive {
+        Primitive::Bool => value
+            .as_bool()
+            .ok_or_else(|| JsonError::new(path, "expected a boolean"))?
+            .encode_to(dest),
+        Primitive::Str => value
+            .as_str()
+            .ok_or_else(|| JsonError::new(path, "expected a string"))?
+            .encode_to(dest),
+        Primitive::U8 => integer!(u8),
+        Primitive::U16 => integer!(u16),
+        Primitive::U32 => integer!(u32),
+        Primitive::U64 => integer!(u64),
+        Primitive::U128 => integer!(u128),
+        Primitive::I8 => integer!(i8),
+        Primitive::I16 => integer!(i16),
+        Primitive::I32 => integer!(i32),
+        Primitive::I64 => integer!(i64),
+        Primitive::I128 => integer!(i128),
+    }
+    Ok(())
+}
+
+/// An integer given as a JSON number or a decimal string. `i128` cannot
+/// hold every `u128`, so the two are kept apart.
+enum Integer {
+    Unsigned(u128),
+    Signed(i128),
+}
+
+impl TryFrom<Integer> for u128 {
+    type Error = ();
+
+    fn try_from(number: Integer) -> Result<Self, ()> {
+        match number {
+            Integer::Unsigned(n) => Ok(n),
+            Integer::Signed(n) => u128::try_from(n).map_err(|_| ()),
+        }
+    }
+}
+
+macro_rules! integer_from {
+    ($($t:ty),*) => {
+        $(
+            impl TryFrom<Integer> for $t {
+                type Error = ();
+
+                fn try_from(number: Integer) -> Result<Self, ()> {
+                    match number {
+                        Integer::Unsigned(n) => <$t>::try_from(n).map_err(|_| ()),
+                        Integer::Signed(n) => <$t>::try_from(n).map_err(|_| ()),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+integer_from!(u8, u16, u32, u64, i8, i16, i32, i64, i128);
+
+fn parse_integer(value: &Value, path: &str) -> Result<Integer, JsonError> {
+    let parsed = match value {
+        Value::Number(number) => match (number.as_u64(), number.as_i64()) {
+            (Some(n), _) => Some(Integer::Unsigned(n.into())),
+            (None, Some(n)) => Some(Integer::Signed(n.into())),
+            _ => None,
+        },
+        Value::String(text) => match text.parse::<u128>() {
+            Ok(n) => Some(Integer::Unsigned(n)),
+            Err(_) => text.parse::<i128>().ok().map(Integer::Signed),
+        },
+        _ => None,
+    };
+    parsed.ok_or_else(|| JsonError::new(path, "expected an integer or a decimal string"))
+}
+
+fn out_of_range(value: &Value, primitive: Primitive, path: &str) -> JsonError {
+    JsonError::new(
+        path,
+        format!("{} does not fit in {}", value, primitive.name()),
+    )
+}
+
+fn expect_array<'a>(value: &'a Value, path: &str) -> Result<&'a [Value], JsonError> {
+    value
+        .as_array()
+        .map(Vec::as_slice)
+        .ok_or_else(|| JsonError::new(path, "expected an array"))
+}
+
+fn expect_len(found: usize, expected: usize, what: &str, path: &str) -> Result<(), JsonError> {
+    if found != expected {
+        return Err(JsonError::new(
+            path,
+            format!("expected {} {}, found {}", expected, what, found),
+        ));
+    }
+    Ok(())
+}
+
+fn parse_bits(value: &Value, path: &str) -> Result<Vec<bool>, JsonError> {
+    let invalid = || {
+        JsonError::new(
+            path,
+            "expected a string of 0s and 1s or an array of booleans",
+        )
+    };
+    match value {
+        Value::String(bits) => bits
+            .chars()
+            .map(|c| match c {
+                '0' => Ok(false),
+                '1' => Ok(true),
+                _ => Err(invalid()),
+            })
+            .collect(),
+        Value::Array(bits) => bits
+            .iter()
+            .map(|bit| bit.as_bool().ok_or_else(invalid))
+            .collect(),
+        _ => Err(invalid()),
+    }
+}
+
+fn encode_bits<S, O, Out>(bits: &[bool], dest: &mut Out)
+where
+    S: BitStore,
+    O: crate::BitOrder,
+    Out: Output + ?Sized,
+{
+    bits.iter()
+        .copied()
+        .collect::<BitSeq<S, O>>()
+        .encode_to(dest);
+}
+
+// Type spec files use the JSON layout of `scale-info`'s portable registry,
+// as found in runtime metadata, without `params` and `docs`:
+//
+//     {"types": [{"id": 0, "type": {"path": ["MyScaleEnum"], "def": {"variant": {"variants": [
+//         {"name": "Foo", "index": 0, "fields": [{"type": 1}]}, ...]}}}}, ...]}
+impl Registry {
+    /// Reads a registry from a JSON type spec in the layout of
+    /// `scale-info`'s portable registry. Type IDs must run from 0 in order.
+    ///
+    /// ```
+    /// use scale::{decode_value_all, Registry};
+    /// use serde_json::json;
+    ///
+    /// let registry = Registry::from_json(&json!({"types": [
+    ///     {"id": 0, "type": {"path": ["MyScaleEnum"], "def": {"variant": {"variants": [
+    ///         {"name": "Foo", "index": 0, "fields": [{"type": 1}]},
+    ///         {"name": "Bar", "index": 1, "fields": [{"type": 2}]},
+    ///         {"name": "Baz", "index": 2},
+    ///     ]}}}},
+    ///     {"id": 1, "type": {"def": {"primitive": "u16"}}},
+    ///     {"id": 2, "type": {"def": {"primitive": "bool"}}},
+    /// ]})).unwrap();
+    /// assert_eq!(decode_value_all(&registry, 0, &[0x00, 0x34, 0x12]), Ok(json!({"Foo": 0x1234})));
+    /// ```
+    pub fn from_json(spec: &Value) -> Result<Registry, JsonError> {
+        let entries = spec
+            .get("types")
+            .and_then(Value::as_array)
+            .ok_or_else(|| JsonError::new("$", "expected an object with a `types` array"))?;
+        let mut registry = Registry::new();
+        for (position, entry) in entries.iter().enumerate() {
+            let path = format!("$.types[{}]", position);
+            if let Some(id) = entry.get("id") {
+                if id.as_u64() != Some(position as u64) {
+                    return Err(JsonError::new(
+                        &path,
+                        format!("expected type ID {}", position),
+                    ));
+                }
+            }
+            let ty = entry
+                .get("type")
+                .ok_or_else(|| JsonError::new(&path, "missing `type`"))?;
+            registry.add(type_from_json(ty, &format!("{}.type", path))?);
+        }
+        for (id, ty) in registry.types() {
+            if let Some(missing) =
+                referenced_types(&ty.def).find(|&r| registry.resolve(r).is_none())
+            {
+                return Err(JsonError::new(
+                    &format!("$.types[{}]", id),
+                    format!("refers to type {}, which is not defined", missing),
+                ));
+            }
+        }
+        if let Some(id) = unnamed_cycle(&registry) {
+            return Err(JsonError::new(
+                &format!("$.types[{}]", id),
+                "contains itself with no named type in between",
+            ));
+        }
+        Ok(registry)
+    }
+
+    /// Writes the registry as a JSON type spec that
+    /// [`Registry::from_json`] reads back.
+    pub fn to_json(&self) -> Value {
+        let types = self
+            .types()
+            .map(|(id, ty)| {
+                let mut described = Map::new();
+                if !ty.path.is_empty() {
+                    described.insert("path".into(), ty.path.clone().into());
+                }
+                described.insert("def".into(), def_to_json(&ty.def));
+                let mut entry = Map::new();
+                entry.insert("id".into(), id.into());
+                entry.insert("type".into(), described.into());
+                Value::Object(entry)
+            })
+            .collect();
+        let mut spec = Map::new();
+        spec.insert("types".into(), Value::Array(types));
+        Value::Object(spec)
+    }
+}
+
+fn referenced_types(def: &TypeDef) -> Box<dyn Iterator<Item = u32> + '_> {
+    match def {
+        TypeDef::Composite(fields) => Box::new(fields.iter().map(|field| field.ty)),
+        TypeDef::Variant(variants) => Box::new(
+            variants
+                .iter()
+                .flat_map(|variant| variant.fields.iter().map(|field| field.ty)),
+        ),
+        TypeDef::Sequence(element) | TypeDef::Compact(element) => Box::new([*element].into_iter()),
+        TypeDef::Array { element, .. } => Box::new([*element].into_iter()),
+        TypeDef::Tuple(elements) => Box::new(elements.iter().copied()),
+        TypeDef::Primitive(_) => Box::new(std::iter::empty()),
+        TypeDef::BitSequence { store, order } => Box::new([*store, *order].into_iter()),
+    }
+}
+
+/// A type that contains itself through built-ins alone, such as a sequence
+/// of itself. Built-ins are named after what they contain, so such a type
+/// has no finite name; recursion must go through a named type, as it does
+/// for any Rust type.
+fn unnamed_cycle(registry: &Registry) -> Option<u32> {
+    // The types a built-in is named after; named types end the walk.
+    let parts = |id: u32| -> Vec<u32> {
+        match registry.resolve(id) {
+            Some(ty) if ty.path.is_empty() => match ty.def {
+                TypeDef::Composite(_) | TypeDef::Variant(_) => Vec::new(),
+                ref def => referenced_types(def).collect(),
+            },
+            _ => Vec::new(),
+        }
+    };
+    #[derive(Clone, Copy, PartialEq)]
+    enum State {
+        New,
+        Open,
+        Done,
+    }
+    // A depth-first walk on an explicit stack, as specs may nest deeply.
+    let mut state = vec![State::New; registry.len()];
+    for root in 0..registry.len() as u32 {
+        if state[root as usize] != State::New {
+            continue;
+        }
+        state[root as usize] = State::Open;
+        let mut stack = vec![(root, parts(root).into_iter())];
+        while let Some((id, remaining)) = stack.last_mut() {
+            match remaining.next() {
+                Some(next) => match state[next as usize] {
+                    State::Open => return Some(next),
+                    State::New => {
+                        state[next as usize] = State::Open;
+                        stack.push((next, parts(next).into_iter()));
+                    }
+                    State::Done => {}
+                },
+                None => {
+                    state[*id as usize] = State::Done;
+                    stack.pop();
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Spec names of the primitives, as in `scale-info`.
+const PRIMITIVES: [(&str, Primitive); 12] = [
+    ("bool", Primitive::Bool),
+    ("str", Primitive::Str),
+    ("u8", Primitive::U8),
+    ("u16", Primitive::U16),
+    ("u32", Primitive::U32),
+    ("u64", Primitive::U64),
+    ("u128", Primitive::U128),
+    ("i8", Primitive::I8),
+    ("i16", Primitive::I16),
+    ("i32", Primitive::I32),
+    ("i64", Primitive::I64),
+    ("i128", Primitive::I128),
+];
+
+fn def_to_json(def: &TypeDef) -> Value {
+    let (kind, body) = match def {
+        TypeDef::Composite(fields) => ("composite", object([("fields", fields_to_json(fields))])),
+        TypeDef::Variant(variants) => {
+            let variants = variants
+                .iter()
+                .map(|variant| {
+                    object([
+                        ("name", variant.name.clone().into()),
+                        ("index", variant.index.into()),
+                        ("fields", fields_to_json(&variant.fields)),
+                    ])
+                })
+                .collect();
+            ("variant", object([("variants", Value::Array(variants))]))
+        }
+        TypeDef::Sequence(element) => ("sequence", object([("type", (*element).into())])),
+        TypeDef::Array { len, element } => (
+            "array",
+            object([("len", (*len).into()), ("type", (*element).into())]),
+        ),
+        TypeDef::Tuple(elements) => ("tuple", elements.clone().into()),
+        TypeDef::Primitive(primitive) => {
+            let name = PRIMITIVES.iter().find(|(_, p)| p == primitive).unwrap().0;
+            ("primitive", name.into())
+        }
+        TypeDef::Compact(inner) => ("compact", object([("type", (*inner).into())])),
+        TypeDef::BitSequence { store, order } => (
+            "bitSequence",
+            object([
+                ("bitStoreType", (*store).into()),
+                ("bitOrderType", (*order).into()),
+            ]),
+        ),
+    };
+    object([(kind, body)])
+}
+
+fn fields_to_json(fields: &[Field]) -> Value {
+    fields
+        .iter()
+        .map(|field| match &field.name {
+            Some(name) => object([("name", name.clone().into()), ("type", field.ty.into())]),
+            None => object([("type", field.ty.into())]),
+        })
+        .collect()
+}
+
+fn object<const N: usize>(entries: [(&str, Value); N]) -> Value {
+    Value::Object(
+        entries
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v))
+            .collect(),
+    )
+}
+
+fn type_from_json(ty: &Value, path: &str) -> Result<Type, JsonError> {
+    let type_path = match ty.get("path") {
+        None => Vec::new(),
+        Some(segments) => expect_array(segments, &format!("{}.path", path))?
+            .iter()
+            .map(|segment| {
+                segment
+                    .as_str()
+                    .map(String::from)
+                    .ok_or_else(|| JsonError::new(&format!("{}.path", path), "expected strings"))
+            })
+            .collect::<Result<_, _>>()?,
+    };
+    let def_path = format!("{}.def", path);
+    let def = match ty.get("def").and_then(Value::as_object) {
+        Some(def) if def.len() == 1 => def.iter().next().unwrap(),
+        _ => return Err(JsonError::new(&def_path, "expected an object with one key")),
+    };
+    let (kind, body) = def;
+    let body_path = format!("{}.{}", def_path, kind);
+    let def = match kind.as_str() {
+        "composite" => TypeDef::Composite(fields_from_json(body.get("fields"), &body_path)?),
+        "variant" => {
+            let variants = match body.get("variants") {
+                None => &[][..],
+                Some(variants) => expect_array(variants, &body_path)?,
+            };
+            let mut parsed = Vec::with_capacity(variants.len());
+            for (i, variant) in variants.iter().enumerate() {
+                let path = format!("{}.variants[{}]", body_path, i);
+                let name = variant
+                    .get("name")
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| JsonError::new(&path, "missing variant `name`"))?;
+                let index = variant
+                    .get("index")
+                    .and_then(Value::as_u64)
+                    .and_then(|index| u8::try_from(index).ok())
+                    .ok_or_else(|| JsonError::new(&path, "missing or invalid variant `index`"))?;
+                let fields = fields_from_json(variant.get("fields"), &path)?;
+                parsed.push(Variant::new(name, index, fields));
+            }
+            TypeDef::Variant(parsed)
+        }
+        "sequence" => TypeDef::Sequence(type_id(body.get("type"), &body_path)?),
+        "array" => TypeDef::Array {
+            len: type_id(body.get("len"), &body_path)?,
+            element: type_id(body.get("type"), &body_path)?,
+        },
+        "tuple" => TypeDef::Tuple(
+            expect_array(body, &body_path)?
+                .iter()
+                .map(|element| type_id(Some(element), &body_path))
+                .collect::<Result<_, _>>()?,
+        ),
+        "primitive" => {
+            let name = body.as_str().unwrap_or_default();
+            let primitive = PRIMITIVES
+                .iter()
+                .find(|(spec_name, _)| *spec_name == name)
+                .ok_or_else(|| {
+                    JsonError::new(&body_path, format!("unsupported primitive {}", body))
+                })?
+                .1;
+            TypeDef::Primitive(primitive)
+        }
+        "compact" => TypeDef::Compact(type_id(body.get("type"), &body_path)?),
+        "bitSequence" => TypeDef::BitSequence {
+            store: type_id(body.get("bitStoreType"), &body_path)?,
+            order: type_id(body.get("bitOrderType"), &body_path)?,
+        },
+        other => {
+            return Err(JsonError::new(
+                &def_path,
+                format!("unknown type kind `{}`", other),
+            ));
+        }
+    };
+    Ok(Type {
+        path: type_path,
+        def,
+    })
+}
+
+fn fields_from_json(fields: Option<&Value>, path: &str) -> Result<Vec<Field>, JsonError> {
+    let Some(fields) = fields else {
+        return Ok(Vec::new());
+    };
+    expect_array(fields, path)?
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            let path = format!("{}.fields[{}]", path, i);
+            let ty = type_id(field.get("type"), &path)?;
+            Ok(match field.get("name").and_then(Value::as_str) {
+                Some(name) => Field::named(name, ty),
+                None => Field::unnamed(ty),
+            })
+        })
+        .collect()
+}
+
+fn type_id(value: Option<&Value>, path: &str) -> Result<u32, JsonError> {
+    value
+        .and_then(Value::as_u64)
+        .and_then(|id| u32::try_from(id).ok())
+        .ok_or_else(|| JsonError::new(path, "expected a type ID"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BitSeq, Compact};
+    use serde_json::json;
+    use std::collections::BTreeMap;
+
+    fn round_trip<T: crate::TypeInfo + Encode>(value: &T, expected: Value) {
+        let mut registry = Registry::new();
+        let ty = registry.register::<T>();
+        let encoded = value.encode();
+        assert_eq!(
+            decode_value_all(&registry, ty, &encoded),
+            Ok(expected.clone())
+        );
+        assert_eq!(encode_value(&registry, ty, &expected), Ok(encoded));
+    }
+
+    #[test]
+    fn primitives_and_collections() {
+        round_trip(&(true, -5i8, 70_000u32), json!([true, -5, 70000]));
+        round_trip(&u128::MAX, json!(u128::MAX.to_string()));
+        round_trip(&Compact(1u128 << 100), json!((1u128 << 100).to_string()));
+        round_trip(&vec![Compact(1u32), Compact(64)], json!([1, 64]));
+        round_trip(&(vec![0xdeu8, 0xad], [0u8; 2]), json!(["0xdead", "0x0000"]));
+        round_trip(&[[1u16, 2], [3, 4]], json!([[1, 2], [3, 4]]));
+        round_trip(&String::from("dot"), json!("dot"));
+        round_trip(&(), json!(null));
+        round_trip(&BTreeMap::from([(1u8, true)]), json!([[1, true]]));
+    }
+
+    #[test]
+    fn options_results_and_bits() {
+        round_trip(&Some(3u16), json!({"Some": 3}));
+        round_trip(&None::<u16>, json!("None"));
+        round_trip(&Some(false), json!("False"));
+        round_trip(&Err::<u8, String>("no".into()), json!({"Err": "no"}));
+        let bits: BitSeq<u32, Msb0> = [true, false, true].into_iter().collect();
+        round_trip(&bits, json!("101"));
+    }
+
+    #[test]
+    fn lenient_json_inputs() {
+        let mut registry = Registry::new();
+        let ty = registry.register::<(u64, Vec<u8>, BitSeq)>();
+        let value = json!(["18446744073709551615", [1, 2], [true, false]]);
+        let expected = (
+            u64::MAX,
+            vec![1u8, 2],
+            [true, false].into_iter().collect::<BitSeq>(),
+        );
+        assert_eq!(encode_value(&registry, ty, &value), Ok(expected.encode()));
+    }
+
+    #[test]
+    fn mismatched_json_reports_path() {
+        let mut registry = Registry::new();
+        let ty = registry.register::<Vec<(u8, Option<u16>)>>();
+        let error = encode_value(&registry, ty, &json!([[1, "None"], [2, {"Some": 70000}]]));
+        assert_eq!(
+            error,
+            Err(JsonError::new("$[1][1].Some", "70000 does not fit in u16"))
+        );
+        let error = encode_value(&registry, ty, &json!([[1, "Maybe"]])).unwrap_err();
+        assert_eq!(error.to_string(), "unknown variant `Maybe` at $[0][1]");
+        let bytes = registry.register::<Vec<u8>>();
+        assert_eq!(
+            encode_value(&registry, bytes, &json!("0x+f")),
+            Err(JsonError::new("$", "invalid hex string"))
+        );
+    }
+
+    #[test]
+    fn decoding_errors() {
+        let mut registry = Registry::new();
+        let ty = registry.register::<Compact<u8>>();
+        assert_eq!(
+            decode_value_all(&registry, ty, &[0x01, 0x04]),
+            Err(DecodeError::CompactOverflow { type_name: "u8" })
+        );
+        assert_eq!(
+            decode_value_all(&registry, 99, &[0x00]),
+            Err(DecodeError::UnknownTypeId(99))
+        );
+        let text = registry.register::<String>();
+        let bad = registry.add(Type::new(TypeDef::Compact(text)));
+        assert!(matches!(
+            decode_value_all(&registry, bad, &[0x00]),
+            Err(DecodeError::UnsupportedType { id, .. }) if id == text
+        ));
+    }
+
+    #[test]
+    fn self_referencing_specs() {
+        let tuple = Registry::from_json(&json!({"types": [
+            {"id": 0, "type": {"path": ["Loop"], "def": {"tuple": [0]}}},
+        ]}))
+        .unwrap();
+        let newtype = Registry::from_json(&json!({"types": [
+            {"id": 0, "type": {"path": ["A"], "def": {"composite": {"fields": [{"type": 0}]}}}},
+        ]}))
+        .unwrap();
+        for registry in [&tuple, &newtype] {
+            let mut input = LimitedInput::new(&[0x00][..], DecodeLimits::default());
+            assert_eq!(
+                decode_value(registry, 0, &mut input),
+                Err(DecodeError::DepthLimit(64))
+            );
+            assert_eq!(
+                decode_value_all(registry, 0, &[0x00]),
+                Err(DecodeError::DepthLimit(64))
+            );
+        }
+        let error = encode_value(&newtype, 0, &json!(1)).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Nesting exceeds the depth limit of 64 at $"
+        );
+        let error = encode_value(&tuple, 0, &json!([[[[1]]]])).unwrap_err();
+        assert_eq!(error.to_string(), "expected an array at $[0][0][0][0]");
+    }
+
+    #[test]
+    fn spec_round_trip() {
+        let mut registry = Registry::new();
+        let ty = registry.register::<(Option<BitSeq<u8, Msb0>>, Result<Compact<u32>, [i16; 2]>)>();
+        let spec = registry.to_json();
+        let parsed = Registry::from_json(&spec).unwrap();
+        assert_eq!(parsed.to_json(), spec);
+        assert!(parsed.types().eq(registry.types()));
+
+        let value = (
+            Some([true].into_iter().collect::<BitSeq<u8, Msb0>>()),
+            Ok::<_, [i16; 2]>(Compact(9u32)),
+        );
+        let encoded = value.encode();
+        assert_eq!(
+            decode_value_all(&parsed, ty, &encoded),
+            decode_value_all(&registry, ty, &encoded)
+        );
+    }
+
+    #[test]
+    fn invalid_specs() {
+        let error = Registry::from_json(&json!({"types": [
+            {"id": 0, "type": {"def": {"sequence": {"type": 1}}}},
+        ]}));
+        assert_eq!(
+            error.unwrap_err().to_string(),
+            "refers to type 1, which is not defined at $.types[0]"
+        );
+        let error = Registry::from_json(&json!({"types": [
+            {"id": 0, "type": {"def": {"primitive": "char"}}},
+        ]}));
+        assert_eq!(
+            error.unwrap_err().to_string(),
+            "unsupported primitive \"char\" at $.types[0].type.def.primitive"
+        );
+        for cycle in [
+            json!([{"id": 0, "type": {"def": {"sequence": {"type": 0}}}}]),
+            json!([
+                {"id": 0, "type": {"def": {"tuple": [2, 1]}}},
+                {"id": 1, "type": {"def": {"array": {"len": 2, "type": 0}}}},
+                {"id": 2, "type": {"def": {"primitive": "u8"}}},
+            ]),
+        ] {
+            let error = Registry::from_json(&json!({ "types": cycle }));
+            assert_eq!(
+                error.unwrap_err().to_string(),
+                "contains itself with no named type in between at $.types[0]"
+            );
+        }
+    }
+}
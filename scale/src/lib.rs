@@ -0,0 +1,203 @@
+//! Shared SCALE codec for the PBA Campus 2025 study notes.
+//!
+//! The examples under `examples/SCALE/` spell out every encoding rule by
+//! hand so they can be read top to bottom. This crate collects the same
+//! rules behind one pair of traits, [`Encode`] and [`Decode`], so other
+//! code can depend on a single implementation:
+//!
+//! - fixed-width integers (`u8`..`u128`, `i8`..`i128`) in little-endian
+//!   (example 01) and `bool`,
+//! - [`Compact`] integers (example 02), with [`CompactBig`] covering the
+//!   full 2^536 - 1 range,
+//! - `Vec<T>` and `[T]` for any element type, including nested vectors
+//!   (example 04),
+//! - `BTreeMap<K, V>` and `BTreeSet<T>` as a length prefix followed by the
+//!   sorted entries,
+//! - `[T; N]` for any element type, with `[u8; N]` copied in one go
+//!   (example 05),
+//! - [`BitSeq`] bit sequences with `u8`..`u64` store words in [`Lsb0`] or
+//!   [`Msb0`] order, laid out like `bitvec`'s `BitVec`,
+//! - `Option<T>` (with the single-byte `Option<bool>` form), `Result<T, E>`,
+//!   tuples and UTF-8 `String`s.
+//!
+//! Decoders read from any [`Input`]: a `&[u8]` slice, or an `IoReader`
+//! wrapping a file or socket so large blobs need not be buffered whole.
+//! In-memory inputs can also be decoded without copying through
+//! [`DecodeBorrowed`], which lends `&[u8]`, `&str` and `&[u8; N]` slices.
+//!
+//! Length prefixes come from the input, so decoding is bounded by
+//! [`DecodeLimits`]: collection lengths, total allocation and nesting depth.
+//! [`Decode::decode_all`] and `IoReader` apply [`DecodeLimits::default`];
+//! other inputs, including a bare `&[u8]` passed to [`Decode::decode`], are
+//! bounded by wrapping them in a [`LimitedInput`].
+//! [`DecodeLimits::UNLIMITED`] opts out.
+//!
+//! Encoders append to any [`Output`] through [`Encode::encode_to`]: an
+//! existing `Vec<u8>`, a fixed [`ArrayOutput`] on the stack, or a hasher
+//! via [`HasherOutput`], so a value can be hashed without first being
+//! encoded into a vector. [`Encode::encode`] is a shorthand for encoding
+//! into a new `Vec`.
+//!
+//! Buffers can be sized up front: [`Encode::encoded_size`] gives the exact
+//! length a value encodes to without encoding it ([`compact_len`] does the
+//! same for a compact integer), and [`MaxEncodedLen`] gives a bound known
+//! from the type alone for integers, fixed arrays, and structs and enums
+//! made of them, e.g. for storage size limits.
+//!
+//! Every decoder fails with a [`DecodeError`]. [`Decode::decode_all`] (or a
+//! [`TrackedInput`] around any other input) locates it, adding the byte
+//! offset, the expected type and the path to the failing field, such as
+//! `Header.digest.logs[2].Consensus`, so failures in large blobs can be
+//! diagnosed.
+//!
+//! For data whose types are only known at runtime, a [`Registry`] holds
+//! descriptions of types modelled on `scale-info`: composites, variants,
+//! sequences, arrays, tuples, primitives, compacts and bit sequences, each
+//! with a numeric ID. Rust types describe themselves through [`TypeInfo`].
+//! With the default `json` feature, `decode_value` turns SCALE bytes into a
+//! `serde_json::Value` following such a description, `encode_value` goes
+//! the other way, and `Registry::from_json` reads type spec files in the
+//! layout of `scale-info`'s portable registry.
+//!
+//! To study an encoding, `explain` decodes bytes following a registry
+//! type and records what each run of bytes encodes, e.g.
+//! `bytes 0..1 = Vec<u16>: compact length (mode 0b00, value 3)`, and
+//! renders the result as an annotated hex dump or a Markdown table.
+//!
+//! The codec itself only needs `alloc`: with `default-features = false`
+//! the crate builds under `#![no_std]`, e.g. for on-chain or embedded code.
+//! The default `std` feature adds `IoReader`, the `std::error::Error`
+//! impls (so errors convert into `Box<dyn Error>`) and `explain`.
+//!
+//! Enums are encoded as a one-byte variant index followed by the fields of
+//! that variant (example 03), so they implement the traits by hand:
+//!
+//! ```
+//! use scale::{Decode, Encode, DecodeError, Input};
+//!
+//! #[derive(Debug, PartialEq)]
+//! enum MyScaleEnum {
+//!     Foo(u16),
+//!     Baz,
+//! }
+//!
+//! impl Encode for MyScaleEnum {
+//!     fn encode(&self) -> Vec<u8> {
+//!         match self {
+//!             MyScaleEnum::Foo(val) => [vec![0x00], val.encode()].concat(),
+//!             MyScaleEnum::Baz => vec![0x01],
+//!         }
+//!     }
+//! }
+//!
+//! impl Decode for MyScaleEnum {
+//!     fn decode<I: Input>(input: &mut I) -> Result<Self, DecodeError> {
+//!         match u8::decode(input)? {
+//!             0x00 => Ok(MyScaleEnum::Foo(u16::decode(input)?)),
+//!             0x01 => Ok(MyScaleEnum::Baz),
+//!             tag => Err(DecodeError::UnknownVariant { type_name: "MyScaleEnum", tag }),
+//!         }
+//!     }
+//! }
+//!
+//! assert_eq!(MyScaleEnum::Foo(0x1234).encode(), vec![0x00, 0x34, 0x12]);
+//! assert_eq!(MyScaleEnum::decode_all(&[0x01]), Ok(MyScaleEnum::Baz));
+//! ```
+//!
+//! With the default `derive` feature the same impls can be generated with
+//! `#[derive(Encode, Decode)]` (and `MaxEncodedLen` and `TypeInfo`), using
+//! the `#[codec(index = N)]`, `#[codec(compact)]` and `#[codec(skip)]`
+//! attributes:
+//!
+//! ```
+//! # #[cfg(feature = "derive")] {
+//! use scale::{Decode, Encode};
+//!
+//! #[derive(Debug, PartialEq, Encode, Decode)]
+//! enum MyScaleEnum {
+//!     Foo(u16),
+//!     #[codec(index = 2)]
+//!     Baz,
+//! }
+//!
+//! #[derive(Debug, PartialEq, Encode, Decode)]
+//! struct Transfer {
+//!     #[codec(compact)]
+//!     amount: u64,
+//!     kind: MyScaleEnum,
+//! }
+//!
+//! let transfer = Transfer { amount: 1, kind: MyScaleEnum::Baz };
+//! assert_eq!(transfer.encode(), vec![0x04, 0x02]);
+//! assert_eq!(Transfer::decode_all(&[0x04, 0x02]), Ok(transfer));
+//! # }
+//! ```
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+// Lets the derive macros refer to `::scale` from inside this crate.
+extern crate self as scale;
+
+/// The `alloc` items every module uses; with `std` these are the same ones
+/// its prelude brings in.
+mod prelude {
+    pub(crate) use alloc::boxed::Box;
+    pub(crate) use alloc::string::{String, ToString};
+    pub(crate) use alloc::vec::Vec;
+    pub(crate) use alloc::{format, vec};
+}
+
+/// Paths used by the code `scale-derive` generates, so it works in
+/// `no_std` crates too.
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::vec;
+}
+
+mod array;
+mod bitseq;
+mod borrowed;
+mod btree;
+mod codec;
+mod compact;
+mod context;
+mod error;
+#[cfg(feature = "std")]
+mod explain;
+mod hex;
+mod input;
+#[cfg(feature = "json")]
+mod json;
+mod limits;
+mod max_encoded_len;
+mod option;
+mod output;
+mod registry;
+mod string;
+mod tuple;
+mod type_expr;
+mod vector;
+
+pub use bitseq::{BitOrder, BitSeq, BitStore, Lsb0, Msb0};
+pub use borrowed::DecodeBorrowed;
+pub use codec::{Decode, DecodeMode, DecodeWithMode, Encode};
+pub use compact::{compact_len, Compact, CompactBig};
+pub use context::{PathSegment, TrackedInput};
+pub use error::DecodeError;
+#[cfg(feature = "std")]
+pub use explain::{explain, Explanation, Span};
+pub use hex::{from_hex, to_hex};
+pub use input::Input;
+#[cfg(feature = "std")]
+pub use input::IoReader;
+#[cfg(feature = "json")]
+pub use json::{decode_value, decode_value_all, encode_value, encode_value_to, JsonError};
+pub use limits::{DecodeLimits, LimitedInput};
+pub use max_encoded_len::MaxEncodedLen;
+pub use output::{ArrayOutput, HasherOutput, Output};
+pub use registry::{Field, Primitive, Registry, Type, TypeDef, TypeInfo, Variant};
+pub use type_expr::ParseTypeError;
+
+#[cfg(feature = "derive")]
+pub use scale_derive::{Decode, Encode, MaxEncodedLen, TypeInfo};
@@ -0,0 +1,327 @@
+// Bounds on what decoding untrusted input may cost: a length prefix is just
+// a few bytes, but can claim billions of elements.
+
+use crate::borrowed::BorrowInput;
+use crate::{DecodeError, Input, PathSegment};
+
+/// Limits enforced while decoding. [`Decode::decode_all`] and
+/// [`IoReader`](crate::IoReader) apply [`DecodeLimits::default`]; any other
+/// input can be bounded by wrapping it in a [`LimitedInput`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodeLimits {
+    /// Total bytes all decoded collections may claim, summed over the
+    /// whole decode.
+    pub max_allocation: usize,
+    /// Maximum number of elements in any single collection.
+    pub max_collection_len: usize,
+    /// Maximum nesting of collections, options and derived types.
+    pub max_depth: usize,
+}
+
+impl DecodeLimits {
+    /// No limits at all, the behaviour of a bare `&[u8]`. Pass this to
+    /// [`Decode::decode_all_with_limits`] to opt out of the defaults.
+    pub const UNLIMITED: DecodeLimits = DecodeLimits {
+        max_allocation: usize::MAX,
+        max_collection_len: usize::MAX,
+        max_depth: usize::MAX,
+    };
+}
+
+/// Generous enough for blocks and runtime storage values such as Wasm code
+/// blobs: 16 MiB in total and 64 levels of nesting. Collections may have
+/// one element per byte of that, so the count never rejects a byte string
+/// the allocation allows; it stops collections of zero-sized elements,
+/// which allocate nothing, from looping without end.
+impl Default for DecodeLimits {
+    fn default() -> Self {
+        DecodeLimits {
+            max_allocation: DEFAULT_MAX_ALLOCATION,
+            max_collection_len: DEFAULT_MAX_ALLOCATION,
+            max_depth: 64,
+        }
+    }
+}
+
+const DEFAULT_MAX_ALLOCATION: usize = 16 * 1024 * 1024;
+
+/// Wraps an [`Input`] and fails decoding as soon as it would exceed
+/// [`DecodeLimits`].
+///
+/// A collection is checked against the limits as soon as its length
+/// prefix is read, before anything is allocated for it:
+///
+/// ```
+/// use scale::{Decode, DecodeLimits, DecodeError, LimitedInput};
+///
+/// // Five bytes claiming 2^30 elements.
+/// let hostile = [0x03, 0x00, 0x00, 0x00, 0x40];
+/// let mut input = LimitedInput::new(&hostile[..], DecodeLimits::default());
+/// assert!(matches!(
+///     Vec::<u64>::decode(&mut input),
+///     Err(DecodeError::CollectionTooLong { .. })
+/// ));
+/// ```
+pub struct LimitedInput<I> {
+    inner: I,
+    budget: Budget,
+}
+
+impl<I: Input> LimitedInput<I> {
+    pub fn new(inner: I, limits: DecodeLimits) -> Self {
+        LimitedInput {
+            inner,
+            budget: Budget::new(limits),
+        }
+    }
+
+    /// Bytes claimed by collections decoded so far.
+    pub fn allocated(&self) -> usize {
+        self.budget.allocated
+    }
+
+    /// Returns the wrapped input.
+    pub fn into_inner(self) -> I {
+        self.inner
+    }
+}
+
+/// What a single decode has used of its [`DecodeLimits`], shared by the
+/// inputs that enforce them.
+pub(crate) struct Budget {
+    limits: DecodeLimits,
+    allocated: usize,
+    depth: usize,
+}
+
+impl Budget {
+    pub(crate) fn new(limits: DecodeLimits) -> Self {
+        Budget {
+            limits,
+            allocated: 0,
+            depth: 0,
+        }
+    }
+
+    pub(crate) fn on_collection(
+        &mut self,
+        len: usize,
+        alloc_bytes: usize,
+    ) -> Result<(), DecodeError> {
+        if len > self.limits.max_collection_len {
+            return Err(DecodeError::CollectionTooLong {
+                len,
+                max: self.limits.max_collection_len,
+            });
+        }
+        let remaining = self.limits.max_allocation - self.allocated;
+        if alloc_bytes > remaining {
+            return Err(DecodeError::AllocationLimit {
+                requested: alloc_bytes,
+                remaining,
+            });
+        }
+        self.allocated += alloc_bytes;
+        Ok(())
+    }
+
+    pub(crate) fn descend(&mut self) -> Result<(), DecodeError> {
+        if self.depth >= self.limits.max_depth {
+            return Err(DecodeError::DepthLimit(self.limits.max_depth));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    pub(crate) fn ascend(&mut self) {
+        self.depth -= 1;
+    }
+}
+
+impl<I: Input> Input for LimitedInput<I> {
+    fn remaining_len(&mut self) -> Result<Option<usize>, DecodeError> {
+        self.inner.remaining_len()
+    }
+
+    fn read(&mut self, into: &mut [u8]) -> Result<(), DecodeError> {
+        self.inner.read(into)
+    }
+
+    fn read_byte(&mut self) -> Result<u8, DecodeError> {
+        self.inner.read_byte()
+    }
+
+    fn on_collection(&mut self, len: usize, alloc_bytes: usize) -> Result<(), DecodeError> {
+        self.budget.on_collection(len, alloc_bytes)?;
+        self.inner.on_collection(len, alloc_bytes)
+    }
+
+    fn descend(&mut self) -> Result<(), DecodeError> {
+        self.budget.descend()?;
+        // The caller only ascends after a successful descend.
+        self.inner.descend().inspect_err(|_| self.budget.ascend())
+    }
+
+    fn ascend(&mut self) {
+        self.budget.ascend();
+        self.inner.ascend();
+    }
+
+    fn enter(&mut self, segment: PathSegment, expected: &'static str) {
+        self.inner.enter(segment, expected);
+    }
+
+    fn leave(&mut self) {
+        self.inner.leave();
+    }
+}
+
+impl<'a, I: BorrowInput<'a>> BorrowInput<'a> for LimitedInput<I> {
+    fn read_borrowed(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        self.inner.read_borrowed(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Decode, Encode};
+    use std::collections::BTreeMap;
+
+    fn limits(max_allocation: usize, max_collection_len: usize, max_depth: usize) -> DecodeLimits {
+        DecodeLimits {
+            max_allocation,
+            max_collection_len,
+            max_depth,
+        }
+    }
+
+    #[test]
+    fn rejects_length_bomb_before_allocating() {
+        let hostile = [0x03, 0x00, 0x00, 0x00, 0x40];
+        assert_eq!(
+            Vec::<u8>::decode_all_with_limits(&hostile, DecodeLimits::default())
+                .map_err(DecodeError::into_kind),
+            Err(DecodeError::CollectionTooLong {
+                len: 1 << 30,
+                max: 16 * 1024 * 1024
+            })
+        );
+        assert_eq!(
+            String::decode_all_with_limits(&hostile, limits(1024, usize::MAX, 8))
+                .map_err(DecodeError::into_kind),
+            Err(DecodeError::AllocationLimit {
+                requested: 1 << 30,
+                remaining: 1024
+            })
+        );
+    }
+
+    #[test]
+    fn default_limits_fit_large_byte_blobs() {
+        // Runtime Wasm code is a few MiB of `Vec<u8>`.
+        let code = vec![0u8; 4 * 1024 * 1024].encode();
+        assert_eq!(
+            Vec::<u8>::decode_all(&code).map(|code| code.len()),
+            Ok(4 * 1024 * 1024)
+        );
+        let too_big = vec![0u8; 16 * 1024 * 1024 + 1].encode();
+        assert!(matches!(
+            Vec::<u8>::decode_all(&too_big).map_err(DecodeError::into_kind),
+            Err(DecodeError::CollectionTooLong { .. } | DecodeError::AllocationLimit { .. })
+        ));
+    }
+
+    #[test]
+    fn failed_descend_releases_depth() {
+        // The inner input allows less nesting than the outer one.
+        let inner = LimitedInput::new(&[][..], limits(0, 0, 1));
+        let mut input = LimitedInput::new(inner, limits(0, 0, 8));
+        assert_eq!(input.descend(), Ok(()));
+        assert_eq!(input.descend(), Err(DecodeError::DepthLimit(1)));
+        assert_eq!(input.budget.depth, 1);
+    }
+
+    #[test]
+    fn allocation_is_summed_over_the_decode() {
+        let value = vec![vec![0u8; 600], vec![0u8; 600]];
+        let encoded = value.encode();
+        // The outer vector claims 2 * size_of::<Vec<u8>>() bytes as well.
+        let outer = 2 * std::mem::size_of::<Vec<u8>>();
+        assert_eq!(
+            Vec::<Vec<u8>>::decode_all_with_limits(&encoded, limits(outer + 1200, 1000, 8)),
+            Ok(value)
+        );
+        assert_eq!(
+            Vec::<Vec<u8>>::decode_all_with_limits(&encoded, limits(outer + 1000, 1000, 8))
+                .map_err(DecodeError::into_kind),
+            Err(DecodeError::AllocationLimit {
+                requested: 600,
+                remaining: 400
+            })
+        );
+    }
+
+    #[test]
+    fn element_size_counts_towards_allocation() {
+        let encoded = vec![0u64; 10].encode();
+        assert_eq!(
+            Vec::<u64>::decode_all_with_limits(&encoded, limits(79, 100, 8))
+                .map_err(DecodeError::into_kind),
+            Err(DecodeError::AllocationLimit {
+                requested: 80,
+                remaining: 79
+            })
+        );
+        let map = BTreeMap::from([(1u32, 2u32), (3, 4)]);
+        assert_eq!(
+            BTreeMap::<u32, u32>::decode_all_with_limits(&map.encode(), limits(100, 1, 8))
+                .map_err(DecodeError::into_kind),
+            Err(DecodeError::CollectionTooLong { len: 2, max: 1 })
+        );
+    }
+
+    #[test]
+    fn depth_is_bounded() {
+        let value: Vec<Vec<Vec<u8>>> = vec![vec![vec![1]]];
+        let encoded = value.encode();
+        assert_eq!(
+            Vec::<Vec<Vec<u8>>>::decode_all_with_limits(&encoded, limits(1024, 16, 3)),
+            Ok(value)
+        );
+        assert_eq!(
+            Vec::<Vec<Vec<u8>>>::decode_all_with_limits(&encoded, limits(1024, 16, 2))
+                .map_err(DecodeError::into_kind),
+            Err(DecodeError::DepthLimit(2))
+        );
+
+        let nested = Some(Some(Some(1u8)));
+        assert_eq!(
+            Option::<Option<Option<u8>>>::decode_all_with_limits(&nested.encode(), limits(0, 0, 2))
+                .map_err(DecodeError::into_kind),
+            Err(DecodeError::DepthLimit(2))
+        );
+    }
+
+    #[test]
+    fn depth_is_restored_between_siblings() {
+        // Many siblings at the same level must not add up.
+        let value = vec![Some(1u8); 100];
+        assert_eq!(
+            Vec::<Option<u8>>::decode_all_with_limits(&value.encode(), limits(1024, 100, 2)),
+            Ok(value)
+        );
+    }
+
+    #[test]
+    fn unlimited_matches_plain_decode() {
+        let value = vec![(String::from("a"), vec![1u16, 2])];
+        assert_eq!(
+            Vec::<(String, Vec<u16>)>::decode_all_with_limits(
+                &value.encode(),
+                DecodeLimits::UNLIMITED
+            ),
+            Ok(value)
+        );
+    }
+}
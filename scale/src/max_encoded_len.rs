@@ -0,0 +1,136 @@
+// Upper bounds on encoded sizes, for types whose encoding cannot grow
+// without limit: integers, compacts, fixed arrays, tuples, and structs and
+// enums built from them. Vectors, strings and maps have no such bound.
+
+use crate::compact::compact_len;
+use crate::{Compact, Encode};
+
+/// Types whose encoding never exceeds a size known from the type alone.
+///
+/// ```
+/// use scale::{Compact, MaxEncodedLen};
+///
+/// assert_eq!(<[u8; 32]>::max_encoded_len(), 32);
+/// assert_eq!(Option::<(u32, bool)>::max_encoded_len(), 6);
+/// assert_eq!(Compact::<u64>::max_encoded_len(), 9);
+/// ```
+pub trait MaxEncodedLen: Encode {
+    /// The largest number of bytes any value of this type encodes to.
+    fn max_encoded_len() -> usize;
+}
+
+macro_rules! impl_fixed_width {
+    ($($t:ty),*) => {
+        $(
+            impl MaxEncodedLen for $t {
+                fn max_encoded_len() -> usize {
+                    core::mem::size_of::<$t>()
+                }
+            }
+        )*
+    };
+}
+
+impl_fixed_width!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+impl MaxEncodedLen for bool {
+    fn max_encoded_len() -> usize {
+        1
+    }
+}
+
+macro_rules! impl_compact {
+    ($($t:ty),*) => {
+        $(
+            impl MaxEncodedLen for Compact<$t> {
+                fn max_encoded_len() -> usize {
+                    compact_len(<$t>::MAX as u128)
+                }
+            }
+        )*
+    };
+}
+
+impl_compact!(u8, u16, u32, u64, u128);
+
+impl<T: MaxEncodedLen, const N: usize> MaxEncodedLen for [T; N] {
+    fn max_encoded_len() -> usize {
+        T::max_encoded_len().saturating_mul(N)
+    }
+}
+
+// `Option<bool>` needs only one byte, but the generic bound is still exact
+// for every other `T` and never too small.
+impl<T: MaxEncodedLen> MaxEncodedLen for Option<T> {
+    fn max_encoded_len() -> usize {
+        1 + T::max_encoded_len()
+    }
+}
+
+impl<T: MaxEncodedLen, E: MaxEncodedLen> MaxEncodedLen for Result<T, E> {
+    fn max_encoded_len() -> usize {
+        1 + T::max_encoded_len().max(E::max_encoded_len())
+    }
+}
+
+macro_rules! impl_tuple {
+    ($($name:ident),*) => {
+        impl<$($name: MaxEncodedLen),*> MaxEncodedLen for ($($name,)*) {
+            fn max_encoded_len() -> usize {
+                0 $(+ $name::max_encoded_len())*
+            }
+        }
+    };
+}
+
+impl_tuple!();
+impl_tuple!(A);
+impl_tuple!(A, B);
+impl_tuple!(A, B, C);
+impl_tuple!(A, B, C, D);
+impl_tuple!(A, B, C, D, E);
+impl_tuple!(A, B, C, D, E, F);
+impl_tuple!(A, B, C, D, E, F, G);
+impl_tuple!(A, B, C, D, E, F, G, H);
+impl_tuple!(A, B, C, D, E, F, G, H, J);
+impl_tuple!(A, B, C, D, E, F, G, H, J, K);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounds_match_largest_values() {
+        assert_eq!(u128::MAX.encoded_size(), u128::max_encoded_len());
+        assert_eq!([u16::MAX; 4].encode().len(), <[u16; 4]>::max_encoded_len());
+        assert_eq!(
+            Compact(u32::MAX).encode().len(),
+            Compact::<u32>::max_encoded_len()
+        );
+        assert_eq!(
+            Compact(u128::MAX).encode().len(),
+            Compact::<u128>::max_encoded_len()
+        );
+        assert_eq!(
+            Some((1u8, 2u64)).encode().len(),
+            Option::<(u8, u64)>::max_encoded_len()
+        );
+        assert_eq!(Result::<u8, u32>::max_encoded_len(), 5);
+        let widest = (
+            1u8,
+            2u16,
+            3u32,
+            4u64,
+            5u128,
+            true,
+            7i8,
+            [8u8; 2],
+            (),
+            Compact(u64::MAX),
+        );
+        assert_eq!(
+            widest.encode().len(),
+            <(u8, u16, u32, u64, u128, bool, i8, [u8; 2], (), Compact<u64>)>::max_encoded_len()
+        );
+    }
+}
@@ -0,0 +1,109 @@
+// SCALE encoding for Option and Result: a one-byte tag followed by the
+// encoded value, if any.
+//
+// - Option<T>: 0x00 = None, 0x01 = Some(T)
+// - Option<bool>: a single byte, 0x00 = None, 0x01 = Some(true),
+//   0x02 = Some(false)
+// - Result<T, E>: 0x00 = Ok(T), 0x01 = Err(E)
+
+use crate::input::nested;
+use crate::{Decode, DecodeError, Encode, Input, Output};
+
+impl<T: Encode> Encode for Option<T> {
+    fn encode_to<O: Output + ?Sized>(&self, dest: &mut O) {
+        T::encode_option(self.as_ref(), dest);
+    }
+
+    fn encoded_size(&self) -> usize {
+        T::encoded_size_option(self.as_ref())
+    }
+}
+
+impl<T: Decode> Decode for Option<T> {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, DecodeError> {
+        nested(input, T::decode_option)
+    }
+}
+
+impl<T: Encode, E: Encode> Encode for Result<T, E> {
+    fn encode_to<O: Output + ?Sized>(&self, dest: &mut O) {
+        match self {
+            Ok(value) => {
+                dest.push_byte(0x00);
+                value.encode_to(dest);
+            }
+            Err(error) => {
+                dest.push_byte(0x01);
+                error.encode_to(dest);
+            }
+        }
+    }
+
+    fn encoded_size(&self) -> usize {
+        1 + match self {
+            Ok(value) => value.encoded_size(),
+            Err(error) => error.encoded_size(),
+        }
+    }
+}
+
+impl<T: Decode, E: Decode> Decode for Result<T, E> {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, DecodeError> {
+        match input.read_byte()? {
+            0x00 => nested(input, T::decode).map(Ok),
+            0x01 => nested(input, E::decode).map(Err),
+            tag => Err(DecodeError::UnknownVariant {
+                type_name: "Result",
+                tag,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Compact;
+
+    #[test]
+    fn option() {
+        assert_eq!(None::<u16>.encode(), vec![0x00]);
+        assert_eq!(Some(0x1234u16).encode(), vec![0x01, 0x34, 0x12]);
+        assert_eq!(Some(Compact(1u64)).encode(), vec![0x01, 0x04]);
+        assert_eq!(
+            Option::<u16>::decode_all(&[0x01, 0x34, 0x12]),
+            Ok(Some(0x1234))
+        );
+        assert_eq!(Option::<u16>::decode_all(&[0x00]), Ok(None));
+        assert_eq!(
+            Option::<u16>::decode_all(&[0x02]).map_err(DecodeError::into_kind),
+            Err(DecodeError::UnknownVariant {
+                type_name: "Option",
+                tag: 0x02
+            })
+        );
+    }
+
+    #[test]
+    fn option_bool_is_one_byte() {
+        let cases = [(None, 0x00), (Some(true), 0x01), (Some(false), 0x02)];
+        for (value, byte) in cases {
+            assert_eq!(value.encode(), vec![byte]);
+            assert_eq!(Option::<bool>::decode_all(&[byte]), Ok(value));
+        }
+        assert!(Option::<bool>::decode_all(&[0x03]).is_err());
+        // Only a direct Option<bool> is packed.
+        assert_eq!(Some(Some(true)).encode(), vec![0x01, 0x01]);
+    }
+
+    #[test]
+    fn result() {
+        let ok: Result<u8, bool> = Ok(42);
+        let err: Result<u8, bool> = Err(false);
+        assert_eq!(ok.encode(), vec![0x00, 0x2a]);
+        assert_eq!(err.encode(), vec![0x01, 0x00]);
+        assert_eq!(Result::<u8, bool>::decode_all(&[0x00, 0x2a]), Ok(ok));
+        assert_eq!(Result::<u8, bool>::decode_all(&[0x01, 0x00]), Ok(err));
+        assert!(Result::<u8, bool>::decode_all(&[0x02]).is_err());
+    }
+}
@@ -0,0 +1,154 @@
+// Destinations for the encoders: growable vectors, fixed stack buffers and
+// hashers, so values can be encoded without an intermediate `Vec` per field.
+
+use core::hash::Hasher;
+
+use crate::prelude::*;
+
+/// A sink that SCALE bytes are appended to.
+pub trait Output {
+    /// Appends `bytes`.
+    fn write(&mut self, bytes: &[u8]);
+
+    /// Appends a single byte.
+    fn push_byte(&mut self, byte: u8) {
+        self.write(&[byte]);
+    }
+}
+
+impl Output for Vec<u8> {
+    fn write(&mut self, bytes: &[u8]) {
+        self.extend_from_slice(bytes);
+    }
+
+    fn push_byte(&mut self, byte: u8) {
+        self.push(byte);
+    }
+}
+
+/// A fixed-capacity buffer on the stack, for encoding small values without
+/// touching the heap.
+///
+/// Size it with [`MaxEncodedLen`](crate::MaxEncodedLen) or
+/// [`Encode::encoded_size`](crate::Encode::encoded_size); writing past the
+/// capacity panics.
+///
+/// ```
+/// use scale::{ArrayOutput, Compact, Encode};
+///
+/// let mut buf = ArrayOutput::<16>::new();
+/// (Compact(1u32), true).encode_to(&mut buf);
+/// assert_eq!(buf.as_slice(), &[0x04, 0x01]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ArrayOutput<const N: usize> {
+    buf: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> ArrayOutput<N> {
+    pub fn new() -> Self {
+        ArrayOutput {
+            buf: [0; N],
+            len: 0,
+        }
+    }
+
+    /// The bytes written so far.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Forgets the bytes written so far.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+impl<const N: usize> Default for ArrayOutput<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Output for ArrayOutput<N> {
+    fn write(&mut self, bytes: &[u8]) {
+        let end = self.len + bytes.len();
+        assert!(
+            end <= N,
+            "ArrayOutput of {} bytes cannot hold {} more after {}",
+            N,
+            bytes.len(),
+            self.len
+        );
+        self.buf[self.len..end].copy_from_slice(bytes);
+        self.len = end;
+    }
+}
+
+/// Feeds encoded bytes straight into a [`Hasher`], so a value can be hashed
+/// without first being encoded into a vector.
+///
+/// Cryptographic hashers (Blake2, Keccak) do not implement `Hasher`; give
+/// them the same treatment by implementing [`Output`] on a small wrapper
+/// that forwards to their `update` method.
+///
+/// ```
+/// use std::collections::hash_map::DefaultHasher;
+/// use std::hash::Hasher;
+/// use scale::{Encode, HasherOutput};
+///
+/// let mut streamed = HasherOutput(DefaultHasher::new());
+/// vec![1u16, 2, 3].encode_to(&mut streamed);
+///
+/// let mut buffered = DefaultHasher::new();
+/// buffered.write(&vec![1u16, 2, 3].encode());
+/// assert_eq!(streamed.0.finish(), buffered.finish());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct HasherOutput<H>(pub H);
+
+impl<H: Hasher> Output for HasherOutput<H> {
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.write(bytes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Compact, Encode, MaxEncodedLen};
+
+    #[test]
+    fn appends_to_existing_vector() {
+        let mut dest = vec![0xaa];
+        vec![Compact(1u64), Compact(1 << 20)].encode_to(&mut dest);
+        assert_eq!(dest, vec![0xaa, 0x08, 0x04, 0x02, 0x00, 0x40, 0x00]);
+    }
+
+    #[test]
+    fn stack_buffer_sized_by_max_encoded_len() {
+        let value = (u32::MAX, Some(Compact(u64::MAX)), [7u8; 3]);
+        let mut buf = ArrayOutput::<20>::new();
+        assert!(<(u32, Option<Compact<u64>>, [u8; 3])>::max_encoded_len() <= 20);
+        value.encode_to(&mut buf);
+        assert_eq!(buf.as_slice(), value.encode().as_slice());
+        buf.clear();
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot hold")]
+    fn stack_buffer_overflow_panics() {
+        let mut buf = ArrayOutput::<3>::new();
+        0u32.encode_to(&mut buf);
+    }
+}
@@ -0,0 +1,611 @@
+// Runtime descriptions of SCALE types, modelled on `scale-info`: each type
+// gets a numeric ID in a [`Registry`] and refers to the types it contains by
+// ID, so shared and recursive types are described once. Used to decode data
+// whose types are only known at runtime.
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use core::any::TypeId;
+
+use crate::prelude::*;
+use crate::{BitOrder, BitSeq, BitStore, Compact, DecodeError, Lsb0, Msb0};
+
+/// A type: its Rust path (empty for built-ins such as `Vec<T>`) and how it
+/// is encoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Type {
+    /// Module path segments followed by the type name, e.g.
+    /// `["header", "DigestItem"]`.
+    pub path: Vec<String>,
+    pub def: TypeDef,
+}
+
+impl Type {
+    /// A built-in type, with an empty path.
+    pub fn new(def: TypeDef) -> Self {
+        Type {
+            path: Vec::new(),
+            def,
+        }
+    }
+
+    /// A named type such as a struct or enum.
+    pub fn with_path<S: Into<String>>(path: impl IntoIterator<Item = S>, def: TypeDef) -> Self {
+        Type {
+            path: path.into_iter().map(Into::into).collect(),
+            def,
+        }
+    }
+}
+
+/// How a type is laid out in SCALE, with contained types given by ID.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypeDef {
+    /// A struct: its fields in order.
+    Composite(Vec<Field>),
+    /// An enum: a one-byte index followed by the fields of that variant.
+    Variant(Vec<Variant>),
+    /// A compact length followed by that many elements.
+    Sequence(u32),
+    /// A fixed number of elements, with no length prefix.
+    Array {
+        len: u32,
+        element: u32,
+    },
+    /// The element types in order; `()` is the empty tuple.
+    Tuple(Vec<u32>),
+    Primitive(Primitive),
+    /// An unsigned integer type in the compact encoding.
+    Compact(u32),
+    /// A compact bit count followed by `store` words. `order` is a type
+    /// whose path ends in `Lsb0` or `Msb0`, as in `bitvec`.
+    BitSequence {
+        store: u32,
+        order: u32,
+    },
+}
+
+/// A field of a struct or variant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Field {
+    /// `None` for tuple structs and variants.
+    pub name: Option<String>,
+    pub ty: u32,
+}
+
+impl Field {
+    pub fn named(name: impl Into<String>, ty: u32) -> Self {
+        Field {
+            name: Some(name.into()),
+            ty,
+        }
+    }
+
+    pub fn unnamed(ty: u32) -> Self {
+        Field { name: None, ty }
+    }
+}
+
+/// An enum variant, encoded as `index` followed by its fields.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Variant {
+    pub name: String,
+    pub index: u8,
+    pub fields: Vec<Field>,
+}
+
+impl Variant {
+    pub fn new(name: impl Into<String>, index: u8, fields: Vec<Field>) -> Self {
+        Variant {
+            name: name.into(),
+            index,
+            fields,
+        }
+    }
+}
+
+/// Types encoded directly rather than built from other types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Primitive {
+    Bool,
+    /// A UTF-8 string, encoded like `Vec<u8>`.
+    Str,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+}
+
+impl Primitive {
+    /// The Rust name of the type, e.g. `u32` or `String`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Primitive::Bool => "bool",
+            Primitive::Str => "String",
+            Primitive::U8 => "u8",
+            Primitive::U16 => "u16",
+            Primitive::U32 => "u32",
+            Primitive::U64 => "u64",
+            Primitive::U128 => "u128",
+            Primitive::I8 => "i8",
+            Primitive::I16 => "i16",
+            Primitive::I32 => "i32",
+            Primitive::I64 => "i64",
+            Primitive::I128 => "i128",
+        }
+    }
+
+    /// The largest value of an unsigned type, as compacts are bounded by.
+    #[cfg(feature = "std")]
+    pub(crate) fn max_unsigned(self) -> u128 {
+        match self {
+            Primitive::U8 => u8::MAX.into(),
+            Primitive::U16 => u16::MAX.into(),
+            Primitive::U32 => u32::MAX.into(),
+            Primitive::U64 => u64::MAX.into(),
+            _ => u128::MAX,
+        }
+    }
+}
+
+/// A table of type descriptions, indexed by type ID.
+///
+/// Rust types are added with [`Registry::register`], which also registers
+/// every type they contain; types only known at runtime are added with
+/// [`Registry::add`].
+///
+/// ```
+/// use scale::{Compact, Primitive, Registry, TypeDef};
+///
+/// let mut registry = Registry::new();
+/// let id = registry.register::<Vec<Compact<u64>>>();
+/// assert_eq!(registry.type_name(id), "Vec<Compact<u64>>");
+///
+/// let TypeDef::Sequence(element) = registry.resolve(id).unwrap().def else { panic!() };
+/// let TypeDef::Compact(inner) = registry.resolve(element).unwrap().def else { panic!() };
+/// assert_eq!(registry.resolve(inner).unwrap().def, TypeDef::Primitive(Primitive::U64));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Registry {
+    types: Vec<Type>,
+    // A `BTreeMap` rather than a `HashMap`, which `alloc` lacks.
+    ids: BTreeMap<TypeId, u32>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the ID of `T`, describing it (and the types it contains) on
+    /// first use.
+    pub fn register<T: TypeInfo + ?Sized>(&mut self) -> u32 {
+        if let Some(&id) = self.ids.get(&TypeId::of::<T>()) {
+            return id;
+        }
+        // Reserve the ID first so recursive types can refer to themselves.
+        let id = self.add(Type::new(TypeDef::Tuple(Vec::new())));
+        self.ids.insert(TypeId::of::<T>(), id);
+        self.types[id as usize] = T::type_info(self);
+        id
+    }
+
+    /// Adds a type built at runtime and returns its ID. Unlike
+    /// [`Registry::register`], equal types are not merged.
+    pub fn add(&mut self, ty: Type) -> u32 {
+        let id = u32::try_from(self.types.len()).expect("more than u32::MAX types");
+        self.types.push(ty);
+        id
+    }
+
+    /// The type with ID `id`, if any.
+    pub fn resolve(&self, id: u32) -> Option<&Type> {
+        self.types.get(id as usize)
+    }
+
+    /// All types with their IDs, in ID order.
+    pub fn types(&self) -> impl Iterator<Item = (u32, &Type)> {
+        self.types
+            .iter()
+            .enumerate()
+            .map(|(id, ty)| (id as u32, ty))
+    }
+
+    pub fn len(&self) -> usize {
+        self.types.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.types.is_empty()
+    }
+
+    /// The ID of the first type named `name`, either by its last path
+    /// segment (`Header`), its full path (`header::Header`) or, for
+    /// built-ins, its [`Registry::type_name`] (`Vec<u8>`).
+    pub fn find(&self, name: &str) -> Option<u32> {
+        self.types()
+            .find(|(id, ty)| ty.path.join("::") == name || self.type_name(*id) == name)
+            .map(|(id, _)| id)
+    }
+
+    /// A readable name for type `id`: the last path segment of named
+    /// types, and Rust syntax such as `Vec<u8>`, `[u16; 4]` or
+    /// `(u8, bool)` for the others. A built-in that contains itself is
+    /// named `<recursive type N>` where it recurs, and one nested too
+    /// deeply or spelling out too many types is cut short as `<type N>`.
+    pub fn type_name(&self, id: u32) -> String {
+        let mut parts = MAX_NAME_PARTS;
+        self.name_within(id, &mut Vec::new(), &mut parts)
+    }
+
+    /// [`Registry::type_name`] inside the built-ins `enclosing`, with
+    /// `parts` more types left to spell out.
+    fn name_within(&self, id: u32, enclosing: &mut Vec<u32>, parts: &mut usize) -> String {
+        let Some(ty) = self.resolve(id) else {
+            return format!("<unknown type {}>", id);
+        };
+        if let Some(name) = ty.path.last() {
+            return name.clone();
+        }
+        if enclosing.contains(&id) {
+            return format!("<recursive type {}>", id);
+        }
+        if enclosing.len() >= MAX_NAME_DEPTH || *parts == 0 {
+            return format!("<type {}>", id);
+        }
+        *parts -= 1;
+        enclosing.push(id);
+        let name = match &ty.def {
+            TypeDef::Primitive(primitive) => primitive.name().to_string(),
+            TypeDef::Sequence(element) => {
+                format!("Vec<{}>", self.name_within(*element, enclosing, parts))
+            }
+            TypeDef::Array { len, element } => format!(
+                "[{}; {}]",
+                self.name_within(*element, enclosing, parts),
+                len
+            ),
+            TypeDef::Tuple(elements) if elements.len() == 1 => {
+                format!("({},)", self.name_within(elements[0], enclosing, parts))
+            }
+            TypeDef::Tuple(elements) => {
+                let names: Vec<_> = elements
+                    .iter()
+                    .map(|&e| self.name_within(e, enclosing, parts))
+                    .collect();
+                format!("({})", names.join(", "))
+            }
+            TypeDef::Compact(inner) => {
+                format!("Compact<{}>", self.name_within(*inner, enclosing, parts))
+            }
+            TypeDef::BitSequence { store, order } => format!(
+                "BitSeq<{}, {}>",
+                self.name_within(*store, enclosing, parts),
+                self.name_within(*order, enclosing, parts)
+            ),
+            TypeDef::Composite(_) | TypeDef::Variant(_) => format!("<anonymous type {}>", id),
+        };
+        enclosing.pop();
+        name
+    }
+}
+
+/// How deeply [`Registry::type_name`] nests built-in types, and how many
+/// it spells out in all: specs loaded at runtime may nest without end or
+/// share a type so that its name doubles at each level.
+const MAX_NAME_DEPTH: usize = 64;
+const MAX_NAME_PARTS: usize = 1024;
+
+// Lookups shared by the registry-driven decoders.
+impl Registry {
+    /// [`Registry::resolve`], failing with [`DecodeError::UnknownTypeId`].
+    pub(crate) fn get(&self, id: u32) -> Result<&Type, DecodeError> {
+        self.resolve(id).ok_or(DecodeError::UnknownTypeId(id))
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn is_u8(&self, ty: u32) -> bool {
+        matches!(
+            self.resolve(ty).map(|ty| &ty.def),
+            Some(TypeDef::Primitive(Primitive::U8))
+        )
+    }
+
+    /// The unsigned integer type a compact wraps.
+    pub(crate) fn compact_primitive(&self, inner: u32) -> Result<Primitive, DecodeError> {
+        match self.get(inner)?.def {
+            TypeDef::Primitive(
+                primitive @ (Primitive::U8
+                | Primitive::U16
+                | Primitive::U32
+                | Primitive::U64
+                | Primitive::U128),
+            ) => Ok(primitive),
+            _ => Err(DecodeError::UnsupportedType {
+                id: inner,
+                reason: "compact of a type other than an unsigned integer",
+            }),
+        }
+    }
+
+    pub(crate) fn store_primitive(&self, store: u32) -> Result<Primitive, DecodeError> {
+        match self.get(store)?.def {
+            TypeDef::Primitive(
+                primitive @ (Primitive::U8 | Primitive::U16 | Primitive::U32 | Primitive::U64),
+            ) => Ok(primitive),
+            _ => Err(DecodeError::UnsupportedType {
+                id: store,
+                reason: "bit store other than u8, u16, u32 or u64",
+            }),
+        }
+    }
+
+    pub(crate) fn is_msb0(&self, order: u32) -> Result<bool, DecodeError> {
+        match self.get(order)?.path.last().map(String::as_str) {
+            Some("Lsb0") => Ok(false),
+            Some("Msb0") => Ok(true),
+            _ => Err(DecodeError::UnsupportedType {
+                id: order,
+                reason: "bit order other than Lsb0 or Msb0",
+            }),
+        }
+    }
+}
+
+/// Types that can describe themselves in a [`Registry`].
+///
+/// Implemented for every type the crate encodes and, with the `derive`
+/// feature, by `#[derive(TypeInfo)]`, which follows the same
+/// `#[codec(...)]` attributes as the codec derives.
+pub trait TypeInfo: 'static {
+    /// Describes `Self`, registering the types it refers to in `registry`.
+    fn type_info(registry: &mut Registry) -> Type;
+
+    /// Describes `Option<Self>`.
+    #[doc(hidden)]
+    fn option_type_info(registry: &mut Registry) -> Type
+    where
+        Self: Sized,
+    {
+        option_type(registry.register::<Self>())
+    }
+}
+
+// Shapes of the generic standard types, shared with type expressions such as
+// `Option<(u8, bool)>` that are only known at runtime.
+
+pub(crate) fn option_type(some: u32) -> Type {
+    Type::with_path(
+        ["Option"],
+        TypeDef::Variant(vec![
+            Variant::new("None", 0, Vec::new()),
+            Variant::new("Some", 1, vec![Field::unnamed(some)]),
+        ]),
+    )
+}
+
+pub(crate) fn result_type(ok: u32, err: u32) -> Type {
+    Type::with_path(
+        ["Result"],
+        TypeDef::Variant(vec![
+            Variant::new("Ok", 0, vec![Field::unnamed(ok)]),
+            Variant::new("Err", 1, vec![Field::unnamed(err)]),
+        ]),
+    )
+}
+
+// Maps and sets are encoded like a vector of their (sorted) entries.
+pub(crate) fn btree_type(name: &str, entries: u32) -> Type {
+    Type::with_path([name], TypeDef::Composite(vec![Field::unnamed(entries)]))
+}
+
+macro_rules! impl_primitive {
+    ($($t:ty => $primitive:ident),*) => {
+        $(
+            impl TypeInfo for $t {
+                fn type_info(_registry: &mut Registry) -> Type {
+                    Type::new(TypeDef::Primitive(Primitive::$primitive))
+                }
+            }
+        )*
+    };
+}
+
+impl_primitive!(
+    u8 => U8, u16 => U16, u32 => U32, u64 => U64, u128 => U128,
+    i8 => I8, i16 => I16, i32 => I32, i64 => I64, i128 => I128,
+    str => Str, String => Str
+);
+
+impl TypeInfo for bool {
+    fn type_info(_registry: &mut Registry) -> Type {
+        Type::new(TypeDef::Primitive(Primitive::Bool))
+    }
+
+    // `Option<bool>` is a single byte: 0 for `None`, 1 for `Some(true)` and
+    // 2 for `Some(false)`, as parity's `OptionBool`.
+    fn option_type_info(_registry: &mut Registry) -> Type {
+        Type::with_path(
+            ["OptionBool"],
+            TypeDef::Variant(vec![
+                Variant::new("None", 0, Vec::new()),
+                Variant::new("True", 1, Vec::new()),
+                Variant::new("False", 2, Vec::new()),
+            ]),
+        )
+    }
+}
+
+impl<T: TypeInfo> TypeInfo for Compact<T> {
+    fn type_info(registry: &mut Registry) -> Type {
+        Type::new(TypeDef::Compact(registry.register::<T>()))
+    }
+}
+
+impl<T: TypeInfo> TypeInfo for Vec<T> {
+    fn type_info(registry: &mut Registry) -> Type {
+        Type::new(TypeDef::Sequence(registry.register::<T>()))
+    }
+}
+
+impl<T: TypeInfo> TypeInfo for [T] {
+    fn type_info(registry: &mut Registry) -> Type {
+        Type::new(TypeDef::Sequence(registry.register::<T>()))
+    }
+}
+
+impl<T: TypeInfo, const N: usize> TypeInfo for [T; N] {
+    fn type_info(registry: &mut Registry) -> Type {
+        Type::new(TypeDef::Array {
+            len: u32::try_from(N).expect("array longer than u32::MAX"),
+            element: registry.register::<T>(),
+        })
+    }
+}
+
+impl<T: TypeInfo> TypeInfo for Option<T> {
+    fn type_info(registry: &mut Registry) -> Type {
+        T::option_type_info(registry)
+    }
+}
+
+impl<T: TypeInfo, E: TypeInfo> TypeInfo for Result<T, E> {
+    fn type_info(registry: &mut Registry) -> Type {
+        let ok = registry.register::<T>();
+        let err = registry.register::<E>();
+        result_type(ok, err)
+    }
+}
+
+impl<K: TypeInfo, V: TypeInfo> TypeInfo for BTreeMap<K, V> {
+    fn type_info(registry: &mut Registry) -> Type {
+        btree_type("BTreeMap", registry.register::<Vec<(K, V)>>())
+    }
+}
+
+impl<T: TypeInfo> TypeInfo for BTreeSet<T> {
+    fn type_info(registry: &mut Registry) -> Type {
+        btree_type("BTreeSet", registry.register::<Vec<T>>())
+    }
+}
+
+impl TypeInfo for Lsb0 {
+    fn type_info(_registry: &mut Registry) -> Type {
+        Type::with_path(["bitvec", "order", "Lsb0"], TypeDef::Composite(Vec::new()))
+    }
+}
+
+impl TypeInfo for Msb0 {
+    fn type_info(_registry: &mut Registry) -> Type {
+        Type::with_path(["bitvec", "order", "Msb0"], TypeDef::Composite(Vec::new()))
+    }
+}
+
+impl<S: BitStore + TypeInfo, O: BitOrder + TypeInfo> TypeInfo for BitSeq<S, O> {
+    fn type_info(registry: &mut Registry) -> Type {
+        Type::new(TypeDef::BitSequence {
+            store: registry.register::<S>(),
+            order: registry.register::<O>(),
+        })
+    }
+}
+
+macro_rules! impl_tuple {
+    ($($name:ident),*) => {
+        impl<$($name: TypeInfo),*> TypeInfo for ($($name,)*) {
+            #[allow(unused_variables)]
+            fn type_info(registry: &mut Registry) -> Type {
+                Type::new(TypeDef::Tuple(vec![$(registry.register::<$name>()),*]))
+            }
+        }
+    };
+}
+
+impl_tuple!();
+impl_tuple!(A);
+impl_tuple!(A, B);
+impl_tuple!(A, B, C);
+impl_tuple!(A, B, C, D);
+impl_tuple!(A, B, C, D, E);
+impl_tuple!(A, B, C, D, E, F);
+impl_tuple!(A, B, C, D, E, F, G);
+impl_tuple!(A, B, C, D, E, F, G, H);
+impl_tuple!(A, B, C, D, E, F, G, H, J);
+impl_tuple!(A, B, C, D, E, F, G, H, J, K);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shared_types_are_registered_once() {
+        let mut registry = Registry::new();
+        let pair = registry.register::<(Vec<u8>, [u8; 4])>();
+        let bytes = registry.register::<Vec<u8>>();
+        let word = registry.register::<[u8; 4]>();
+        assert_eq!(registry.register::<(Vec<u8>, [u8; 4])>(), pair);
+        assert_eq!(
+            registry.resolve(pair).unwrap().def,
+            TypeDef::Tuple(vec![bytes, word])
+        );
+        // u8, Vec<u8>, [u8; 4] and the tuple.
+        assert_eq!(registry.len(), 4);
+    }
+
+    #[test]
+    fn type_names() {
+        let mut registry = Registry::new();
+        let id = registry.register::<Option<(u8, bool)>>();
+        assert_eq!(registry.type_name(id), "Option");
+        let id = registry.register::<[Compact<u32>; 2]>();
+        assert_eq!(registry.type_name(id), "[Compact<u32>; 2]");
+        let id = registry.register::<(String,)>();
+        assert_eq!(registry.type_name(id), "(String,)");
+        let id = registry.register::<BitSeq<u32, Msb0>>();
+        assert_eq!(registry.type_name(id), "BitSeq<u32, Msb0>");
+        assert_eq!(registry.find("BitSeq<u32, Msb0>"), Some(id));
+        assert_eq!(registry.find("bitvec::order::Msb0"), registry.find("Msb0"));
+        assert_eq!(registry.find("Vec<u32>"), None);
+        assert_eq!(registry.type_name(1000), "<unknown type 1000>");
+    }
+
+    #[test]
+    fn self_referencing_type_names() {
+        let mut registry = Registry::new();
+        let sequence = registry.add(Type::new(TypeDef::Sequence(0)));
+        assert_eq!(registry.type_name(sequence), "Vec<<recursive type 0>>");
+        let byte = registry.add(Type::new(TypeDef::Primitive(Primitive::U8)));
+        let tuple = registry.add(Type::new(TypeDef::Tuple(vec![byte, 2])));
+        assert_eq!(registry.type_name(tuple), "(u8, <recursive type 2>)");
+        assert_eq!(registry.find("Loop"), None);
+        assert_eq!(registry.find("u8"), Some(byte));
+
+        // Acyclic but deep, and doubling at each level.
+        let mut inner = byte;
+        for _ in 0..10_000 {
+            inner = registry.add(Type::new(TypeDef::Tuple(vec![inner, inner])));
+        }
+        let name = registry.type_name(inner);
+        assert!(name.contains("<type ") && name.len() < 100_000);
+    }
+
+    #[test]
+    fn option_bool_is_single_byte_variant() {
+        let mut registry = Registry::new();
+        let id = registry.register::<Option<bool>>();
+        let TypeDef::Variant(variants) = &registry.resolve(id).unwrap().def else {
+            panic!("not a variant");
+        };
+        let indexes: Vec<_> = variants
+            .iter()
+            .map(|v| (v.name.as_str(), v.index))
+            .collect();
+        assert_eq!(indexes, [("None", 0), ("True", 1), ("False", 2)]);
+        assert!(variants.iter().all(|v| v.fields.is_empty()));
+    }
+}
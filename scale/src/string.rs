@@ -0,0 +1,61 @@
+// SCALE encoding for strings: the same as Vec<u8> (a compact length
+// followed by the bytes), with the bytes required to be valid UTF-8.
+
+use crate::compact::{compact_len, encode_compact};
+use crate::input::read_vec;
+use crate::prelude::*;
+use crate::vector::decode_len;
+use crate::{Decode, DecodeError, Encode, Input, Output};
+
+impl Encode for str {
+    fn encode_to<O: Output + ?Sized>(&self, dest: &mut O) {
+        encode_compact(self.len() as u128, dest);
+        dest.write(self.as_bytes());
+    }
+
+    fn encoded_size(&self) -> usize {
+        compact_len(self.len() as u128) + self.len()
+    }
+}
+
+impl Encode for String {
+    fn encode_to<O: Output + ?Sized>(&self, dest: &mut O) {
+        self.as_str().encode_to(dest);
+    }
+
+    fn encoded_size(&self) -> usize {
+        self.as_str().encoded_size()
+    }
+}
+
+impl Decode for String {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, DecodeError> {
+        let len = decode_len(input)?;
+        input.on_collection(len, len)?;
+        let bytes = read_vec(input, len)?;
+        String::from_utf8(bytes).map_err(|e| DecodeError::InvalidUtf8 {
+            valid_up_to: e.utf8_error().valid_up_to(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn string_round_trip() {
+        assert_eq!("".encode(), vec![0x00]);
+        assert_eq!("abc".encode(), vec![0x0c, b'a', b'b', b'c']);
+        let value = String::from("Polkadot ●");
+        assert_eq!(String::decode_all(&value.encode()), Ok(value));
+    }
+
+    #[test]
+    fn string_rejects_invalid_utf8() {
+        assert_eq!(
+            String::decode_all(&[0x0c, b'a', b'b', 0xc3]).map_err(DecodeError::into_kind),
+            Err(DecodeError::InvalidUtf8 { valid_up_to: 2 })
+        );
+    }
+}
@@ -0,0 +1,70 @@
+// SCALE encoding for tuples: the elements are concatenated in order, with
+// no prefix. The unit type `()` encodes to nothing.
+
+use crate::{Decode, DecodeError, Encode, Input, Output};
+
+macro_rules! impl_tuple {
+    ($($name:ident),*) => {
+        impl<$($name: Encode),*> Encode for ($($name,)*) {
+            #[allow(non_snake_case, unused_variables)]
+            fn encode_to<Out: Output + ?Sized>(&self, dest: &mut Out) {
+                let ($($name,)*) = self;
+                $($name.encode_to(dest);)*
+            }
+
+            #[allow(non_snake_case)]
+            fn encoded_size(&self) -> usize {
+                let ($($name,)*) = self;
+                0 $(+ $name.encoded_size())*
+            }
+        }
+
+        impl<$($name: Decode),*> Decode for ($($name,)*) {
+            #[allow(unused_variables)]
+            fn decode<In: Input>(input: &mut In) -> Result<Self, DecodeError> {
+                Ok(($($name::decode(input)?,)*))
+            }
+        }
+    };
+}
+
+impl_tuple!();
+impl_tuple!(A);
+impl_tuple!(A, B);
+impl_tuple!(A, B, C);
+impl_tuple!(A, B, C, D);
+impl_tuple!(A, B, C, D, E);
+impl_tuple!(A, B, C, D, E, F);
+impl_tuple!(A, B, C, D, E, F, G);
+impl_tuple!(A, B, C, D, E, F, G, H);
+impl_tuple!(A, B, C, D, E, F, G, H, J);
+impl_tuple!(A, B, C, D, E, F, G, H, J, K);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Compact;
+
+    #[test]
+    fn tuples_concatenate_elements() {
+        assert_eq!(().encode(), Vec::<u8>::new());
+        assert_eq!((1u8,).encode(), vec![0x01]);
+        assert_eq!(
+            (0x1234u16, true, Compact(64u32)).encode(),
+            vec![0x34, 0x12, 0x01, 0x01, 0x01]
+        );
+        assert_eq!(
+            <(u16, bool, Compact<u32>)>::decode_all(&[0x34, 0x12, 0x01, 0x01, 0x01]),
+            Ok((0x1234, true, Compact(64)))
+        );
+    }
+
+    #[test]
+    fn tuple_errors_propagate() {
+        assert_eq!(
+            <(u8, bool)>::decode_all(&[0x01, 0x07]).map_err(DecodeError::into_kind),
+            Err(DecodeError::InvalidBool(0x07))
+        );
+        assert!(<(u8, u16)>::decode_all(&[0x01, 0x02]).is_err());
+    }
+}
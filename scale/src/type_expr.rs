@@ -0,0 +1,350 @@
+// Rust type expressions such as `Vec<Compact<u64>>`, `[u16; 4]` or
+// `Option<(u8, bool)>`, turned into registry types at runtime so tools can
+// take a type on the command line.
+
+use core::fmt;
+
+use crate::prelude::*;
+use crate::registry::{btree_type, option_type, result_type};
+use crate::{DecodeLimits, Lsb0, Msb0, Primitive, Registry, Type, TypeDef};
+
+/// A type expression that could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseTypeError {
+    /// Byte offset into the expression.
+    pub position: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseTypeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at offset {}", self.message, self.position)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseTypeError {}
+
+impl Registry {
+    /// Adds the type written as the Rust type expression `expr` and
+    /// returns its ID.
+    ///
+    /// Understands the integer types, `bool`, `String` (or `str`), `()`,
+    /// tuples, `[T; N]`, `[T]`, `Vec`, `Compact`, `Option`, `Result`,
+    /// `BTreeMap`, `BTreeSet` and `BitSeq<S, O>` (or `BitVec`) with `Lsb0`
+    /// or `Msb0`. Any other name is looked up with [`Registry::find`], so
+    /// types already in the registry can be used too. Types may nest as
+    /// deep as [`DecodeLimits::default`] allows values to.
+    ///
+    /// ```
+    /// use scale::{Registry, TypeDef};
+    ///
+    /// let mut registry = Registry::new();
+    /// let ty = registry.parse_type("Option<(u8, bool)>").unwrap();
+    /// let TypeDef::Variant(variants) = &registry.resolve(ty).unwrap().def else { panic!() };
+    /// assert_eq!(registry.type_name(variants[1].fields[0].ty), "(u8, bool)");
+    /// ```
+    pub fn parse_type(&mut self, expr: &str) -> Result<u32, ParseTypeError> {
+        let mut parser = Parser {
+            expr,
+            position: 0,
+            depth: 0,
+        };
+        let ty = parser.ty(self)?;
+        parser.skip_whitespace();
+        if parser.position < expr.len() {
+            return Err(parser.error("expected the end of the type"));
+        }
+        Ok(ty)
+    }
+}
+
+struct Parser<'a> {
+    expr: &'a str,
+    position: usize,
+    /// Types currently being parsed, so deep nesting fails instead of
+    /// overflowing the stack.
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn ty(&mut self, registry: &mut Registry) -> Result<u32, ParseTypeError> {
+        if self.depth == DecodeLimits::default().max_depth {
+            return Err(self.error("types are nested too deeply"));
+        }
+        self.depth += 1;
+        let ty = self.nested_ty(registry);
+        self.depth -= 1;
+        ty
+    }
+
+    fn nested_ty(&mut self, registry: &mut Registry) -> Result<u32, ParseTypeError> {
+        self.skip_whitespace();
+        if self.eat('[') {
+            let element = self.ty(registry)?;
+            if self.eat(']') {
+                return Ok(registry.add(Type::new(TypeDef::Sequence(element))));
+            }
+            self.expect(';')?;
+            let len = self.number()?;
+            self.expect(']')?;
+            return Ok(registry.add(Type::new(TypeDef::Array { len, element })));
+        }
+        if self.eat('(') {
+            let (elements, trailing_comma) = self.list(registry, ')')?;
+            // `(T)` is just `T` in parentheses; `(T,)` is a tuple.
+            if elements.len() == 1 && !trailing_comma {
+                return Ok(elements[0]);
+            }
+            return Ok(registry.add(Type::new(TypeDef::Tuple(elements))));
+        }
+        let start = self.position;
+        let name = self.ident()?;
+        let args = if self.eat('<') {
+            self.list(registry, '>')?.0
+        } else {
+            Vec::new()
+        };
+        named(registry, name, &args).map_err(|message| ParseTypeError {
+            position: start,
+            message,
+        })
+    }
+
+    /// Types separated by commas up to `close`, and whether the last one
+    /// was followed by a comma.
+    fn list(
+        &mut self,
+        registry: &mut Registry,
+        close: char,
+    ) -> Result<(Vec<u32>, bool), ParseTypeError> {
+        let mut items = Vec::new();
+        loop {
+            if self.eat(close) {
+                return Ok((items, true));
+            }
+            items.push(self.ty(registry)?);
+            if !self.eat(',') {
+                self.expect(close)?;
+                return Ok((items, false));
+            }
+        }
+    }
+
+    /// A name, possibly with a path such as `header::Header`.
+    fn ident(&mut self) -> Result<&'a str, ParseTypeError> {
+        self.skip_whitespace();
+        let rest = &self.expr[self.position..];
+        let len = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == ':'))
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return Err(self.error("expected a type"));
+        }
+        self.position += len;
+        Ok(&rest[..len])
+    }
+
+    fn number(&mut self) -> Result<u32, ParseTypeError> {
+        self.skip_whitespace();
+        let rest = &self.expr[self.position..];
+        let len = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '_'))
+            .unwrap_or(rest.len());
+        let Ok(value) = rest[..len].replace('_', "").parse() else {
+            return Err(self.error("expected an array length"));
+        };
+        self.position += len;
+        Ok(value)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        if self.expr[self.position..].starts_with(c) {
+            self.position += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), ParseTypeError> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected `{}`", c)))
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.expr[self.position..];
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    fn error(&self, message: &str) -> ParseTypeError {
+        ParseTypeError {
+            position: self.position,
+            message: message.to_string(),
+        }
+    }
+}
+
+/// The type called `name` with type arguments `args`.
+fn named(registry: &mut Registry, name: &str, args: &[u32]) -> Result<u32, String> {
+    let arity = match name {
+        "Vec" | "Compact" | "Option" | "BTreeSet" => 1,
+        "Result" | "BTreeMap" | "BitSeq" | "BitVec" => 2,
+        _ => 0,
+    };
+    if args.len() != arity {
+        return Err(match arity {
+            0 => format!("`{}` takes no type arguments", name),
+            1 => format!("`{}` takes 1 type argument", name),
+            _ => format!("`{}` takes {} type arguments", name, arity),
+        });
+    }
+    let ty = match (name, args) {
+        ("bool", _) => registry.register::<bool>(),
+        ("String" | "str", _) => registry.register::<String>(),
+        ("u8", _) => registry.register::<u8>(),
+        ("u16", _) => registry.register::<u16>(),
+        ("u32", _) => registry.register::<u32>(),
+        ("u64", _) => registry.register::<u64>(),
+        ("u128", _) => registry.register::<u128>(),
+        ("i8", _) => registry.register::<i8>(),
+        ("i16", _) => registry.register::<i16>(),
+        ("i32", _) => registry.register::<i32>(),
+        ("i64", _) => registry.register::<i64>(),
+        ("i128", _) => registry.register::<i128>(),
+        ("Lsb0", _) => registry.register::<Lsb0>(),
+        ("Msb0", _) => registry.register::<Msb0>(),
+        ("Vec", &[element]) => registry.add(Type::new(TypeDef::Sequence(element))),
+        ("Compact", &[inner]) => {
+            registry
+                .compact_primitive(inner)
+                .map_err(|_| "`Compact` takes an unsigned integer type".to_string())?;
+            registry.add(Type::new(TypeDef::Compact(inner)))
+        }
+        // `Option<bool>` has its own one-byte layout.
+        ("Option", &[some])
+            if registry.resolve(some).map(|ty| &ty.def)
+                == Some(&TypeDef::Primitive(Primitive::Bool)) =>
+        {
+            registry.register::<Option<bool>>()
+        }
+        ("Option", &[some]) => registry.add(option_type(some)),
+        ("Result", &[ok, err]) => registry.add(result_type(ok, err)),
+        ("BTreeMap", &[key, value]) => {
+            let entry = registry.add(Type::new(TypeDef::Tuple(vec![key, value])));
+            let entries = registry.add(Type::new(TypeDef::Sequence(entry)));
+            registry.add(btree_type("BTreeMap", entries))
+        }
+        ("BTreeSet", &[element]) => {
+            let entries = registry.add(Type::new(TypeDef::Sequence(element)));
+            registry.add(btree_type("BTreeSet", entries))
+        }
+        ("BitSeq" | "BitVec", &[store, order]) => {
+            registry
+                .store_primitive(store)
+                .map_err(|_| "bit store must be u8, u16, u32 or u64".to_string())?;
+            registry
+                .is_msb0(order)
+                .map_err(|_| "bit order must be Lsb0 or Msb0".to_string())?;
+            registry.add(Type::new(TypeDef::BitSequence { store, order }))
+        }
+        _ => registry
+            .find(name)
+            .ok_or_else(|| format!("unknown type `{}`", name))?,
+    };
+    Ok(ty)
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "std")]
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use super::*;
+    use crate::Field;
+    #[cfg(feature = "std")]
+    use crate::{explain, BitSeq, Compact, Encode, TypeInfo};
+
+    /// Asserts `expr` parses to a type that decodes `value` exactly like
+    /// the registered Rust type `T`.
+    #[cfg(feature = "std")]
+    fn assert_parses_as<T: TypeInfo + Encode>(expr: &str, value: T) {
+        let mut registry = Registry::new();
+        let parsed = registry.parse_type(expr).unwrap();
+        let registered = registry.register::<T>();
+        assert_eq!(registry.type_name(parsed), registry.type_name(registered));
+        let bytes = value.encode();
+        let explanation = explain(&registry, parsed, &bytes);
+        assert_eq!(explanation.error, None, "{}", expr);
+        assert_eq!(
+            explanation,
+            explain(&registry, registered, &bytes),
+            "{}",
+            expr
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn parses_standard_types() {
+        assert_parses_as("Vec<Compact<u64>>", vec![Compact(1u64), Compact(1 << 40)]);
+        assert_parses_as("[u16; 4]", [1u16, 2, 3, 4]);
+        assert_parses_as("[ [u8; 2] ; 1_0 ]", [[7u8; 2]; 10]);
+        assert_parses_as("Option<(u8, bool)>", Some((5u8, true)));
+        assert_parses_as("Option<bool>", Some(false));
+        assert_parses_as("(String, (i32,), ())", (String::from("dot"), (-1i32,), ()));
+        assert_parses_as("(u8)", 3u8);
+        assert_parses_as("Result<u32, String>", Err::<u32, _>(String::from("no")));
+        assert_parses_as(
+            "BTreeMap<u8, Vec<bool>>",
+            BTreeMap::from([(1u8, vec![true]), (2, vec![])]),
+        );
+        assert_parses_as("BTreeSet<u128>", BTreeSet::from([1u128, 2]));
+        let bits: BitSeq<u16, Msb0> = [true, false, true].into_iter().collect();
+        assert_parses_as("BitVec<u16, Msb0>", bits);
+    }
+
+    #[test]
+    fn finds_registered_types() {
+        let mut registry = Registry::new();
+        let u32_ty = registry.register::<u32>();
+        let point = registry.add(Type::with_path(
+            ["geometry", "Point"],
+            TypeDef::Composite(vec![Field::named("x", u32_ty), Field::named("y", u32_ty)]),
+        ));
+        let ty = registry.parse_type("Vec<geometry::Point>").unwrap();
+        assert_eq!(registry.resolve(ty).unwrap().def, TypeDef::Sequence(point));
+        assert_eq!(registry.type_name(ty), "Vec<Point>");
+    }
+
+    #[test]
+    fn reports_where_parsing_failed() {
+        let error = |expr: &str| Registry::new().parse_type(expr).unwrap_err().to_string();
+        assert_eq!(error("Vec<u8"), "expected `>` at offset 6");
+        assert_eq!(error("[u8; x]"), "expected an array length at offset 5");
+        assert_eq!(error("Vec<Foo>"), "unknown type `Foo` at offset 4");
+        assert_eq!(
+            error("Vec<u8, u8>"),
+            "`Vec` takes 1 type argument at offset 0"
+        );
+        assert_eq!(error("u8<u8>"), "`u8` takes no type arguments at offset 0");
+        assert_eq!(
+            error("Compact<i8>"),
+            "`Compact` takes an unsigned integer type at offset 0"
+        );
+        assert_eq!(
+            error("BitSeq<u8, u8>"),
+            "bit order must be Lsb0 or Msb0 at offset 0"
+        );
+        assert_eq!(error("u8 u8"), "expected the end of the type at offset 3");
+        assert_eq!(error(""), "expected a type at offset 0");
+        let deep = format!("{}u8{}", "Vec<".repeat(5000), ">".repeat(5000));
+        assert_eq!(error(&deep), "types are nested too deeply at offset 256");
+        let deepest = format!("{}u8{}", "Vec<".repeat(63), ">".repeat(63));
+        assert!(Registry::new().parse_type(&deepest).is_ok());
+    }
+}
@@ -0,0 +1,166 @@
+// SCALE Vector encoding: a compact-encoded length prefix followed by the
+// SCALE-encoded elements.
+
+use crate::compact::{compact_len, decode_compact, encode_compact};
+use crate::input::nested;
+use crate::prelude::*;
+use crate::{Decode, DecodeError, DecodeMode, Encode, Input, Output};
+
+/// Decodes the compact length prefix of a collection.
+pub(crate) fn decode_len<I: Input>(input: &mut I) -> Result<usize, DecodeError> {
+    let len = decode_compact(input, DecodeMode::Strict)?;
+    usize::try_from(len).map_err(|_| DecodeError::CompactOverflow { type_name: "usize" })
+}
+
+impl<T: Encode> Encode for Vec<T> {
+    fn encode_to<O: Output + ?Sized>(&self, dest: &mut O) {
+        self.as_slice().encode_to(dest);
+    }
+
+    fn encoded_size(&self) -> usize {
+        self.as_slice().encoded_size()
+    }
+}
+
+impl<T: Encode> Encode for [T] {
+    fn encode_to<O: Output + ?Sized>(&self, dest: &mut O) {
+        encode_compact(self.len() as u128, dest);
+        T::encode_slice(self, dest);
+    }
+
+    fn encoded_size(&self) -> usize {
+        compact_len(self.len() as u128) + T::encoded_size_slice(self)
+    }
+}
+
+impl<T: Decode> Decode for Vec<T> {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, DecodeError> {
+        let len = decode_len(input)?;
+        input.on_collection(len, len.saturating_mul(core::mem::size_of::<T>()))?;
+        nested(input, |input| T::decode_vec(input, len))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Compact, DecodeLimits};
+
+    #[test]
+    fn vector_u8() {
+        let cases: &[(Vec<u8>, &[u8])] = &[
+            (vec![], &[0x00]),
+            (vec![1], &[0x04, 0x01]),
+            (vec![1, 0], &[0x08, 0x01, 0x00]),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(value.encode(), *expected);
+            assert_eq!(Vec::<u8>::decode_all(expected).as_ref(), Ok(value));
+        }
+    }
+
+    #[test]
+    fn vector_compact() {
+        let value: Vec<Compact<u64>> = vec![Compact(1), Compact(0), Compact(64)];
+        let encoded = value.encode();
+        assert_eq!(encoded, vec![0x0c, 0x04, 0x00, 0x01, 0x01]);
+        assert_eq!(Vec::<Compact<u64>>::decode_all(&encoded), Ok(value));
+    }
+
+    #[test]
+    fn vector_u16_and_slice() {
+        let value: Vec<u16> = vec![1, 0x0102];
+        assert_eq!(value.encode(), vec![0x08, 0x01, 0x00, 0x02, 0x01]);
+        assert_eq!(value[..].encode(), value.encode());
+        assert_eq!(Vec::<u16>::decode_all(&value.encode()), Ok(value));
+    }
+
+    #[test]
+    fn nested_vectors() {
+        let value: Vec<Vec<u8>> = vec![vec![], vec![1, 2], vec![3]];
+        let encoded = value.encode();
+        assert_eq!(encoded, vec![0x0c, 0x00, 0x08, 0x01, 0x02, 0x04, 0x03]);
+        assert_eq!(Vec::<Vec<u8>>::decode_all(&encoded), Ok(value));
+    }
+
+    #[test]
+    fn vector_of_authorities() {
+        // `next_authorities: Vec<(Vec<u8>, u64)>` from the header example.
+        let value: Vec<(Vec<u8>, u64)> = vec![(vec![0xaa; 32], 1), (vec![0xbb; 32], 2)];
+        let encoded = value.encode();
+        assert_eq!(encoded.len(), 1 + 2 * (1 + 32 + 8));
+        assert_eq!(&encoded[..2], &[0x08, 0x80]);
+        assert_eq!(Vec::<(Vec<u8>, u64)>::decode_all(&encoded), Ok(value));
+    }
+
+    #[test]
+    fn vector_of_options() {
+        let value = vec![Some(true), None, Some(false)];
+        assert_eq!(value.encode(), vec![0x0c, 0x01, 0x00, 0x02]);
+        assert_eq!(Vec::<Option<bool>>::decode_all(&value.encode()), Ok(value));
+    }
+
+    #[test]
+    fn bogus_length_does_not_preallocate() {
+        // Claims 2^30 elements of 16 bytes each but holds only one. The
+        // default limits reject it outright, so turn them off.
+        let mut encoded = vec![0x03, 0x00, 0x00, 0x00, 0x40];
+        encoded.extend_from_slice(&1u128.encode());
+        assert_eq!(
+            Vec::<u128>::decode_all_with_limits(&encoded, DecodeLimits::UNLIMITED)
+                .map_err(DecodeError::into_kind),
+            Err(DecodeError::NotEnoughData {
+                expected: 16,
+                remaining: 0
+            })
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn vector_from_reader() {
+        let encoded = vec![Compact(1u64), Compact(1 << 40)].encode();
+        let mut reader = crate::IoReader::new(std::io::Cursor::new(encoded.clone()));
+        assert_eq!(
+            Vec::<Compact<u64>>::decode(&mut reader),
+            Ok(vec![Compact(1), Compact(1 << 40)])
+        );
+        assert_eq!(reader.position(), encoded.len() as u64);
+
+        // Without limits, a length prefix claiming 2^30 bytes fails at the
+        // end of the stream.
+        let bogus = [0xfe, 0xff, 0xff, 0xff, 0x01];
+        let mut reader = crate::IoReader::with_limits(&bogus[..], DecodeLimits::UNLIMITED);
+        assert_eq!(
+            Vec::<u8>::decode(&mut reader),
+            Err(DecodeError::NotEnoughData {
+                expected: (1 << 30) - 1,
+                remaining: 1
+            })
+        );
+    }
+
+    #[test]
+    fn vector_shorter_than_prefix() {
+        assert_eq!(
+            Vec::<u8>::decode_all(&[0x0c, 0x01]).map_err(DecodeError::into_kind),
+            Err(DecodeError::NotEnoughData {
+                expected: 3,
+                remaining: 1
+            })
+        );
+        assert!(Vec::<Compact<u64>>::decode_all(&[0x08, 0x04]).is_err());
+    }
+
+    #[test]
+    fn encoded_size_without_encoding() {
+        let options = vec![Some(true), None, Some(false)];
+        assert_eq!(options.encoded_size(), 4);
+        let nested = vec![vec![Compact(1u64 << 40)], vec![]];
+        assert_eq!(nested.encoded_size(), nested.encode().len());
+        let long = vec![0u16; 100];
+        assert_eq!(long.encoded_size(), 2 + 200);
+        let names = vec!["Alice".to_string(), String::new()];
+        assert_eq!(names.encoded_size(), names.encode().len());
+    }
+}
@@ -0,0 +1,296 @@
+// Tests for `#[derive(Encode, Decode)]`, using the types from the SCALE
+// examples as fixtures.
+
+#![cfg(feature = "derive")]
+
+use scale::{
+    Compact, Decode, DecodeError, DecodeLimits, Encode, Field, MaxEncodedLen, Primitive, Registry,
+    TypeDef, TypeInfo, Variant,
+};
+
+// Same layout as `MyScaleEnum` in example 03.
+#[derive(Debug, PartialEq, Encode, Decode, MaxEncodedLen, TypeInfo)]
+enum MyScaleEnum {
+    Foo(u16),
+    Bar(bool),
+    Baz,
+}
+
+#[derive(Debug, PartialEq, Encode, Decode, TypeInfo)]
+enum Indexed {
+    #[codec(index = 3)]
+    Three,
+    #[codec(index = 1)]
+    One { value: u16 },
+}
+
+#[derive(Debug, PartialEq, Encode, Decode, TypeInfo)]
+struct Named {
+    first: u8,
+    #[codec(compact)]
+    second: u64,
+    #[codec(skip)]
+    cached: u16,
+    payload: Vec<u8>,
+}
+
+#[derive(Debug, PartialEq, Encode, Decode, MaxEncodedLen)]
+struct Tuple(u16, #[codec(compact)] u64, [u8; 2]);
+
+#[derive(Debug, PartialEq, Encode, Decode, MaxEncodedLen)]
+struct Unit;
+
+#[derive(Debug, PartialEq, Encode, Decode, MaxEncodedLen, TypeInfo)]
+struct Wrapper<T> {
+    inner: T,
+}
+
+// Recursive through `Vec`, so only the depth limit bounds it.
+#[derive(Debug, PartialEq, Encode, Decode, TypeInfo)]
+enum Tree {
+    Leaf(u8),
+    Node(Vec<Tree>),
+}
+
+#[test]
+fn enum_uses_position_as_index() {
+    assert_eq!(MyScaleEnum::Foo(1).encode(), vec![0x00, 0x01, 0x00]);
+    assert_eq!(MyScaleEnum::Foo(0x1234).encode(), vec![0x00, 0x34, 0x12]);
+    assert_eq!(MyScaleEnum::Bar(false).encode(), vec![0x01, 0x00]);
+    assert_eq!(MyScaleEnum::Bar(true).encode(), vec![0x01, 0x01]);
+    assert_eq!(MyScaleEnum::Baz.encode(), vec![0x02]);
+    assert_eq!(
+        MyScaleEnum::decode_all(&[0x00, 0x34, 0x12]),
+        Ok(MyScaleEnum::Foo(0x1234))
+    );
+    assert_eq!(MyScaleEnum::decode_all(&[0x02]), Ok(MyScaleEnum::Baz));
+}
+
+#[test]
+fn enum_honours_explicit_index() {
+    assert_eq!(Indexed::Three.encode(), vec![0x03]);
+    assert_eq!(Indexed::One { value: 2 }.encode(), vec![0x01, 0x02, 0x00]);
+    assert_eq!(Indexed::decode_all(&[0x03]), Ok(Indexed::Three));
+    assert_eq!(
+        Indexed::decode_all(&[0x00]).map_err(DecodeError::into_kind),
+        Err(DecodeError::UnknownVariant {
+            type_name: "Indexed",
+            tag: 0
+        })
+    );
+}
+
+#[test]
+fn struct_fields_in_order() {
+    let value = Named {
+        first: 1,
+        second: 64,
+        cached: 99,
+        payload: vec![0xaa],
+    };
+    let encoded = value.encode();
+    assert_eq!(encoded, vec![0x01, 0x01, 0x01, 0x04, 0xaa]);
+    assert_eq!(
+        Named::decode_all(&encoded),
+        Ok(Named { cached: 0, ..value })
+    );
+}
+
+#[test]
+fn tuple_and_unit_structs() {
+    let value = Tuple(258, 1, [7, 8]);
+    assert_eq!(value.encode(), vec![0x02, 0x01, 0x04, 0x07, 0x08]);
+    assert_eq!(Tuple::decode_all(&value.encode()), Ok(value));
+    assert_eq!(Unit.encode(), Vec::<u8>::new());
+    assert_eq!(Unit::decode_all(&[]), Ok(Unit));
+}
+
+#[test]
+fn generic_struct() {
+    let value = Wrapper {
+        inner: vec![Compact(1u64), Compact(64)],
+    };
+    assert_eq!(value.encode(), vec![0x08, 0x04, 0x01, 0x01]);
+    assert_eq!(Wrapper::decode_all(&value.encode()), Ok(value));
+}
+
+#[test]
+fn truncated_input_is_an_error() {
+    assert!(matches!(
+        MyScaleEnum::decode_all(&[0x00, 0x01]).map_err(DecodeError::into_kind),
+        Err(DecodeError::NotEnoughData { .. })
+    ));
+    assert!(Named::decode_all(&[0x01]).is_err());
+    assert_eq!(
+        MyScaleEnum::decode_all(&[0x01, 0x05]).map_err(DecodeError::into_kind),
+        Err(DecodeError::InvalidBool(0x05))
+    );
+    assert_eq!(
+        MyScaleEnum::decode_all(&[0xff]).map_err(DecodeError::into_kind),
+        Err(DecodeError::UnknownVariant {
+            type_name: "MyScaleEnum",
+            tag: 0xff
+        })
+    );
+}
+
+#[test]
+fn arrays_of_derived_types() {
+    let value = [
+        MyScaleEnum::Foo(1),
+        MyScaleEnum::Baz,
+        MyScaleEnum::Bar(true),
+    ];
+    let encoded = value.encode();
+    assert_eq!(encoded, vec![0x00, 0x01, 0x00, 0x02, 0x01, 0x01]);
+    assert_eq!(<[MyScaleEnum; 3]>::decode_all(&encoded), Ok(value));
+
+    let wrapped = Wrapper {
+        inner: [Tuple(1, 2, [3, 4]), Tuple(5, 6, [7, 8])],
+    };
+    assert_eq!(
+        Wrapper::<[Tuple; 2]>::decode_all(&wrapped.encode()),
+        Ok(wrapped)
+    );
+}
+
+#[test]
+fn recursion_depth_is_limited() {
+    let mut tree = Tree::Leaf(7);
+    for _ in 0..100 {
+        tree = Tree::Node(vec![tree]);
+    }
+    let encoded = tree.encode();
+    assert_eq!(
+        Tree::decode_all(&encoded).map_err(DecodeError::into_kind),
+        Err(DecodeError::DepthLimit(64))
+    );
+    assert_eq!(
+        Tree::decode_all_with_limits(&encoded, DecodeLimits::UNLIMITED).as_ref(),
+        Ok(&tree)
+    );
+
+    // Each level is one derived type and one vector.
+    let limits = DecodeLimits {
+        max_depth: 201,
+        ..DecodeLimits::default()
+    };
+    assert_eq!(Tree::decode_all_with_limits(&encoded, limits), Ok(tree));
+}
+
+#[test]
+fn hostile_nesting_is_limited_by_default() {
+    // `Node` holding one element, over and over: deep enough to overflow
+    // the stack if nothing bounded it.
+    let hostile = [0x01, 0x04].repeat(1024 * 1024);
+    let error = Tree::decode_all(&hostile).unwrap_err();
+    assert_eq!(error.kind(), &DecodeError::DepthLimit(64));
+    assert_eq!(error.offset(), Some(63));
+    #[cfg(feature = "std")]
+    {
+        let mut reader = scale::IoReader::new(&hostile[..]);
+        assert_eq!(Tree::decode(&mut reader), Err(DecodeError::DepthLimit(64)));
+    }
+}
+
+#[test]
+fn encoded_size_matches_encoding() {
+    let named = Named {
+        first: 1,
+        second: 1 << 20,
+        cached: 7,
+        payload: vec![0xaa; 70],
+    };
+    assert_eq!(named.encoded_size(), named.encode().len());
+    let tuple = Tuple(1, u64::MAX, [2, 3]);
+    assert_eq!(tuple.encoded_size(), tuple.encode().len());
+    for value in [
+        MyScaleEnum::Foo(1),
+        MyScaleEnum::Bar(true),
+        MyScaleEnum::Baz,
+    ] {
+        assert_eq!(value.encoded_size(), value.encode().len());
+    }
+    let tree = Tree::Node(vec![Tree::Leaf(1), Tree::Node(vec![Tree::Leaf(2)])]);
+    assert_eq!(tree.encoded_size(), tree.encode().len());
+}
+
+#[test]
+fn max_encoded_len_of_bounded_types() {
+    // Index byte plus the largest variant, `Foo(u16)`.
+    assert_eq!(MyScaleEnum::max_encoded_len(), 3);
+    // u16, a nine-byte compact u64 and two bytes.
+    assert_eq!(Tuple::max_encoded_len(), 13);
+    assert_eq!(Tuple(u16::MAX, u64::MAX, [0xff; 2]).encode().len(), 13);
+    assert_eq!(Unit::max_encoded_len(), 0);
+    assert_eq!(Wrapper::<[u8; 32]>::max_encoded_len(), 32);
+}
+
+#[test]
+fn type_info_of_example_enum() {
+    let mut registry = Registry::new();
+    let id = registry.register::<MyScaleEnum>();
+    let u16_id = registry.register::<u16>();
+    let bool_id = registry.register::<bool>();
+    let ty = registry.resolve(id).unwrap();
+    assert_eq!(ty.path, ["derive", "MyScaleEnum"]);
+    assert_eq!(
+        ty.def,
+        TypeDef::Variant(vec![
+            Variant::new("Foo", 0, vec![Field::unnamed(u16_id)]),
+            Variant::new("Bar", 1, vec![Field::unnamed(bool_id)]),
+            Variant::new("Baz", 2, vec![]),
+        ])
+    );
+}
+
+#[test]
+fn type_info_follows_codec_attributes() {
+    let mut registry = Registry::new();
+    let indexed = registry.register::<Indexed>();
+    let TypeDef::Variant(variants) = &registry.resolve(indexed).unwrap().def else {
+        panic!("not a variant");
+    };
+    assert_eq!(variants[0].index, 3);
+    assert_eq!(variants[1].index, 1);
+    assert_eq!(variants[1].fields[0].name.as_deref(), Some("value"));
+
+    // `cached` is skipped and `second` is described as a compact.
+    let named = registry.register::<Named>();
+    let compact = registry.register::<Compact<u64>>();
+    let bytes = registry.register::<Vec<u8>>();
+    let first = registry.register::<u8>();
+    assert_eq!(
+        registry.resolve(named).unwrap().def,
+        TypeDef::Composite(vec![
+            Field::named("first", first),
+            Field::named("second", compact),
+            Field::named("payload", bytes),
+        ])
+    );
+    assert_eq!(registry.type_name(compact), "Compact<u64>");
+
+    let wrapper = registry.register::<Wrapper<u32>>();
+    let TypeDef::Composite(fields) = &registry.resolve(wrapper).unwrap().def else {
+        panic!("not a composite");
+    };
+    assert_eq!(
+        registry.resolve(fields[0].ty).unwrap().def,
+        TypeDef::Primitive(Primitive::U32)
+    );
+}
+
+#[test]
+fn type_info_of_recursive_type() {
+    let mut registry = Registry::new();
+    let tree = registry.register::<Tree>();
+    let TypeDef::Variant(variants) = &registry.resolve(tree).unwrap().def else {
+        panic!("not a variant");
+    };
+    let children = variants[1].fields[0].ty;
+    assert_eq!(
+        registry.resolve(children).unwrap().def,
+        TypeDef::Sequence(tree)
+    );
+    assert_eq!(registry.type_name(children), "Vec<Tree>");
+}
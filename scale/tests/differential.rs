@@ -0,0 +1,292 @@
+// Differential tests against `parity-scale-codec`, the codec the node uses.
+// Random values must encode to the same bytes, and random or corrupted
+// bytes must decode to the same value (and leave the same number of bytes
+// unread) or fail in both codecs.
+
+#![cfg(feature = "derive")]
+
+use std::fmt::Debug;
+
+use proptest::collection::vec;
+use proptest::prelude::*;
+
+// Derives both codecs' traits, so each call below names the one it means.
+#[derive(Debug, Clone, PartialEq, scale::Encode, scale::Decode, codec::Encode, codec::Decode)]
+enum MyScaleEnum {
+    Foo(u16),
+    Bar(bool),
+    Baz,
+}
+
+#[derive(Debug, Clone, PartialEq, scale::Encode, scale::Decode, codec::Encode, codec::Decode)]
+enum Log {
+    #[codec(index = 4)]
+    Consensus([u8; 4], Vec<u8>),
+    #[codec(index = 6)]
+    PreRuntime {
+        engine: [u8; 4],
+        #[codec(compact)]
+        slot: u64,
+    },
+    #[codec(index = 8)]
+    Updated,
+}
+
+#[derive(Debug, Clone, PartialEq, scale::Encode, scale::Decode, codec::Encode, codec::Decode)]
+struct Transfer {
+    #[codec(compact)]
+    amount: u128,
+    kind: MyScaleEnum,
+    memo: Option<Vec<u8>>,
+}
+
+fn my_scale_enum() -> impl Strategy<Value = MyScaleEnum> {
+    prop_oneof![
+        any::<u16>().prop_map(MyScaleEnum::Foo),
+        any::<bool>().prop_map(MyScaleEnum::Bar),
+        Just(MyScaleEnum::Baz),
+    ]
+}
+
+fn log() -> impl Strategy<Value = Log> {
+    prop_oneof![
+        (any::<[u8; 4]>(), vec(any::<u8>(), 0..40)).prop_map(|(id, data)| Log::Consensus(id, data)),
+        (any::<[u8; 4]>(), any::<u64>())
+            .prop_map(|(engine, slot)| Log::PreRuntime { engine, slot }),
+        Just(Log::Updated),
+    ]
+}
+
+fn transfer() -> impl Strategy<Value = Transfer> {
+    (
+        any::<u128>(),
+        my_scale_enum(),
+        proptest::option::of(vec(any::<u8>(), 0..20)),
+    )
+        .prop_map(|(amount, kind, memo)| Transfer { amount, kind, memo })
+}
+
+/// Values spread over every compact mode rather than clustered near the
+/// top of the range.
+fn compact_value() -> impl Strategy<Value = u128> {
+    (any::<u128>(), 0u32..128).prop_map(|(value, shift)| value >> shift)
+}
+
+/// `bytes` truncated to `cut` bytes (if shorter) with the byte at `pos`
+/// (if any) XORed with `flip`.
+fn corrupt(mut bytes: Vec<u8>, cut: usize, pos: usize, flip: u8) -> Vec<u8> {
+    bytes.truncate(cut);
+    if !bytes.is_empty() {
+        let pos = pos % bytes.len();
+        bytes[pos] ^= flip;
+    }
+    bytes
+}
+
+/// Encodings of `value` in every compact mode wide enough for it, not just
+/// the canonical one.
+fn compact_in_every_mode(value: u128) -> Vec<Vec<u8>> {
+    let mut encodings = Vec::new();
+    if value < 1 << 6 {
+        encodings.push(vec![(value as u8) << 2]);
+    }
+    if value < 1 << 14 {
+        encodings.push((((value as u16) << 2) | 0b01).to_le_bytes().to_vec());
+    }
+    if value < 1 << 30 {
+        encodings.push((((value as u32) << 2) | 0b10).to_le_bytes().to_vec());
+    }
+    let significant = 16 - (value.leading_zeros() / 8) as usize;
+    for num_value_bytes in significant.max(4)..=17 {
+        let mut encoded = vec![(((num_value_bytes - 4) as u8) << 2) | 0b11];
+        encoded.extend_from_slice(&value.to_le_bytes()[..num_value_bytes.min(16)]);
+        encoded.resize(1 + num_value_bytes, 0);
+        encodings.push(encoded);
+    }
+    encodings
+}
+
+/// Decodes `bytes` with both codecs, mapping each result to a common type,
+/// and asserts they agree on the value and on the bytes left over, or both
+/// fail.
+fn assert_same_decode<Ours, Theirs, V>(
+    bytes: &[u8],
+    ours: impl Fn(Ours) -> V,
+    theirs: impl Fn(Theirs) -> V,
+) where
+    Ours: scale::Decode,
+    Theirs: codec::Decode,
+    V: PartialEq + Debug,
+{
+    let mut our_input = bytes;
+    let our_result = Ours::decode(&mut our_input).map(|value| (ours(value), our_input.len()));
+    let mut their_input = bytes;
+    let their_result =
+        Theirs::decode(&mut their_input).map(|value| (theirs(value), their_input.len()));
+    match (our_result, their_result) {
+        (Ok(ours), Ok(theirs)) => assert_eq!(ours, theirs, "input {:02x?}", bytes),
+        (Err(_), Err(_)) => {}
+        (ours, theirs) => panic!(
+            "codecs disagree on {:02x?}: ours {:?}, parity {:?}",
+            bytes, ours, theirs
+        ),
+    }
+}
+
+/// [`assert_same_decode`] for a type implementing both codecs.
+fn assert_same_decode_as<T>(bytes: &[u8])
+where
+    T: scale::Decode + codec::Decode + PartialEq + Debug,
+{
+    assert_same_decode::<T, T, T>(bytes, |value| value, |value| value);
+}
+
+/// Asserts both codecs encode `value` identically and both decode the
+/// result back to it.
+fn assert_same_round_trip<T>(value: &T)
+where
+    T: scale::Encode + scale::Decode + codec::Encode + codec::Decode + PartialEq + Debug,
+{
+    let ours = scale::Encode::encode(value);
+    assert_eq!(ours, codec::Encode::encode(value), "encoding {:?}", value);
+    assert_eq!(scale::Encode::encoded_size(value), ours.len());
+    assert_eq!(<T as scale::Decode>::decode_all(&ours).as_ref(), Ok(value));
+    assert_same_decode_as::<T>(&ours);
+}
+
+macro_rules! compact_tests {
+    ($($name:ident: $t:ty,)*) => {
+        $(
+            proptest! {
+                #[test]
+                fn $name(value in compact_value(), cut in 0usize..20, pos: usize, flip: u8) {
+                    let value = value as $t;
+                    let ours = scale::Encode::encode(&scale::Compact(value));
+                    prop_assert_eq!(&ours, &codec::Encode::encode(&codec::Compact(value)));
+                    prop_assert_eq!(scale::compact_len(value as u128), ours.len());
+
+                    let corrupted = corrupt(ours, cut, pos, flip);
+                    assert_same_decode::<scale::Compact<$t>, codec::Compact<$t>, $t>(
+                        &corrupted,
+                        |compact| compact.0,
+                        |compact| compact.0,
+                    );
+                }
+            }
+        )*
+    };
+}
+
+compact_tests! {
+    compact_u8: u8,
+    compact_u16: u16,
+    compact_u32: u32,
+    compact_u64: u64,
+    compact_u128: u128,
+}
+
+proptest! {
+    #[test]
+    fn non_canonical_compacts(value in compact_value()) {
+        // Both codecs accept only the shortest encoding of a value.
+        for encoded in compact_in_every_mode(value) {
+            assert_same_decode::<scale::Compact<u8>, codec::Compact<u8>, u8>(&encoded, |c| c.0, |c| c.0);
+            assert_same_decode::<scale::Compact<u16>, codec::Compact<u16>, u16>(&encoded, |c| c.0, |c| c.0);
+            assert_same_decode::<scale::Compact<u32>, codec::Compact<u32>, u32>(&encoded, |c| c.0, |c| c.0);
+            assert_same_decode::<scale::Compact<u64>, codec::Compact<u64>, u64>(&encoded, |c| c.0, |c| c.0);
+            assert_same_decode::<scale::Compact<u128>, codec::Compact<u128>, u128>(&encoded, |c| c.0, |c| c.0);
+        }
+    }
+
+    #[test]
+    fn enums(value in my_scale_enum(), other in log()) {
+        assert_same_round_trip(&value);
+        assert_same_round_trip(&other);
+    }
+
+    #[test]
+    fn derived_struct(value in transfer(), cut in 0usize..64, pos: usize, flip: u8) {
+        assert_same_round_trip(&value);
+        let encoded = scale::Encode::encode(&value);
+        assert_same_decode_as::<Transfer>(&corrupt(encoded, cut, pos, flip));
+    }
+
+    #[test]
+    fn vectors(
+        bytes in vec(any::<u8>(), 0..300),
+        words in vec(any::<u32>(), 0..80),
+        nested in vec(vec(any::<u16>(), 0..8), 0..8),
+        text in ".{0,40}",
+        enums in vec(my_scale_enum(), 0..20),
+    ) {
+        assert_same_round_trip(&bytes);
+        assert_same_round_trip(&words);
+        assert_same_round_trip(&nested);
+        assert_same_round_trip(&text);
+        assert_same_round_trip(&enums);
+    }
+
+    #[test]
+    fn compact_vectors(values in vec(compact_value(), 0..20)) {
+        let ours: Vec<_> = values.iter().map(|&v| scale::Compact(v as u64)).collect();
+        let theirs: Vec<_> = values.iter().map(|&v| codec::Compact(v as u64)).collect();
+        prop_assert_eq!(scale::Encode::encode(&ours), codec::Encode::encode(&theirs));
+    }
+
+    #[test]
+    fn arrays(
+        hash in any::<[u8; 32]>(),
+        words in any::<[u16; 4]>(),
+        pairs in any::<[(u32, bool); 3]>(),
+        hashes in any::<[[u8; 32]; 2]>(),
+    ) {
+        assert_same_round_trip(&hash);
+        assert_same_round_trip(&words);
+        assert_same_round_trip(&pairs);
+        assert_same_round_trip(&hashes);
+    }
+
+    #[test]
+    fn options_and_results(
+        flag in proptest::option::of(any::<bool>()),
+        number in proptest::option::of(any::<u32>()),
+        result in proptest::result::maybe_ok(any::<u64>(), my_scale_enum()),
+    ) {
+        // Our `Option<bool>` has the single-byte layout of parity's
+        // `OptionBool`; parity's own `Option<bool>` uses the generic one.
+        prop_assert_eq!(scale::Encode::encode(&flag), codec::Encode::encode(&codec::OptionBool(flag)));
+        assert_same_decode::<Option<bool>, codec::OptionBool, Option<bool>>(
+            &scale::Encode::encode(&flag),
+            |value| value,
+            |value| value.0,
+        );
+        assert_same_round_trip(&number);
+        assert_same_round_trip(&result);
+    }
+
+    #[test]
+    fn malformed_bytes(bytes in vec(any::<u8>(), 0..64)) {
+        assert_same_decode_as::<MyScaleEnum>(&bytes);
+        assert_same_decode_as::<Log>(&bytes);
+        assert_same_decode_as::<Transfer>(&bytes);
+        assert_same_decode_as::<Vec<u8>>(&bytes);
+        assert_same_decode_as::<Vec<u32>>(&bytes);
+        assert_same_decode_as::<Vec<Vec<u8>>>(&bytes);
+        assert_same_decode_as::<Vec<MyScaleEnum>>(&bytes);
+        assert_same_decode_as::<String>(&bytes);
+        assert_same_decode_as::<[u8; 32]>(&bytes);
+        assert_same_decode_as::<[u16; 4]>(&bytes);
+        assert_same_decode_as::<Option<u32>>(&bytes);
+        assert_same_decode_as::<Result<u8, MyScaleEnum>>(&bytes);
+        assert_same_decode::<Option<bool>, codec::OptionBool, Option<bool>>(
+            &bytes,
+            |value| value,
+            |value| value.0,
+        );
+        assert_same_decode::<Vec<scale::Compact<u32>>, Vec<codec::Compact<u32>>, Vec<u32>>(
+            &bytes,
+            |values| values.into_iter().map(|c| c.0).collect(),
+            |values| values.into_iter().map(|c| c.0).collect(),
+        );
+    }
+}
@@ -0,0 +1,40 @@
+// Replays the `library` fuzz corpus (see `fuzz/`) on stable: decoding
+// arbitrary bytes under the default limits must return an error, never
+// panic or allocate without bound.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use scale::{BitSeq, Compact, CompactBig, Decode, DecodeLimits, Msb0};
+
+fn decode_everything(data: &[u8]) {
+    let limits = DecodeLimits::default();
+    let _ = Vec::<(Compact<u64>, Option<bool>, String)>::decode_all_with_limits(data, limits);
+    let _ = BTreeMap::<u32, Vec<u8>>::decode_all_with_limits(data, limits);
+    let _ = BitSeq::<u32, Msb0>::decode_all_with_limits(data, limits);
+    let _ = CompactBig::decode_all_with_limits(data, limits);
+    let _ = Result::<[u16; 4], Vec<Compact<u128>>>::decode_all_with_limits(data, limits);
+    let _ = Vec::<Option<Vec<u8>>>::decode_all(data).map_err(|e| e.to_string());
+}
+
+#[test]
+fn library_corpus() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../fuzz/corpus/library");
+    let mut seeds = 0;
+    for entry in fs::read_dir(&dir).unwrap() {
+        let bytes = fs::read(entry.unwrap().path()).unwrap();
+        for len in 0..=bytes.len() {
+            decode_everything(&bytes[..len]);
+        }
+        for pos in 0..bytes.len() {
+            for flip in [0x01, 0x80, 0xff] {
+                let mut corrupted = bytes.clone();
+                corrupted[pos] ^= flip;
+                decode_everything(&corrupted);
+            }
+        }
+        seeds += 1;
+    }
+    assert!(seeds > 0);
+}
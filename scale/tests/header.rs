@@ -0,0 +1,342 @@
+// The Polkadot header types from the JSON-RPC example, modelled with this
+// crate instead of `parity-scale-codec`.
+
+#![cfg(feature = "derive")]
+
+use scale::{Decode, DecodeLimits, Encode, PathSegment, Registry, TypeDef, TypeInfo};
+
+#[derive(Debug, PartialEq, Encode, Decode, TypeInfo)]
+struct Header {
+    parent_hash: [u8; 32],
+    // Compact on chain; the JSON-RPC example uses a plain `u32`.
+    #[codec(compact)]
+    number: u32,
+    state_root: [u8; 32],
+    extrinsics_root: [u8; 32],
+    digest: HeaderDigest,
+}
+
+#[derive(Debug, PartialEq, Encode, Decode, TypeInfo)]
+struct HeaderDigest {
+    logs: Vec<DigestItem>,
+}
+
+#[derive(Debug, PartialEq, Encode, Decode, TypeInfo)]
+enum DigestItem {
+    #[codec(index = 0)]
+    Other(Vec<u8>),
+    #[codec(index = 1)]
+    Consensus(ConsensusLog),
+    #[codec(index = 2)]
+    Seal(Vec<u8>),
+    #[codec(index = 3)]
+    PreRuntime(Vec<u8>),
+    #[codec(index = 4)]
+    RuntimeEnvironmentUpdated,
+}
+
+#[derive(Debug, PartialEq, Encode, Decode, TypeInfo)]
+enum ConsensusLog {
+    #[codec(index = 0)]
+    Grandpa(GrandpaLog),
+    #[codec(index = 1)]
+    Babe(BabeLog),
+}
+
+#[derive(Debug, PartialEq, Encode, Decode, TypeInfo)]
+enum GrandpaLog {
+    #[codec(index = 0)]
+    ScheduledChange(GrandpaScheduledChange),
+    #[codec(index = 2)]
+    OnDisabled(u64),
+}
+
+#[derive(Debug, PartialEq, Encode, Decode, TypeInfo)]
+struct GrandpaScheduledChange {
+    next_authorities: Vec<(Vec<u8>, u64)>,
+    delay: u32,
+}
+
+#[derive(Debug, PartialEq, Encode, Decode, TypeInfo)]
+enum BabeLog {
+    #[codec(index = 0)]
+    NextEpochData(BabeNextEpoch),
+}
+
+#[derive(Debug, PartialEq, Encode, Decode, TypeInfo)]
+struct BabeNextEpoch {
+    authorities: Vec<(Vec<u8>, u64)>,
+    randomness: [u8; 32],
+}
+
+fn sample_header() -> Header {
+    Header {
+        parent_hash: [0x11; 32],
+        number: 1_000_000,
+        state_root: [0x22; 32],
+        extrinsics_root: [0x33; 32],
+        digest: HeaderDigest {
+            logs: vec![
+                DigestItem::PreRuntime(vec![0x42; 4]),
+                DigestItem::Consensus(ConsensusLog::Grandpa(GrandpaLog::ScheduledChange(
+                    GrandpaScheduledChange {
+                        next_authorities: vec![(vec![0xaa; 32], 1), (vec![0xbb; 32], 1)],
+                        delay: 0,
+                    },
+                ))),
+                DigestItem::Consensus(ConsensusLog::Babe(BabeLog::NextEpochData(BabeNextEpoch {
+                    authorities: vec![(vec![0xcc; 32], 1)],
+                    randomness: [0x77; 32],
+                }))),
+                DigestItem::Seal(vec![0x55; 64]),
+            ],
+        },
+    }
+}
+
+#[test]
+fn header_round_trip() {
+    let header = sample_header();
+    assert_eq!(Header::decode_all(&header.encode()), Ok(header));
+}
+
+#[test]
+fn header_within_default_limits() {
+    let header = sample_header();
+    assert_eq!(
+        Header::decode_all_with_limits(&header.encode(), DecodeLimits::default()),
+        Ok(header)
+    );
+}
+
+#[test]
+fn header_encoded_size() {
+    let header = sample_header();
+    assert_eq!(header.encoded_size(), header.encode().len());
+    assert_eq!(header.digest.encoded_size(), header.encode().len() - 100);
+}
+
+#[test]
+fn header_layout() {
+    let encoded = sample_header().encode();
+    // 1_000_000 fits the four-byte compact mode.
+    assert_eq!(&encoded[32..36], &[0x02, 0x09, 0x3d, 0x00]);
+    // Three hashes and the block number, then four digest logs.
+    assert_eq!(encoded[100], 0x10);
+    // PreRuntime: tag 3, then the 4-byte payload.
+    assert_eq!(&encoded[101..107], &[0x03, 0x10, 0x42, 0x42, 0x42, 0x42]);
+    // Consensus -> Grandpa -> ScheduledChange with two authorities.
+    assert_eq!(&encoded[107..111], &[0x01, 0x00, 0x00, 0x08]);
+}
+
+#[test]
+fn digest_logs_with_unknown_variant() {
+    let mut encoded = HeaderDigest { logs: vec![] }.encode();
+    encoded[0] = 0x04;
+    encoded.push(0x09);
+    assert_eq!(
+        HeaderDigest::decode_all(&encoded).map_err(scale::DecodeError::into_kind),
+        Err(scale::DecodeError::UnknownVariant {
+            type_name: "DigestItem",
+            tag: 0x09
+        })
+    );
+}
+
+#[test]
+fn errors_point_into_the_header() {
+    let header = sample_header();
+    let mut encoded = header.encode();
+    // Skip the hashes, the block number, the log count and the first two
+    // logs, then the `DigestItem::Consensus` tag of the third log.
+    let offset =
+        100 + 1 + header.digest.logs[0].encode().len() + header.digest.logs[1].encode().len() + 1;
+    assert_eq!(encoded[offset], 0x01);
+    encoded[offset] = 0x07;
+
+    let error = Header::decode_all(&encoded).unwrap_err();
+    assert_eq!(
+        error,
+        scale::DecodeError::At {
+            error: Box::new(scale::DecodeError::UnknownVariant {
+                type_name: "ConsensusLog",
+                tag: 0x07
+            }),
+            offset: offset as u64,
+            expected: "ConsensusLog".to_string(),
+            root: "Header".to_string(),
+            path: vec![
+                PathSegment::Field("digest"),
+                PathSegment::Field("logs"),
+                PathSegment::Index(2),
+                PathSegment::Variant("Consensus"),
+            ],
+        }
+    );
+    assert_eq!(
+        error.path_string().as_deref(),
+        Some("Header.digest.logs[2].Consensus")
+    );
+}
+
+#[test]
+fn truncated_authority_list() {
+    // The second authority's weight starts at byte 185.
+    let encoded = sample_header().encode();
+    let error = Header::decode_all(&encoded[..190]).unwrap_err();
+    assert_eq!(error.offset(), Some(185));
+    assert_eq!(
+        error.to_string(),
+        "Not enough bytes. Expected 8, got 5 at byte 185 while decoding `(Vec<u8>, u64)` \
+         at Header.digest.logs[1].Consensus.Grandpa.ScheduledChange.next_authorities[1]"
+    );
+}
+
+#[test]
+fn header_type_registry() {
+    let mut registry = Registry::new();
+    let header = registry.register::<Header>();
+    let TypeDef::Composite(fields) = &registry.resolve(header).unwrap().def else {
+        panic!("not a composite");
+    };
+    let described: Vec<_> = fields
+        .iter()
+        .map(|field| (field.name.as_deref().unwrap(), registry.type_name(field.ty)))
+        .collect();
+    assert_eq!(
+        described,
+        [
+            ("parent_hash", "[u8; 32]".to_string()),
+            ("number", "Compact<u32>".to_string()),
+            ("state_root", "[u8; 32]".to_string()),
+            ("extrinsics_root", "[u8; 32]".to_string()),
+            ("digest", "HeaderDigest".to_string()),
+        ]
+    );
+    // The hashes share one `[u8; 32]` entry.
+    assert_eq!(fields[0].ty, fields[2].ty);
+
+    let digest_item = registry.register::<DigestItem>();
+    let TypeDef::Variant(variants) = &registry.resolve(digest_item).unwrap().def else {
+        panic!("not a variant");
+    };
+    let indexes: Vec<_> = variants
+        .iter()
+        .map(|v| (v.name.as_str(), v.index))
+        .collect();
+    assert_eq!(
+        indexes,
+        [
+            ("Other", 0),
+            ("Consensus", 1),
+            ("Seal", 2),
+            ("PreRuntime", 3),
+            ("RuntimeEnvironmentUpdated", 4),
+        ]
+    );
+
+    let grandpa = registry.register::<GrandpaLog>();
+    let TypeDef::Variant(variants) = &registry.resolve(grandpa).unwrap().def else {
+        panic!("not a variant");
+    };
+    assert_eq!(variants[1].index, 2);
+    assert_eq!(registry.type_name(variants[1].fields[0].ty), "u64");
+
+    let authorities = registry.register::<Vec<(Vec<u8>, u64)>>();
+    assert_eq!(registry.type_name(authorities), "Vec<(Vec<u8>, u64)>");
+    // Registering the header again adds nothing.
+    let len = registry.len();
+    registry.register::<Header>();
+    assert_eq!(registry.len(), len);
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn header_as_json() {
+    use serde_json::json;
+
+    let mut registry = Registry::new();
+    let ty = registry.register::<Header>();
+    // Decode through a spec file, as for a type with no Rust struct.
+    let registry = Registry::from_json(&registry.to_json()).unwrap();
+    assert_eq!(registry.find("Header"), Some(ty));
+
+    let encoded = sample_header().encode();
+    let value = scale::decode_value_all(&registry, ty, &encoded).unwrap();
+    let hash = |byte: &str| format!("0x{}", byte.repeat(32));
+    assert_eq!(value["parent_hash"], json!(hash("11")));
+    assert_eq!(value["number"], json!(1_000_000));
+    assert_eq!(
+        value["digest"]["logs"][0],
+        json!({"PreRuntime": "0x42424242"})
+    );
+    assert_eq!(
+        value["digest"]["logs"][1],
+        json!({"Consensus": {"Grandpa": {"ScheduledChange": {
+            "next_authorities": [[hash("aa"), 1], [hash("bb"), 1]],
+            "delay": 0,
+        }}}})
+    );
+    assert_eq!(
+        value["digest"]["logs"][2]["Consensus"]["Babe"]["NextEpochData"]["randomness"],
+        json!(hash("77"))
+    );
+    assert_eq!(scale::encode_value(&registry, ty, &value), Ok(encoded));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn header_explained() {
+    let mut registry = Registry::new();
+    let ty = registry.register::<Header>();
+    let encoded = sample_header().encode();
+    let explanation = scale::explain(&registry, ty, &encoded);
+    assert_eq!(explanation.error, None);
+    assert_eq!(explanation.explained_len(), encoded.len());
+
+    let lines: Vec<_> = explanation
+        .spans
+        .iter()
+        .map(|span| span.to_string())
+        .collect();
+    assert_eq!(lines[0], "bytes 0..32 = Header.parent_hash: 32 bytes");
+    assert_eq!(
+        lines[1],
+        "bytes 32..36 = Header.number: compact u32 (mode 0b10, value 1000000)"
+    );
+    assert_eq!(
+        lines[4..8],
+        [
+            "bytes 100..101 = Header.digest.logs: compact length (mode 0b00, value 4)",
+            "bytes 101..102 = Header.digest.logs[0]: variant index 3 (PreRuntime)",
+            "bytes 102..103 = Header.digest.logs[0].PreRuntime.0: compact length (mode 0b00, value 4)",
+            "bytes 103..107 = Header.digest.logs[0].PreRuntime.0: 4 bytes",
+        ]
+    );
+    assert!(explanation.to_markdown().contains(
+        "| 32..36 | `02 09 3d 00` | `Header.number` | compact u32 (mode 0b10, value 1000000) |\n"
+    ));
+}
+
+// `specs/polkadot_header.json` lets the `scale` tool transcode headers
+// without these Rust types; regenerate it with
+// `UPDATE_SPECS=1 cargo test -p scale --test header`.
+#[cfg(feature = "json")]
+#[test]
+fn header_spec_file_is_current() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../specs/polkadot_header.json");
+    let mut registry = Registry::new();
+    registry.register::<Header>();
+    let expected = serde_json::to_string_pretty(&registry.to_json()).unwrap() + "\n";
+    if std::env::var_os("UPDATE_SPECS").is_some() {
+        std::fs::write(path, &expected).unwrap();
+    }
+    let actual = std::fs::read_to_string(path).unwrap();
+    assert!(
+        actual == expected,
+        "{} is out of date; run with UPDATE_SPECS=1",
+        path
+    );
+    let spec = Registry::from_json(&serde_json::from_str(&actual).unwrap()).unwrap();
+    assert_eq!(spec.find("Header"), Some(0));
+}
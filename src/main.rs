@@ -1,7 +1,204 @@
-fn main() {
-    println!("PBA Campus 2025 Study Notes - Rust Examples");
-    println!("==========================================");
-    println!("This repository contains various Rust examples for learning purposes.");
-    println!("Check the README.md file for a list of available examples.");
-    println!("Each example can be found in the examples/ directory.");
+//! `scale`: encode, decode and explain SCALE values from the command line,
+//! using the shared `scale` codec instead of a throwaway program per check.
+//!
+//! ```text
+//! $ scale encode 'Vec<Compact<u64>>' '[1, 300]'
+//! 0x0804b104
+//! $ scale decode 'Option<(u8, bool)>' 0x010501
+//! {"Some":[5,true]}
+//! $ scale explain '[u16; 2]' 0x01000200
+//! 0000  01 00    [u16; 2][0]: u16 LE (value 1)
+//! 0002  02 00    [u16; 2][1]: u16 LE (value 2)
+//! $ scale to-json --types specs/polkadot_header.json Header header.hex
+//! $ scale from-json --ndjson --binary 'Vec<u8>' values.ndjson > values.scale
+//! ```
+
+mod transcode;
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::process::ExitCode;
+
+use scale::{decode_value_all, encode_value, explain, to_hex, Registry};
+use serde_json::Value;
+
+use transcode::Format;
+
+const USAGE: &str = "\
+Usage:
+  scale encode <TYPE> <VALUE>                Encode a JSON value, printing hex
+  scale decode <TYPE> <HEX>                  Decode hex bytes, printing JSON
+  scale explain [--markdown] <TYPE> <HEX>    Show which bytes encode what
+  scale to-json [OPTIONS] <TYPE> [FILE]      Transcode SCALE to pretty JSON
+  scale from-json [OPTIONS] <TYPE> [FILE]    Transcode JSON to SCALE
+
+Options:
+  --types <SPEC>  Read types from a JSON type spec file so TYPE can name
+                  them, e.g. --types specs/polkadot_header.json Header
+  --markdown      Print `explain` output as a Markdown table
+  --binary        Read (to-json) or write (from-json) raw bytes, not hex
+  --ndjson        Stream one value per input line to one output line; with
+                  --binary, to-json reads consecutive values to the end
+
+TYPE is a Rust type expression, e.g. 'Vec<Compact<u64>>', '[u16; 4]' or
+'Option<(u8, bool)>'. VALUE is JSON as printed by `decode`: integers above
+64 bits and byte strings are strings (\"0x...\"), enums are \"Name\" or
+{\"Name\": fields}; a VALUE that is not JSON is taken as a string. HEX may
+start with 0x and contain spaces. FILE defaults to stdin, as does `-`.
+";
+
+#[derive(Default)]
+struct Options {
+    types: Option<String>,
+    markdown: bool,
+    format: Format,
+    positional: Vec<String>,
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn parse_options(args: &[String]) -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--types" => {
+                let path = args.next().ok_or("`--types` needs a spec file")?;
+                options.types = Some(path.clone());
+            }
+            "--markdown" => options.markdown = true,
+            "--binary" => options.format.binary = true,
+            "--ndjson" => options.format.ndjson = true,
+            flag if flag.starts_with("--") && flag != "--help" => {
+                return Err(format!("unknown option `{}`\n\n{}", flag, USAGE));
+            }
+            _ => options.positional.push(arg.clone()),
+        }
+    }
+    Ok(options)
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    let options = parse_options(args)?;
+    let positional: Vec<&str> = options.positional.iter().map(String::as_str).collect();
+    let (command, type_expr, input) = match positional[..] {
+        [] | ["help" | "-h" | "--help"] => {
+            print!("{}", USAGE);
+            return Ok(());
+        }
+        [command @ ("encode" | "decode" | "explain"), type_expr, input] => {
+            (command, type_expr, Some(input))
+        }
+        [command @ ("to-json" | "from-json"), type_expr] => (command, type_expr, None),
+        [command @ ("to-json" | "from-json"), type_expr, file] => (command, type_expr, Some(file)),
+        _ => {
+            return Err(format!(
+                "expected a command, a type and an input\n\n{}",
+                USAGE
+            ))
+        }
+    };
+    if options.markdown && command != "explain" {
+        return Err("`--markdown` only applies to `explain`".to_string());
+    }
+    if (options.format.binary || options.format.ndjson) && !command.ends_with("-json") {
+        return Err(
+            "`--binary` and `--ndjson` only apply to `to-json` and `from-json`".to_string(),
+        );
+    }
+
+    let mut registry = match &options.types {
+        Some(path) => read_type_spec(path)?,
+        None => Registry::new(),
+    };
+    let ty = registry
+        .parse_type(type_expr)
+        .map_err(|error| format!("invalid type `{}`: {}", type_expr, error))?;
+
+    match (command, input) {
+        ("encode", Some(input)) => {
+            // Anything that is not JSON is a bare string, so `scale encode
+            // String hello` needs no extra quotes.
+            let value =
+                serde_json::from_str(input).unwrap_or_else(|_| Value::String(input.to_string()));
+            let encoded = encode_value(&registry, ty, &value).map_err(|error| error.to_string())?;
+            println!("{}", to_hex(&encoded));
+        }
+        ("decode", Some(input)) => {
+            let value = decode_bytes(&registry, ty, &from_hex(input)?)?;
+            println!("{}", value);
+        }
+        ("explain", Some(input)) => {
+            let explanation = explain(&registry, ty, &from_hex(input)?);
+            if options.markdown {
+                print!("{}", explanation.to_markdown());
+            } else {
+                print!("{}", explanation.hex_dump());
+            }
+            if let Some(error) = explanation.error {
+                return Err(error.to_string());
+            }
+        }
+        ("to-json", file) => transcode::to_json(
+            &registry,
+            ty,
+            open(file)?,
+            io::stdout().lock(),
+            options.format,
+        )?,
+        (_, file) => transcode::from_json(
+            &registry,
+            ty,
+            open(file)?,
+            io::stdout().lock(),
+            options.format,
+        )?,
+    }
+    Ok(())
+}
+
+/// Reads a type spec file in the layout of `Registry::to_json`.
+fn read_type_spec(path: &str) -> Result<Registry, String> {
+    let text = std::fs::read_to_string(path).map_err(|error| format!("{}: {}", path, error))?;
+    let spec = serde_json::from_str(&text).map_err(|error| format!("{}: {}", path, error))?;
+    Registry::from_json(&spec).map_err(|error| format!("{}: {}", path, error))
+}
+
+/// `file`, or stdin if there is none or it is `-`.
+fn open(file: Option<&str>) -> Result<Box<dyn BufRead>, String> {
+    match file {
+        None | Some("-") => Ok(Box::new(io::stdin().lock())),
+        Some(path) => {
+            let file = File::open(path).map_err(|error| format!("{}: {}", path, error))?;
+            Ok(Box::new(BufReader::new(file)))
+        }
+    }
+}
+
+/// Decodes one value from all of `bytes`, within `DecodeLimits::default()`.
+fn decode_bytes(registry: &Registry, ty: u32, bytes: &[u8]) -> Result<Value, String> {
+    decode_value_all(registry, ty, bytes).map_err(|error| error.to_string())
+}
+
+fn from_hex(hex: &str) -> Result<Vec<u8>, String> {
+    let digits: String = hex.split_whitespace().collect();
+    let digits = digits.strip_prefix("0x").unwrap_or(&digits);
+    // Whole files are hex too; only quote the start of them.
+    let quoted = match hex.char_indices().nth(32) {
+        Some((end, _)) => format!("`{}…`", &hex[..end]),
+        None => format!("`{}`", hex),
+    };
+    if !digits.len().is_multiple_of(2) {
+        return Err(format!("{} is not an even number of hex digits", quoted));
+    }
+    scale::from_hex(digits).ok_or_else(|| format!("{} is not valid hex", quoted))
 }
@@ -0,0 +1,123 @@
+// `scale to-json` and `scale from-json`: SCALE blobs (hex or raw bytes) to
+// JSON and back, either one value per input or streamed as one value per
+// line (NDJSON), writing each result as soon as its line is read.
+
+use std::io::{BufRead, Write};
+
+use scale::{decode_value, encode_value, to_hex, IoReader, Registry};
+use serde_json::Value;
+
+use crate::{decode_bytes, from_hex};
+
+/// How SCALE bytes and values are laid out in the input and output.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Format {
+    /// Raw bytes instead of hex.
+    pub binary: bool,
+    /// One value per line instead of one per input.
+    pub ndjson: bool,
+}
+
+/// Decodes SCALE bytes from `input` as registry type `ty` into JSON.
+///
+/// A single value is written as pretty JSON. With `ndjson`, every line of
+/// hex is decoded to one line of compact JSON; raw bytes have no lines, so
+/// values are decoded one after another until the input ends.
+pub fn to_json(
+    registry: &Registry,
+    ty: u32,
+    mut input: impl BufRead,
+    mut output: impl Write,
+    format: Format,
+) -> Result<(), String> {
+    if !format.ndjson {
+        let bytes = if format.binary {
+            let mut bytes = Vec::new();
+            input.read_to_end(&mut bytes).map_err(io_error)?;
+            bytes
+        } else {
+            let mut text = String::new();
+            input.read_to_string(&mut text).map_err(io_error)?;
+            from_hex(&text)?
+        };
+        let value = decode_bytes(registry, ty, &bytes)?;
+        let pretty = serde_json::to_string_pretty(&value).expect("JSON values serialize");
+        return writeln!(output, "{}", pretty).map_err(io_error);
+    }
+
+    if format.binary {
+        let mut count = 0;
+        while !input.fill_buf().map_err(io_error)?.is_empty() {
+            count += 1;
+            let mut reader = IoReader::new(&mut input);
+            let value = decode_value(registry, ty, &mut reader)
+                .map_err(|error| format!("value {}: {}", count, error))?;
+            // A type that takes no bytes would repeat forever.
+            if reader.position() == 0 {
+                return Err("values of this type take no bytes to stream".to_string());
+            }
+            write_line(&mut output, &value)?;
+        }
+        return Ok(());
+    }
+
+    for (index, line) in input.lines().enumerate() {
+        let line = line.map_err(io_error)?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let value = from_hex(&line)
+            .and_then(|bytes| decode_bytes(registry, ty, &bytes))
+            .map_err(|error| format!("line {}: {}", index + 1, error))?;
+        write_line(&mut output, &value)?;
+    }
+    Ok(())
+}
+
+/// Encodes JSON from `input` as registry type `ty`, the reverse of
+/// [`to_json`]: each value (or with `ndjson`, each line) becomes a line of
+/// hex, or its raw bytes.
+pub fn from_json(
+    registry: &Registry,
+    ty: u32,
+    mut input: impl BufRead,
+    mut output: impl Write,
+    format: Format,
+) -> Result<(), String> {
+    let mut encode = |text: &str| -> Result<(), String> {
+        let value: Value =
+            serde_json::from_str(text).map_err(|error| format!("invalid JSON: {}", error))?;
+        let encoded = encode_value(registry, ty, &value).map_err(|error| error.to_string())?;
+        if format.binary {
+            output.write_all(&encoded).map_err(io_error)?;
+        } else {
+            writeln!(output, "{}", to_hex(&encoded)).map_err(io_error)?;
+        }
+        output.flush().map_err(io_error)
+    };
+
+    if !format.ndjson {
+        let mut text = String::new();
+        input.read_to_string(&mut text).map_err(io_error)?;
+        return encode(&text);
+    }
+    for (index, line) in input.lines().enumerate() {
+        let line = line.map_err(io_error)?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        encode(&line).map_err(|error| format!("line {}: {}", index + 1, error))?;
+    }
+    Ok(())
+}
+
+/// Writes `value` as one line of compact JSON, flushed so a reader at the
+/// other end of a pipe sees it straight away.
+fn write_line(output: &mut impl Write, value: &Value) -> Result<(), String> {
+    writeln!(output, "{}", value).map_err(io_error)?;
+    output.flush().map_err(io_error)
+}
+
+fn io_error(error: std::io::Error) -> String {
+    error.to_string()
+}
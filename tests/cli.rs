@@ -0,0 +1,306 @@
+// Runs the `scale` binary the way the team uses it from a shell.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+const HEADER_SPEC: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/specs/polkadot_header.json");
+
+/// Runs `scale` with `args`, returning whether it succeeded, stdout and
+/// stderr.
+fn scale(args: &[&str]) -> (bool, String, String) {
+    let (success, stdout, stderr) = scale_with_stdin(args, b"");
+    (success, String::from_utf8(stdout).unwrap(), stderr)
+}
+
+/// [`scale`] with `stdin` piped in, returning stdout as bytes.
+fn scale_with_stdin(args: &[&str], stdin: &[u8]) -> (bool, Vec<u8>, String) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_scale"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to run scale");
+    child.stdin.take().unwrap().write_all(stdin).unwrap();
+    let output = child.wait_with_output().unwrap();
+    (
+        output.status.success(),
+        output.stdout,
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
+
+#[test]
+fn encode() {
+    let ok = |args: &[&str]| {
+        let (success, stdout, stderr) = scale(args);
+        assert!(success, "{:?}: {}", args, stderr);
+        stdout
+    };
+    assert_eq!(
+        ok(&["encode", "Vec<Compact<u64>>", "[1, 300]"]),
+        "0x0804b104\n"
+    );
+    assert_eq!(
+        ok(&["encode", "[u16; 4]", "[1, 2, 3, 4]"]),
+        "0x0100020003000400\n"
+    );
+    assert_eq!(
+        ok(&["encode", "Option<(u8, bool)>", r#"{"Some": [5, true]}"#]),
+        "0x010501\n"
+    );
+    assert_eq!(ok(&["encode", "Option<bool>", r#""False""#]), "0x02\n");
+    assert_eq!(ok(&["encode", "String", "hello"]), "0x1468656c6c6f\n");
+    assert_eq!(
+        ok(&[
+            "encode",
+            "u128",
+            r#""340282366920938463463374607431768211455""#
+        ]),
+        format!("0x{}\n", "ff".repeat(16))
+    );
+}
+
+#[test]
+fn decode() {
+    let (success, stdout, _) = scale(&["decode", "Option<(u8, bool)>", "0x010501"]);
+    assert!(success);
+    assert_eq!(stdout, "{\"Some\":[5,true]}\n");
+
+    let (success, stdout, _) = scale(&["decode", "(Vec<u8>, BitSeq<u8, Lsb0>)", "08 ab cd 0c 05"]);
+    assert!(success);
+    assert_eq!(stdout, "[\"0xabcd\",\"101\"]\n");
+
+    let (success, _, stderr) = scale(&["decode", "u16", "0x010203"]);
+    assert!(!success);
+    assert_eq!(stderr, "error: 1 trailing bytes after decoded value\n");
+
+    // Hostile length prefixes are rejected up front.
+    let (success, _, stderr) = scale(&["decode", "Vec<u64>", "0x03000000ff"]);
+    assert!(!success);
+    assert!(stderr.starts_with("error: Collection of"), "{}", stderr);
+}
+
+#[test]
+fn explain() {
+    let (success, stdout, _) = scale(&["explain", "Vec<u16>", "0x0c010002000300"]);
+    assert!(success);
+    assert_eq!(
+        stdout,
+        "0000  0c     Vec<u16>: compact length (mode 0b00, value 3)\n\
+         0001  01 00    Vec<u16>[0]: u16 LE (value 1)\n\
+         0003  02 00    Vec<u16>[1]: u16 LE (value 2)\n\
+         0005  03 00    Vec<u16>[2]: u16 LE (value 3)\n"
+    );
+
+    let (success, stdout, _) = scale(&["explain", "--markdown", "Compact<u32>", "0x0101"]);
+    assert!(success);
+    assert_eq!(
+        stdout,
+        "| Bytes | Hex | Field | Meaning |\n\
+         | --- | --- | --- | --- |\n\
+         | 0..2 | `01 01` | `Compact<u32>` | compact u32 (mode 0b01, value 64) |\n"
+    );
+
+    // A failure still prints what was explained before it.
+    let (success, stdout, stderr) = scale(&["explain", "(u8, u32)", "0x0701"]);
+    assert!(!success);
+    assert!(
+        stdout.starts_with("0000  07    (u8, u32).0: u8 (value 7)\n"),
+        "{}",
+        stdout
+    );
+    assert_eq!(stderr, "error: Not enough bytes. Expected 4, got 1\n");
+}
+
+#[test]
+fn usage_errors() {
+    let (success, stdout, _) = scale(&[]);
+    assert!(success);
+    assert!(stdout.starts_with("Usage:"));
+
+    let (success, _, stderr) = scale(&["decode", "Vec<u8", "00"]);
+    assert!(!success);
+    assert_eq!(
+        stderr,
+        "error: invalid type `Vec<u8`: expected `>` at offset 6\n"
+    );
+
+    let (success, _, stderr) = scale(&["decode", "u8", "0xabc"]);
+    assert!(!success);
+    assert_eq!(
+        stderr,
+        "error: `0xabc` is not an even number of hex digits\n"
+    );
+
+    // `u8::from_str_radix` on its own would take the signs.
+    let (success, _, stderr) = scale(&["decode", "u16", "0x+f+f"]);
+    assert!(!success);
+    assert_eq!(stderr, "error: `0x+f+f` is not valid hex\n");
+
+    let (success, _, stderr) = scale(&["encode", "u8", "300"]);
+    assert!(!success);
+    assert!(stderr.starts_with("error: "), "{}", stderr);
+
+    let (success, _, _) = scale(&["encode", "--markdown", "u8", "1"]);
+    assert!(!success);
+    let (success, _, _) = scale(&["transcode", "u8", "1"]);
+    assert!(!success);
+    let (success, _, stderr) = scale(&["decode", "--ndjson", "u8", "01"]);
+    assert!(!success);
+    assert_eq!(
+        stderr,
+        "error: `--binary` and `--ndjson` only apply to `to-json` and `from-json`\n"
+    );
+    let (success, _, stderr) = scale(&["to-json", "u8", "/nonexistent/input.hex"]);
+    assert!(!success);
+    assert!(
+        stderr.starts_with("error: /nonexistent/input.hex: "),
+        "{}",
+        stderr
+    );
+    let (success, _, stderr) = scale(&["decode", "--types", HEADER_SPEC, "Header", "0x00"]);
+    assert!(!success);
+    assert_eq!(stderr, "error: Not enough bytes. Expected 32, got 1\n");
+}
+
+#[test]
+fn self_referencing_specs() {
+    let dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR"));
+    let seq = dir.join("seq.json");
+    std::fs::write(
+        &seq,
+        r#"{"types": [{"id": 0, "type": {"def": {"sequence": {"type": 0}}}}]}"#,
+    )
+    .unwrap();
+    let seq = seq.to_str().unwrap();
+    let (success, _, stderr) = scale(&["decode", "--types", seq, "Loop", "0x00"]);
+    assert!(!success);
+    assert_eq!(
+        stderr,
+        format!(
+            "error: {}: contains itself with no named type in between at $.types[0]\n",
+            seq
+        )
+    );
+
+    // A named type may contain itself; values of it are bounded in depth.
+    let named = dir.join("loop.json");
+    std::fs::write(
+        &named,
+        r#"{"types": [{"id": 0, "type": {"path": ["Loop"], "def": {"tuple": [0]}}}]}"#,
+    )
+    .unwrap();
+    let named = named.to_str().unwrap();
+    for command in ["decode", "explain"] {
+        let (success, _, stderr) = scale(&[command, "--types", named, "Loop", "0x00"]);
+        assert!(!success);
+        assert!(stderr.contains("depth limit of 64"), "{}", stderr);
+    }
+}
+
+#[test]
+fn header_transcoding() {
+    let header = format!(
+        r#"{{
+  "parent_hash": "0x{}",
+  "number": 1000000,
+  "state_root": "0x{}",
+  "extrinsics_root": "0x{}",
+  "digest": {{
+    "logs": [
+      {{
+        "PreRuntime": "0x42424242"
+      }},
+      "RuntimeEnvironmentUpdated"
+    ]
+  }}
+}}
+"#,
+        "11".repeat(32),
+        "22".repeat(32),
+        "33".repeat(32)
+    );
+    let from_json = ["from-json", "--types", HEADER_SPEC, "Header"];
+    let (success, hex, stderr) = scale_with_stdin(&from_json, header.as_bytes());
+    assert!(success, "{}", stderr);
+    let hex = String::from_utf8(hex).unwrap();
+    assert!(hex.starts_with("0x1111"));
+    assert!(hex.ends_with("0803104242424204\n"), "{}", hex);
+
+    // The reverse direction, from a file, gives back the same pretty JSON.
+    let path = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("header.hex");
+    std::fs::write(&path, &hex).unwrap();
+    let (success, json, stderr) = scale(&[
+        "to-json",
+        "--types",
+        HEADER_SPEC,
+        "Header",
+        path.to_str().unwrap(),
+    ]);
+    assert!(success, "{}", stderr);
+    assert_eq!(json, header);
+
+    let from_binary = ["to-json", "--binary", "--types", HEADER_SPEC, "Header"];
+    let (_, binary, _) = scale_with_stdin(
+        &["from-json", "--binary", "--types", HEADER_SPEC, "Header"],
+        header.as_bytes(),
+    );
+    let (success, json, _) = scale_with_stdin(&from_binary, &binary);
+    assert!(success);
+    assert_eq!(String::from_utf8(json).unwrap(), header);
+}
+
+#[test]
+fn ndjson_streams() {
+    let (success, stdout, _) = scale_with_stdin(
+        &["to-json", "--ndjson", "(u8, Compact<u32>)", "-"],
+        b"0x0104\n\n07 0110\n",
+    );
+    assert!(success);
+    assert_eq!(stdout, b"[1,1]\n[7,1024]\n");
+
+    let (success, stdout, _) = scale_with_stdin(
+        &["from-json", "--ndjson", "Option<u16>"],
+        b"\"None\"\n{\"Some\": 513}\n",
+    );
+    assert!(success);
+    assert_eq!(stdout, b"0x00\n0x010102\n");
+
+    // Raw bytes have no lines: values follow each other to the end.
+    let (_, binary, _) = scale_with_stdin(
+        &["from-json", "--ndjson", "--binary", "Vec<u8>"],
+        b"[1, 2]\n[]\n[3]\n",
+    );
+    assert_eq!(binary, [0x08, 1, 2, 0x00, 0x04, 3]);
+    let (success, stdout, _) =
+        scale_with_stdin(&["to-json", "--ndjson", "--binary", "Vec<u8>"], &binary);
+    assert!(success);
+    assert_eq!(stdout, b"\"0x0102\"\n\"0x\"\n\"0x03\"\n");
+
+    // Lines before a bad one are still written.
+    let (success, stdout, stderr) =
+        scale_with_stdin(&["to-json", "--ndjson", "u16"], b"0x0100\n0x01\n0x0200\n");
+    assert!(!success);
+    assert_eq!(stdout, b"1\n");
+    assert_eq!(
+        stderr,
+        "error: line 2: Not enough bytes. Expected 2, got 1\n"
+    );
+
+    let (success, _, stderr) =
+        scale_with_stdin(&["to-json", "--ndjson", "--binary", "()"], b"\x00");
+    assert!(!success);
+    assert_eq!(
+        stderr,
+        "error: values of this type take no bytes to stream\n"
+    );
+
+    let (success, _, stderr) = scale_with_stdin(&["from-json", "--ndjson", "u8"], b"1\nnope\n");
+    assert!(!success);
+    assert!(
+        stderr.starts_with("error: line 2: invalid JSON"),
+        "{}",
+        stderr
+    );
+}